@@ -0,0 +1,62 @@
+;; Calls the contract whose address is stored in the first 8 bytes of the input. The
+;; next 4 bytes are the flags passed to `seal_call` and the rest of the input is passed
+;; on to the callee. The contract returns immediately if the input is empty.
+;;
+;; If the call fails its return code is returned as output. Otherwise the output of the
+;; callee is passed on.
+(module
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal1" "seal_call" (func $seal_call (param i32 i32 i32 i64 i32 i32 i32 i32 i32 i32) (result i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; [0, 4) size of the input buffer
+	(data (i32.const 0) "\80")
+
+	;; [4, 132) input buffer
+
+	;; [132, 136) size of the output buffer
+	(data (i32.const 132) "\80")
+
+	;; [136, 264) output buffer
+
+	;; [264, 268) here we store the return code of the call
+
+	;; [268, 276) value to transfer: 0
+
+	(func (export "deploy"))
+
+	(func (export "call")
+		(call $seal_input (i32.const 4) (i32.const 0))
+
+		;; Being called with an empty input means we were reentered.
+		(if (i32.eqz (i32.load (i32.const 0)))
+			(then
+				(call $seal_return (i32.const 0) (i32.const 0) (i32.const 0))
+			)
+		)
+
+		(i32.store
+			(i32.const 264)
+			(call $seal_call
+				(i32.load (i32.const 12)) ;; Flags
+				(i32.const 4) ;; Pointer to "callee" address.
+				(i32.const 8) ;; Length of "callee" address.
+				(i64.const 0) ;; How much gas to devote for the execution. 0 = all.
+				(i32.const 268) ;; Pointer to the buffer with value to transfer
+				(i32.const 8) ;; Length of the buffer with value to transfer.
+				(i32.const 16) ;; Pointer to input data buffer address
+				(i32.sub (i32.load (i32.const 0)) (i32.const 12)) ;; Length of input data buffer
+				(i32.const 136) ;; Pointer to the output buffer
+				(i32.const 132) ;; Pointer to the length of the output buffer
+			)
+		)
+
+		(if (i32.load (i32.const 264))
+			(then
+				(call $seal_return (i32.const 0) (i32.const 264) (i32.const 4))
+			)
+		)
+		(call $seal_return (i32.const 0) (i32.const 136) (i32.load (i32.const 132)))
+	)
+)
@@ -0,0 +1,77 @@
+(module
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+
+	(import "seal0" "seal_sr25519_verify" (func $seal_sr25519_verify (param i32 i32 i32 i32) (result i32)))
+	(import "seal0" "seal_ed25519_verify" (func $seal_ed25519_verify (param i32 i32 i32 i32) (result i32)))
+
+	(import "env" "memory" (memory 1 1))
+
+	(type $verify_fn_sig (func (param i32 i32 i32 i32) (result i32)))
+	(table 2 funcref)
+	(elem (i32.const 0)
+		$seal_sr25519_verify
+		$seal_ed25519_verify
+	)
+
+	;; Not in use by the tests besides instantiating the contract.
+	(func (export "deploy"))
+
+	;; Called by the tests.
+	;;
+	;; The `call` function expects data in a certain format in the input buffer.
+	;;
+	;; 1. The first byte encodes an identifier for the signature scheme
+	;;    under test. (*)
+	;; 2. The next 64 bytes are the signature.
+	;; 3. The next 32 bytes are the public key.
+	;; 4. The rest is the signed message.
+	;;
+	;; The `call` function then verifies the signature with the chosen scheme and
+	;; returns the `ReturnCode` of the verification in the output buffer.
+	;;
+	;; (*) The possible values for the signature scheme identifiers can be found below:
+	;;
+	;; | value | Scheme  |
+	;; |-------|---------|
+	;; |     0 | sr25519 |
+	;; |     1 | ed25519 |
+	;; -------------------
+	(func (export "call")
+		(local $input_len i32)
+		(local $chosen_verify_fn i32)
+
+		;; [0, 4) size of the input buffer
+		(i32.store (i32.const 0) (i32.const 1024))
+		;; [8, 1032) input buffer
+		(call $seal_input (i32.const 8) (i32.const 0))
+		(local.set $input_len (i32.load (i32.const 0)))
+		(if (i32.lt_u (local.get $input_len) (i32.const 97))
+			;; The input is too short to contain the signature and the public key.
+			(unreachable)
+		)
+		(local.set $chosen_verify_fn (i32.load8_u (i32.const 8)))
+		(if (i32.gt_u (local.get $chosen_verify_fn) (i32.const 1))
+			;; We check that the chosen scheme identifier is within bounds: [0,1]
+			(unreachable)
+		)
+
+		;; [4, 8) return code of the verification
+		(i32.store
+			(i32.const 4)
+			(call_indirect (type $verify_fn_sig)
+				(i32.const 9) ;; Pointer to the signature.
+				(i32.const 73) ;; Pointer to the public key.
+				(i32.const 105) ;; Pointer to the message.
+				(i32.sub (local.get $input_len) (i32.const 97)) ;; Length of the message.
+				(local.get $chosen_verify_fn) ;; Which signature scheme to verify.
+			)
+		)
+		(call $seal_return
+			(i32.const 0)
+			(i32.const 4) ;; Linear memory location of the return code.
+			(i32.const 4) ;; Size of the return code.
+		)
+		(unreachable)
+	)
+)
@@ -0,0 +1,38 @@
+;; This module returns the first key of its storage that starts with the input
+(module
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal0" "seal_storage_next_key" (func $seal_storage_next_key (param i32 i32 i32 i32 i32 i32) (result i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; [0, 4) size of the input buffer
+	(data (i32.const 0) "\80")
+
+	;; [4, 132) input buffer holding the prefix
+
+	;; [132, 136) size of the output buffer
+	(data (i32.const 132) "\80")
+
+	;; [136, 264) output buffer holding the next key
+
+	(func (export "deploy"))
+
+	(func (export "call")
+		(call $seal_input (i32.const 4) (i32.const 0))
+		(if
+			(i32.eqz
+				(call $seal_storage_next_key
+					(i32.const 4)				;; Pointer to the prefix
+					(i32.load (i32.const 0))	;; Size of the prefix
+					(i32.const -1)				;; Start at the first key
+					(i32.const 0)
+					(i32.const 136)				;; Pointer to the output buffer
+					(i32.const 132)				;; Pointer to the output buffer size
+				)
+			)
+			(then
+				(call $seal_return (i32.const 0) (i32.const 136) (i32.load (i32.const 132)))
+			)
+		)
+	)
+)
@@ -21,11 +21,33 @@
 
 use crate::*;
 use crate::Module as Contracts;
+use crate::exec::StorageKey;
+
+use codec::Encode;
 
 use frame_benchmarking::{benchmarks, account};
 use frame_system::{Module as System, RawOrigin};
-use parity_wasm::elements::FuncBody;
-use sp_runtime::traits::Hash;
+use parity_wasm::elements::{BlockType, FuncBody, Instruction, ValueType};
+use sp_io::hashing::blake2_256;
+use sp_runtime::traits::{Bounded, Hash};
+use sp_trie::{generate_trie_proof, Layout, MemoryDB, TrieDBMut, TrieMut};
+
+/// A valid signature of `ECDSA_MESSAGE` consisting of `r`, `s` and the recovery id.
+const ECDSA_SIGNATURE: [u8; 65] = [
+	0x17, 0xf5, 0x32, 0x89, 0xea, 0xc9, 0x61, 0xe5, 0xad, 0xc8, 0x58, 0xd3,
+	0xca, 0x50, 0xda, 0xb0, 0x56, 0xdd, 0xca, 0x7a, 0x1a, 0x90, 0x6c, 0x08,
+	0x15, 0xa0, 0x36, 0x93, 0x12, 0xd1, 0xaa, 0x49, 0x40, 0x6f, 0x34, 0x24,
+	0x68, 0xa3, 0x73, 0xa0, 0x62, 0x46, 0xe2, 0x43, 0x00, 0x9b, 0x6b, 0xdc,
+	0x49, 0x20, 0x59, 0x37, 0x4e, 0x36, 0x3e, 0x0e, 0xef, 0x96, 0x78, 0xd0,
+	0x8b, 0x03, 0xa7, 0xcd, 0x01,
+];
+
+/// The message hash signed by `ECDSA_SIGNATURE`: `blake2_256("Hello world")`.
+const ECDSA_MESSAGE: [u8; 32] = [
+	0xa2, 0x1c, 0xf4, 0xb3, 0x60, 0x4c, 0xf4, 0xb2, 0xbc, 0x53, 0xe6, 0xf8,
+	0x8f, 0x6a, 0x4d, 0x75, 0xef, 0x5f, 0xf4, 0xab, 0x41, 0x5f, 0x3e, 0x99,
+	0xae, 0xa6, 0xb6, 0x1c, 0x82, 0x49, 0xc4, 0xd0,
+];
 
 macro_rules! load_module {
 	($name:expr) => {{
@@ -103,11 +125,335 @@ fn expanded_contract<T: Trait>(target_bytes: u32) -> (Vec<u8>, <T::Hashing as Ha
 	contract_with_call_body::<T>(FuncBody::new(Vec::new(), instructions))
 }
 
+/// The maximum number of component steps of the `instr_*` benchmarks.
+const INSTR_BENCHMARK_BATCHES: u32 = 50;
+
+/// Creates a contract whose `call` function executes `instructions` `repetitions` times.
+///
+/// In order to be able to benchmark every class of instructions the module imports a memory
+/// of one page, has a mutable `i64` global, the `call` function has an `i64` local and the
+/// table contains a function doing nothing (idx 2) at index 0.
+fn instruction_contract<T: Trait>(
+	instructions: &[Instruction],
+	repetitions: u32,
+) -> (Vec<u8>, <T::Hashing as Hash>::Output) {
+	use parity_wasm::elements::{Instructions, Instruction::End, Local, ValueType};
+	let body = FuncBody::new(
+		vec![Local::new(1, ValueType::I64)],
+		Instructions::new(
+			instructions
+				.iter()
+				.cycle()
+				.take(instructions.len() * repetitions as usize)
+				.cloned()
+				.chain(sp_std::iter::once(End))
+				.collect()
+		),
+	);
+	let contract = parity_wasm::builder::ModuleBuilder::new()
+		.import().module("env").field("memory").external().memory(1, Some(1)).build()
+		.global().with_type(ValueType::I64).mutable().init_expr(Instruction::I64Const(0)).build()
+		// deploy function (idx 0)
+		.function()
+			.signature().with_params(vec![]).with_return_type(None).build()
+			.body().with_instructions(Instructions::new(vec![End])).build()
+			.build()
+		// call function (idx 1)
+		.function()
+			.signature().with_params(vec![]).with_return_type(None).build()
+			.with_body(body)
+			.build()
+		// function doing nothing (idx 2)
+		.function()
+			.signature().with_params(vec![]).with_return_type(None).build()
+			.body().with_instructions(Instructions::new(vec![End])).build()
+			.build()
+		.table().with_min(1).with_max(Some(1)).with_element(0, vec![2]).build()
+		.export().field("deploy").internal().func(0).build()
+		.export().field("call").internal().func(1).build()
+		.build();
+	let bytes = contract.to_bytes().unwrap();
+	let hash = T::Hashing::hash(&bytes);
+	(bytes, hash)
+}
+
+/// Stores and instantiates the given contract on behalf of `caller`.
+///
+/// Returns the address of the contract.
+fn instantiate_contract<T: Trait>(
+	caller: &T::AccountId,
+	(binary, hash): (Vec<u8>, <T::Hashing as Hash>::Output),
+	endowment: BalanceOf<T>,
+	data: Vec<u8>,
+) -> T::AccountId {
+	let addr = T::DetermineContractAddress::contract_address_for(&hash, &data, caller);
+	// The code might have already been stored by an earlier instantiation.
+	let _ = Contracts::<T>::put_code(RawOrigin::Signed(caller.clone()).into(), binary);
+	Contracts::<T>::instantiate(
+		RawOrigin::Signed(caller.clone()).into(),
+		endowment,
+		Weight::max_value(),
+		hash,
+		data,
+	).unwrap();
+	addr
+}
+
+/// Stores and instantiates a contract executing `instructions` `r` batches of times.
+///
+/// Returns the funded caller and the address of the contract.
+fn instantiate_instruction_contract<T: Trait>(
+	instructions: &[Instruction],
+	r: u32,
+) -> (T::AccountId, T::AccountId) {
+	let caller = create_funded_user::<T>("caller", 0);
+	let addr = instantiate_contract::<T>(
+		&caller,
+		instruction_contract::<T>(instructions, r * INSTR_BENCHMARK_BATCH_SIZE),
+		Config::<T>::subsistence_threshold_uncached(),
+		vec![],
+	);
+	(caller, addr)
+}
+
+/// The maximum number of component steps of the `seal_*` benchmarks.
+const API_BENCHMARK_BATCHES: u32 = 20;
+
+/// A host function imported from the `seal0` module by the `seal_*` benchmarks.
+struct ImportedFunction {
+	name: &'static str,
+	params: Vec<ValueType>,
+	return_type: Option<ValueType>,
+}
+
+/// Creates a contract whose `call` function executes `body`.
+///
+/// The module imports `func` as function with index 0 and a memory of one page which is
+/// initialized with the supplied `(offset, bytes)` data segments.
+fn host_fn_contract<T: Trait>(
+	func: ImportedFunction,
+	data_segments: Vec<(u32, Vec<u8>)>,
+	body: Vec<Instruction>,
+) -> (Vec<u8>, <T::Hashing as Hash>::Output) {
+	use parity_wasm::elements::{Instructions, Instruction::End};
+	let mut contract = parity_wasm::builder::ModuleBuilder::new()
+		.import().module("env").field("memory").external().memory(1, Some(1)).build();
+	let signature = contract.push_signature(
+		parity_wasm::builder::signature()
+			.with_params(func.params)
+			.with_return_type(func.return_type)
+			.build_sig()
+	);
+	let mut contract = contract
+		.import().module("seal0").field(func.name).external().func(signature).build()
+		// deploy function (idx 1)
+		.function()
+			.signature().with_params(vec![]).with_return_type(None).build()
+			.body().with_instructions(Instructions::new(vec![End])).build()
+			.build()
+		// call function (idx 2)
+		.function()
+			.signature().with_params(vec![]).with_return_type(None).build()
+			.body().with_instructions(Instructions::new(
+				body.into_iter().chain(sp_std::iter::once(End)).collect()
+			)).build()
+			.build()
+		.export().field("deploy").internal().func(1).build()
+		.export().field("call").internal().func(2).build();
+	for (offset, value) in data_segments {
+		contract = contract.data()
+			.offset(Instruction::I32Const(offset as i32))
+			.value(value)
+			.build();
+	}
+	let bytes = contract.build().to_bytes().unwrap();
+	let hash = T::Hashing::hash(&bytes);
+	(bytes, hash)
+}
+
+/// Repeats `instructions` `repetitions` times.
+fn repeated(instructions: &[Instruction], repetitions: u32) -> Vec<Instruction> {
+	instructions
+		.iter()
+		.cycle()
+		.take(instructions.len() * repetitions as usize)
+		.cloned()
+		.collect()
+}
+
+/// Stores and instantiates a host function contract with a large endowment.
+///
+/// Returns the funded caller and the address of the contract.
+fn instantiate_host_fn_contract<T: Trait>(
+	func: ImportedFunction,
+	data_segments: Vec<(u32, Vec<u8>)>,
+	body: Vec<Instruction>,
+) -> (T::AccountId, T::AccountId) {
+	let caller = create_funded_user::<T>("caller", 0);
+	let addr = instantiate_contract::<T>(
+		&caller,
+		host_fn_contract::<T>(func, data_segments, body),
+		funding::<T>() / 2.into(),
+		vec![],
+	);
+	(caller, addr)
+}
+
+/// Instantiates a contract calling the getter `name` `r` batches of times.
+///
+/// The output buffer starts at offset 4. Its length is stored at offset 0.
+fn instantiate_getter_contract<T: Trait>(name: &'static str, r: u32)
+	-> (T::AccountId, T::AccountId)
+{
+	instantiate_host_fn_contract::<T>(
+		ImportedFunction {
+			name,
+			params: vec![ValueType::I32, ValueType::I32],
+			return_type: None,
+		},
+		vec![(0, 128u32.encode())],
+		repeated(&[
+			Instruction::I32Const(4),
+			Instruction::I32Const(0),
+			Instruction::Call(0),
+		], r * API_BENCHMARK_BATCH_SIZE),
+	)
+}
+
+/// Instantiates a contract calling the hash function `name` `r` times on `input_len` bytes.
+fn instantiate_hash_contract<T: Trait>(name: &'static str, r: u32, input_len: u32)
+	-> (T::AccountId, T::AccountId)
+{
+	instantiate_host_fn_contract::<T>(
+		ImportedFunction {
+			name,
+			params: vec![ValueType::I32, ValueType::I32, ValueType::I32],
+			return_type: None,
+		},
+		vec![],
+		repeated(&[
+			Instruction::I32Const(0),
+			Instruction::I32Const(input_len as i32),
+			Instruction::I32Const(0),
+			Instruction::Call(0),
+		], r),
+	)
+}
+
+/// The signature schemes that can be verified by a contract.
+enum SignatureScheme {
+	Sr25519,
+	Ed25519,
+}
+
+/// Instantiates a contract verifying a signature of a `message_len` bytes long message
+/// with the host function `name` `r` times.
+///
+/// The keys are generated in the keystore of the benchmark environment.
+fn instantiate_verify_contract<T: Trait>(
+	name: &'static str,
+	scheme: SignatureScheme,
+	r: u32,
+	message_len: u32,
+) -> (T::AccountId, T::AccountId) {
+	let key_type = sp_core::crypto::KeyTypeId(*b"code");
+	let message = vec![42u8; message_len as usize];
+	let (signature, pub_key) = match scheme {
+		SignatureScheme::Sr25519 => {
+			let pub_key = sp_io::crypto::sr25519_generate(key_type, None);
+			let signature = sp_io::crypto::sr25519_sign(key_type, &pub_key, &message).unwrap();
+			(signature.0.to_vec(), pub_key.0.to_vec())
+		}
+		SignatureScheme::Ed25519 => {
+			let pub_key = sp_io::crypto::ed25519_generate(key_type, None);
+			let signature = sp_io::crypto::ed25519_sign(key_type, &pub_key, &message).unwrap();
+			(signature.0.to_vec(), pub_key.0.to_vec())
+		}
+	};
+	instantiate_host_fn_contract::<T>(
+		ImportedFunction {
+			name,
+			params: vec![ValueType::I32; 4],
+			return_type: Some(ValueType::I32),
+		},
+		vec![(0, signature), (64, pub_key), (96, message)],
+		repeated(&[
+			Instruction::I32Const(0),
+			Instruction::I32Const(64),
+			Instruction::I32Const(96),
+			Instruction::I32Const(message_len as i32),
+			Instruction::Call(0),
+			Instruction::Drop,
+		], r),
+	)
+}
+
+/// Returns a distinct storage key for every `n`.
+fn storage_key(n: u32) -> StorageKey {
+	let mut key = [0u8; 32];
+	key[..4].copy_from_slice(&n.to_le_bytes());
+	key
+}
+
+/// Writes a value of `value_len` bytes under each of `keys` into the storage of `addr`.
+fn populate_storage<T: Trait>(addr: &T::AccountId, keys: &[StorageKey], value_len: u32) {
+	let trie_id = ContractInfoOf::<T>::get(addr).and_then(|c| c.get_alive()).unwrap().trie_id;
+	let storage_mode = crate::storage::storage_mode(&trie_id);
+	for key in keys {
+		crate::storage::write_contract_storage::<T>(
+			addr,
+			&trie_id,
+			storage_mode,
+			key,
+			Some(vec![42u8; value_len as usize]),
+		).unwrap();
+	}
+}
+
 fn advance_block<T: Trait>(num: <T as frame_system::Trait>::BlockNumber) {
 	let now = System::<T>::block_number();
 	System::<T>::set_block_number(now + num);
 }
 
+/// Instantiates the dummy contract with `n` storage items of 32 bytes and one additional
+/// item of `k` kilobytes and lets it be evicted.
+///
+/// Returns the caller, the address of the tombstone, the code hash of the dummy contract
+/// and the storage items of the evicted contract.
+fn evicted_contract_with_storage<T: Trait>(n: u32, k: u32)
+	-> (T::AccountId, T::AccountId, CodeHash<T>, Vec<(StorageKey, Vec<u8>)>)
+{
+	let caller = create_funded_user::<T>("caller", 0);
+	let (binary, hash) = load_module!("dummy");
+	let addr = instantiate_contract::<T>(
+		&caller,
+		(binary, hash),
+		Config::<T>::subsistence_threshold_uncached(),
+		vec![],
+	);
+	let mut items = (0 .. n)
+		.map(|i| (storage_key(i), vec![42u8; 32]))
+		.collect::<Vec<_>>();
+	items.push((storage_key(n), vec![42u8; (k * 1024) as usize]));
+	let trie_id = ContractInfoOf::<T>::get(&addr).and_then(|c| c.get_alive()).unwrap().trie_id;
+	for (key, value) in items.iter() {
+		crate::storage::write_contract_storage::<T>(
+			&addr,
+			&trie_id,
+			StorageMode::Hashed,
+			key,
+			Some(value.clone()),
+		).unwrap();
+	}
+
+	advance_block::<T>(<T as Trait>::SignedClaimHandicap::get() + 1.into());
+	Contracts::<T>::claim_surcharge(RawOrigin::Signed(caller.clone()).into(), addr.clone(), None)
+		.unwrap();
+	ContractInfoOf::<T>::get(&addr).and_then(|c| c.get_tombstone()).unwrap();
+
+	(caller, addr, hash, items)
+}
+
 benchmarks! {
 	_ {
 	}
@@ -120,14 +466,37 @@ benchmarks! {
 		};
 	}: _(RawOrigin::Root, schedule)
 
+	// Scheduling an update for a future block is the more expensive path as it needs to
+	// write the pending schedule.
+	update_schedule_at {
+		let schedule = Schedule {
+			version: 1,
+			.. Default::default()
+		};
+		let activate_at = System::<T>::block_number() + 1u32.into();
+	}: _(RawOrigin::Root, schedule, activate_at, false)
+
 	// This constructs a contract that is maximal expensive to instrument.
 	// It creates a maximum number of metering blocks per byte.
 	put_code {
-		let n in 0 .. Contracts::<T>::current_schedule().max_code_size;
+		let n in 0 .. Contracts::<T>::current_schedule().max_code_size / 1024;
 		let caller = create_funded_user::<T>("caller", 0);
-		let (binary, hash) = expanded_contract::<T>(n);
+		let (binary, hash) = expanded_contract::<T>(n * 1024);
 	}: _(RawOrigin::Signed(caller), binary)
 
+	// The same contract as in `put_code` is re-instrumented after the schedule was bumped.
+	reinstrument_code {
+		let n in 0 .. Contracts::<T>::current_schedule().max_code_size / 1024;
+		let caller = create_funded_user::<T>("caller", 0);
+		let (binary, hash) = expanded_contract::<T>(n * 1024);
+		Contracts::<T>::put_code(RawOrigin::Signed(caller.clone()).into(), binary)?;
+		let schedule = Contracts::<T>::current_schedule();
+		Contracts::<T>::update_schedule(RawOrigin::Root.into(), Schedule {
+			version: schedule.version + 1,
+			.. schedule
+		})?;
+	}: _(RawOrigin::Signed(caller), hash)
+
 	// Instantiate uses a dummy contract constructor to measure the overhead of the instantiate.
 	// The size of the data has no influence on the costs of this extrinsic as long as the contract
 	// won't call `seal_input` in its constructor to copy the data to contract memory.
@@ -226,6 +595,1628 @@ benchmarks! {
 			T::Currency::free_balance(&caller),
 		);
 	}
+
+	// Deposits into a contract that owes rent for one block and raises its rent allowance.
+	deposit_rent {
+		let caller = create_funded_user::<T>("caller", 0);
+		let addr = instantiate_contract::<T>(
+			&caller,
+			load_module!("dummy"),
+			funding::<T>() / 2.into(),
+			vec![],
+		);
+		advance_block::<T>(1.into());
+		let amount = T::Currency::minimum_balance() * 100.into();
+	}: _(RawOrigin::Signed(caller), T::Lookup::unlookup(addr.clone()), amount, true)
+	verify {
+		ContractInfoOf::<T>::get(&addr).and_then(|c| c.get_alive()).unwrap();
+	}
+
+	// Restores a tombstone whose storage consists of `n` items of 32 bytes and one
+	// item of `k` kilobytes.
+	restore_contract {
+		let n in 0 .. API_BENCHMARK_BATCHES * API_BENCHMARK_BATCH_SIZE;
+		let k in 0 .. T::MaxValueSize::get() / 1024;
+		let (caller, addr, hash, items) = evicted_contract_with_storage::<T>(n, k);
+	}: _(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr.clone()),
+			hash,
+			BalanceOf::<T>::max_value(),
+			items
+		)
+	verify {
+		ContractInfoOf::<T>::get(&addr).and_then(|c| c.get_alive()).unwrap();
+	}
+
+	// Same as `restore_contract` but every item is verified by a proof. Costs for proofs
+	// which are larger than needed are charged on top of this per byte of the proof.
+	restore_contract_with_proof {
+		let n in 0 .. API_BENCHMARK_BATCHES * API_BENCHMARK_BATCH_SIZE;
+		let k in 0 .. T::MaxValueSize::get() / 1024;
+		let (_, addr, hash, items) = evicted_contract_with_storage::<T>(n, k);
+		let mut db = MemoryDB::<T::Hashing>::default();
+		let mut root = Default::default();
+		{
+			let mut trie = TrieDBMut::<Layout<T::Hashing>>::new(&mut db, &mut root);
+			for (key, value) in items.iter() {
+				trie.insert(&blake2_256(key), value).unwrap();
+			}
+		}
+		let keys = items.iter().map(|(key, _)| blake2_256(key)).collect::<Vec<_>>();
+		let proof = generate_trie_proof::<Layout<T::Hashing>, _, _, _>(&db, root, &keys)
+			.unwrap();
+	}: _(
+			RawOrigin::Root,
+			T::Lookup::unlookup(addr.clone()),
+			hash,
+			BalanceOf::<T>::max_value(),
+			items,
+			proof
+		)
+	verify {
+		ContractInfoOf::<T>::get(&addr).and_then(|c| c.get_alive()).unwrap();
+	}
+
+	// Imports `n` storage items of 32 bytes and one additional item of `k` kilobytes
+	// into an empty contract. The items are imported in `StorageMode::Unhashed` so that
+	// the storage mode of the contract needs to be written, too.
+	import_storage {
+		let n in 0 .. API_BENCHMARK_BATCHES * API_BENCHMARK_BATCH_SIZE;
+		let k in 0 .. T::MaxValueSize::get() / 1024;
+		let caller = create_funded_user::<T>("caller", 0);
+		let addr = instantiate_contract::<T>(
+			&caller,
+			load_module!("dummy"),
+			Config::<T>::subsistence_threshold_uncached(),
+			vec![],
+		);
+		let mut items = (0 .. n)
+			.map(|i| (storage_key(i).to_vec(), vec![42u8; 32]))
+			.collect::<Vec<_>>();
+		items.push((storage_key(n).to_vec(), vec![42u8; (k * 1024) as usize]));
+		let mut db = MemoryDB::<T::Hashing>::default();
+		let mut root = Default::default();
+		{
+			let mut trie = TrieDBMut::<Layout<T::Hashing>>::new(&mut db, &mut root);
+			for (key, value) in items.iter() {
+				trie.insert(key, value).unwrap();
+			}
+		}
+		let expected_root = root.as_ref().to_vec();
+	}: _(
+			RawOrigin::Root,
+			T::Lookup::unlookup(addr.clone()),
+			StorageMode::Unhashed,
+			items,
+			expected_root
+		)
+	verify {
+		let info = ContractInfoOf::<T>::get(&addr).and_then(|c| c.get_alive()).unwrap();
+		assert_eq!(info.total_pair_count, n + 1);
+		assert_eq!(<StorageModeOf>::get(&info.trie_id), StorageMode::Unhashed);
+	}
+
+	// The `instr_*` benchmarks measure the costs of the instruction classes as charged by
+	// the gas instrumentation (see `InstructionWeights`). Every benchmark executes the
+	// benchmarked instruction together with the instructions needed to balance the stack.
+	// The cost of those helper instructions needs to be subtracted from the result.
+
+	// Executes `i64.const` followed by a `drop` (`drop` is a `nop` class instruction).
+	instr_const {
+		let r in 0 .. INSTR_BENCHMARK_BATCHES;
+		let (caller, addr) = instantiate_instruction_contract::<T>(&[
+			Instruction::I64Const(0x7fff_ffff_3f3f_3f3f),
+			Instruction::Drop,
+		], r);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// Executes `get_local` and `set_local` on the same local.
+	instr_local {
+		let r in 0 .. INSTR_BENCHMARK_BATCHES;
+		let (caller, addr) = instantiate_instruction_contract::<T>(&[
+			Instruction::GetLocal(0),
+			Instruction::SetLocal(0),
+		], r);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// Executes `get_global` and `set_global` on the same global.
+	instr_global {
+		let r in 0 .. INSTR_BENCHMARK_BATCHES;
+		let (caller, addr) = instantiate_instruction_contract::<T>(&[
+			Instruction::GetGlobal(0),
+			Instruction::SetGlobal(0),
+		], r);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// Executes `i64.load` from the start of the memory.
+	instr_load {
+		let r in 0 .. INSTR_BENCHMARK_BATCHES;
+		let (caller, addr) = instantiate_instruction_contract::<T>(&[
+			Instruction::I32Const(0),
+			Instruction::I64Load(3, 0),
+			Instruction::Drop,
+		], r);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// Executes `i64.store` to the start of the memory.
+	instr_store {
+		let r in 0 .. INSTR_BENCHMARK_BATCHES;
+		let (caller, addr) = instantiate_instruction_contract::<T>(&[
+			Instruction::I32Const(0),
+			Instruction::I64Const(42),
+			Instruction::I64Store(3, 0),
+		], r);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// Executes a `block` which is left by a `br`.
+	instr_branch {
+		let r in 0 .. INSTR_BENCHMARK_BATCHES;
+		let (caller, addr) = instantiate_instruction_contract::<T>(&[
+			Instruction::Block(BlockType::NoResult),
+			Instruction::Br(0),
+			Instruction::End,
+		], r);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// Executes a `call` to a function doing nothing.
+	instr_call {
+		let r in 0 .. INSTR_BENCHMARK_BATCHES;
+		let (caller, addr) = instantiate_instruction_contract::<T>(&[
+			Instruction::Call(2),
+		], r);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// Executes a `call_indirect` to a function doing nothing.
+	instr_call_indirect {
+		let r in 0 .. INSTR_BENCHMARK_BATCHES;
+		let (caller, addr) = instantiate_instruction_contract::<T>(&[
+			Instruction::I32Const(0),
+			Instruction::CallIndirect(0, 0),
+		], r);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// Executes `i64.lt_s`.
+	instr_integer_comparison {
+		let r in 0 .. INSTR_BENCHMARK_BATCHES;
+		let (caller, addr) = instantiate_instruction_contract::<T>(&[
+			Instruction::I64Const(1),
+			Instruction::I64Const(2),
+			Instruction::I64LtS,
+			Instruction::Drop,
+		], r);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// Executes `i64.and`.
+	instr_bit {
+		let r in 0 .. INSTR_BENCHMARK_BATCHES;
+		let (caller, addr) = instantiate_instruction_contract::<T>(&[
+			Instruction::I64Const(1),
+			Instruction::I64Const(2),
+			Instruction::I64And,
+			Instruction::Drop,
+		], r);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// Executes `i64.add`.
+	instr_add {
+		let r in 0 .. INSTR_BENCHMARK_BATCHES;
+		let (caller, addr) = instantiate_instruction_contract::<T>(&[
+			Instruction::I64Const(1),
+			Instruction::I64Const(2),
+			Instruction::I64Add,
+			Instruction::Drop,
+		], r);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// Executes `i64.mul`.
+	instr_mul {
+		let r in 0 .. INSTR_BENCHMARK_BATCHES;
+		let (caller, addr) = instantiate_instruction_contract::<T>(&[
+			Instruction::I64Const(1),
+			Instruction::I64Const(2),
+			Instruction::I64Mul,
+			Instruction::Drop,
+		], r);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// Executes `i64.div_s`.
+	instr_div {
+		let r in 0 .. INSTR_BENCHMARK_BATCHES;
+		let (caller, addr) = instantiate_instruction_contract::<T>(&[
+			Instruction::I64Const(1),
+			Instruction::I64Const(2),
+			Instruction::I64DivS,
+			Instruction::Drop,
+		], r);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// Executes `i32.wrap/i64`.
+	instr_conversion {
+		let r in 0 .. INSTR_BENCHMARK_BATCHES;
+		let (caller, addr) = instantiate_instruction_contract::<T>(&[
+			Instruction::I64Const(1),
+			Instruction::I32WrapI64,
+			Instruction::Drop,
+		], r);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// Executes `nop`.
+	instr_nop {
+		let r in 0 .. INSTR_BENCHMARK_BATCHES;
+		let (caller, addr) = instantiate_instruction_contract::<T>(&[
+			Instruction::Nop,
+		], r);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// Executes `current_memory`.
+	instr_current_memory {
+		let r in 0 .. INSTR_BENCHMARK_BATCHES;
+		let (caller, addr) = instantiate_instruction_contract::<T>(&[
+			Instruction::CurrentMemory(0),
+			Instruction::Drop,
+		], r);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// Executes `grow_memory` by zero pages. The per page cost is benchmarked separately.
+	instr_grow_memory {
+		let r in 0 .. INSTR_BENCHMARK_BATCHES;
+		let (caller, addr) = instantiate_instruction_contract::<T>(&[
+			Instruction::I32Const(0),
+			Instruction::GrowMemory(0),
+			Instruction::Drop,
+		], r);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// The `seal_*` benchmarks measure the costs of the host functions as charged by
+	// `HostFnWeights`. The contracts call the benchmarked function from their `call` export
+	// so the overhead of the `call` dispatchable needs to be subtracted from the result.
+	// Functions that are charged per byte are benchmarked in separate `*_per_kb` benchmarks.
+
+	seal_caller {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let (caller, addr) = instantiate_getter_contract::<T>("seal_caller", r);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	seal_address {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let (caller, addr) = instantiate_getter_contract::<T>("seal_address", r);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	seal_origin {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let (caller, addr) = instantiate_getter_contract::<T>("seal_origin", r);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	seal_caller_is_origin {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let (caller, addr) = instantiate_host_fn_contract::<T>(
+			ImportedFunction {
+				name: "seal_caller_is_origin",
+				params: vec![],
+				return_type: Some(ValueType::I32),
+			},
+			vec![],
+			repeated(&[
+				Instruction::Call(0),
+				Instruction::Drop,
+			], r * API_BENCHMARK_BATCH_SIZE),
+		);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// Queries an alive contract so that the lookup of its contract info succeeds.
+	seal_is_contract {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let caller = create_funded_user::<T>("caller", 0);
+		let account = instantiate_contract::<T>(
+			&caller,
+			load_module!("dummy"),
+			Config::<T>::subsistence_threshold_uncached(),
+			vec![],
+		).encode();
+		let account_len = account.len() as i32;
+		let addr = instantiate_contract::<T>(
+			&caller,
+			host_fn_contract::<T>(
+				ImportedFunction {
+					name: "seal_is_contract",
+					params: vec![ValueType::I32, ValueType::I32],
+					return_type: Some(ValueType::I32),
+				},
+				vec![(0, account)],
+				repeated(&[
+					Instruction::I32Const(0),
+					Instruction::I32Const(account_len),
+					Instruction::Call(0),
+					Instruction::Drop,
+				], r * API_BENCHMARK_BATCH_SIZE),
+			),
+			funding::<T>() / 2.into(),
+			vec![],
+		);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// The output buffer starts after the queried account. Its length is stored at offset 0.
+	seal_code_hash {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let caller = create_funded_user::<T>("caller", 0);
+		let account = instantiate_contract::<T>(
+			&caller,
+			load_module!("dummy"),
+			Config::<T>::subsistence_threshold_uncached(),
+			vec![],
+		).encode();
+		let account_len = account.len() as i32;
+		let addr = instantiate_contract::<T>(
+			&caller,
+			host_fn_contract::<T>(
+				ImportedFunction {
+					name: "seal_code_hash",
+					params: vec![ValueType::I32; 4],
+					return_type: Some(ValueType::I32),
+				},
+				vec![(0, 128u32.encode()), (4, account)],
+				repeated(&[
+					Instruction::I32Const(4),
+					Instruction::I32Const(account_len),
+					Instruction::I32Const(4 + account_len),
+					Instruction::I32Const(0),
+					Instruction::Call(0),
+					Instruction::Drop,
+				], r * API_BENCHMARK_BATCH_SIZE),
+			),
+			funding::<T>() / 2.into(),
+			vec![],
+		);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	seal_own_code_hash {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let (caller, addr) = instantiate_getter_contract::<T>("seal_own_code_hash", r);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	seal_gas_left {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let (caller, addr) = instantiate_getter_contract::<T>("seal_gas_left", r);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	seal_balance {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let (caller, addr) = instantiate_getter_contract::<T>("seal_balance", r);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	seal_value_transferred {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let (caller, addr) = instantiate_getter_contract::<T>("seal_value_transferred", r);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	seal_minimum_balance {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let (caller, addr) = instantiate_getter_contract::<T>("seal_minimum_balance", r);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	seal_tombstone_deposit {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let (caller, addr) = instantiate_getter_contract::<T>("seal_tombstone_deposit", r);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	seal_rent_allowance {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let (caller, addr) = instantiate_getter_contract::<T>("seal_rent_allowance", r);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	seal_block_number {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let (caller, addr) = instantiate_getter_contract::<T>("seal_block_number", r);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	seal_now {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let (caller, addr) = instantiate_getter_contract::<T>("seal_now", r);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	seal_weight_to_fee {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let (caller, addr) = instantiate_host_fn_contract::<T>(
+			ImportedFunction {
+				name: "seal_weight_to_fee",
+				params: vec![ValueType::I64, ValueType::I32, ValueType::I32],
+				return_type: None,
+			},
+			vec![(0, 128u32.encode())],
+			repeated(&[
+				Instruction::I64Const(500_000),
+				Instruction::I32Const(4),
+				Instruction::I32Const(0),
+				Instruction::Call(0),
+			], r * API_BENCHMARK_BATCH_SIZE),
+		);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// The input can only be taken once per call. The copy is charged as sandbox memory write.
+	seal_input {
+		let r in 0 .. 1;
+		let (caller, addr) = instantiate_host_fn_contract::<T>(
+			ImportedFunction {
+				name: "seal_input",
+				params: vec![ValueType::I32; 2],
+				return_type: None,
+			},
+			vec![(0, 128u32.encode())],
+			repeated(&[
+				Instruction::I32Const(4),
+				Instruction::I32Const(0),
+				Instruction::Call(0),
+			], r),
+		);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// `seal_return` traps, therefore it can only be called once per call.
+	seal_return {
+		let r in 0 .. 1;
+		let (caller, addr) = instantiate_host_fn_contract::<T>(
+			ImportedFunction {
+				name: "seal_return",
+				params: vec![ValueType::I32; 3],
+				return_type: None,
+			},
+			vec![],
+			repeated(&[
+				Instruction::I32Const(0),
+				Instruction::I32Const(0),
+				Instruction::I32Const(0),
+				Instruction::Call(0),
+			], r),
+		);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	seal_return_per_kb {
+		let n in 0 .. 32;
+		let (caller, addr) = instantiate_host_fn_contract::<T>(
+			ImportedFunction {
+				name: "seal_return",
+				params: vec![ValueType::I32; 3],
+				return_type: None,
+			},
+			vec![],
+			vec![
+				Instruction::I32Const(0),
+				Instruction::I32Const(0),
+				Instruction::I32Const((n * 1024) as i32),
+				Instruction::Call(0),
+			],
+		);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// `seal_terminate` traps, therefore it can only be called once per call.
+	seal_terminate {
+		let r in 0 .. 1;
+		let beneficiary = account::<T::AccountId>("beneficiary", 0, 0).encode();
+		let beneficiary_len = beneficiary.len();
+		let (caller, addr) = instantiate_host_fn_contract::<T>(
+			ImportedFunction {
+				name: "seal_terminate",
+				params: vec![ValueType::I32; 2],
+				return_type: None,
+			},
+			vec![(0, beneficiary)],
+			repeated(&[
+				Instruction::I32Const(0),
+				Instruction::I32Const(beneficiary_len as i32),
+				Instruction::Call(0),
+			], r),
+		);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr.clone()),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+	verify {
+		if r > 0 {
+			assert!(ContractInfoOf::<T>::get(&addr).is_none());
+		}
+	}
+
+	// Restores a tombstone left by the dummy contract. The restoring contract owns `d` batches
+	// of storage items which are passed as delta and therefore removed before the tombstones
+	// are compared.
+	seal_restore_to {
+		let d in 0 .. API_BENCHMARK_BATCHES;
+		let caller = create_funded_user::<T>("caller", 0);
+		let (dummy_binary, dummy_hash) = load_module!("dummy");
+		let dest = instantiate_contract::<T>(
+			&caller,
+			(dummy_binary, dummy_hash),
+			Config::<T>::subsistence_threshold_uncached(),
+			vec![],
+		);
+		advance_block::<T>(<T as Trait>::SignedClaimHandicap::get() + 1.into());
+		Contracts::<T>::claim_surcharge(
+			RawOrigin::Signed(caller.clone()).into(),
+			dest.clone(),
+			None,
+		).unwrap();
+		ContractInfoOf::<T>::get(&dest).and_then(|c| c.get_tombstone()).unwrap();
+
+		let keys = (0 .. d * API_BENCHMARK_BATCH_SIZE).map(storage_key).collect::<Vec<_>>();
+		let dest_encoded = dest.encode();
+		let code_hash = dummy_hash.encode();
+		let rent_allowance = BalanceOf::<T>::max_value().encode();
+		let code_hash_ptr = dest_encoded.len() as u32;
+		let rent_allowance_ptr = code_hash_ptr + code_hash.len() as u32;
+		let delta_ptr = rent_allowance_ptr + rent_allowance.len() as u32;
+		let body = vec![
+			Instruction::I32Const(0),
+			Instruction::I32Const(dest_encoded.len() as i32),
+			Instruction::I32Const(code_hash_ptr as i32),
+			Instruction::I32Const(code_hash.len() as i32),
+			Instruction::I32Const(rent_allowance_ptr as i32),
+			Instruction::I32Const(rent_allowance.len() as i32),
+			Instruction::I32Const(delta_ptr as i32),
+			Instruction::I32Const(keys.len() as i32),
+			Instruction::Call(0),
+		];
+		let addr = instantiate_contract::<T>(
+			&caller,
+			host_fn_contract::<T>(
+				ImportedFunction {
+					name: "seal_restore_to",
+					params: vec![ValueType::I32; 8],
+					return_type: None,
+				},
+				vec![
+					(0, dest_encoded),
+					(code_hash_ptr, code_hash),
+					(rent_allowance_ptr, rent_allowance),
+					(delta_ptr, keys.iter().flatten().cloned().collect()),
+				],
+				body,
+			),
+			funding::<T>() / 2.into(),
+			vec![],
+		);
+		populate_storage::<T>(&addr, &keys, 32);
+		// The storage of the restoring contract must not be written in the current block.
+		advance_block::<T>(1.into());
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr.clone()),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+	verify {
+		ContractInfoOf::<T>::get(&dest).and_then(|c| c.get_alive()).unwrap();
+		assert!(ContractInfoOf::<T>::get(&addr).is_none());
+	}
+
+	seal_random {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let (caller, addr) = instantiate_host_fn_contract::<T>(
+			ImportedFunction {
+				name: "seal_random",
+				params: vec![ValueType::I32; 4],
+				return_type: None,
+			},
+			vec![(0, 128u32.encode())],
+			repeated(&[
+				// The subject are the 32 zero bytes at offset 4.
+				Instruction::I32Const(4),
+				Instruction::I32Const(32),
+				Instruction::I32Const(36),
+				Instruction::I32Const(0),
+				Instruction::Call(0),
+			], r * API_BENCHMARK_BATCH_SIZE),
+		);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// Deposits events without topics and without data.
+	seal_deposit_event {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let (caller, addr) = instantiate_host_fn_contract::<T>(
+			ImportedFunction {
+				name: "seal_deposit_event",
+				params: vec![ValueType::I32; 4],
+				return_type: None,
+			},
+			vec![],
+			repeated(&[
+				Instruction::I32Const(0),
+				Instruction::I32Const(0),
+				Instruction::I32Const(0),
+				Instruction::I32Const(0),
+				Instruction::Call(0),
+			], r * API_BENCHMARK_BATCH_SIZE),
+		);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// Deposits a single event with `t` topics and `n` kilobytes of data.
+	seal_deposit_event_per_topic_and_kb {
+		let t in 0 .. Contracts::<T>::current_schedule().max_event_topics;
+		let n in 0 .. 16;
+		let topics = (0 .. t)
+			.map(|i| T::Hashing::hash_of(&i))
+			.collect::<Vec<_>>()
+			.encode();
+		let topics_len = topics.len() as i32;
+		let (caller, addr) = instantiate_host_fn_contract::<T>(
+			ImportedFunction {
+				name: "seal_deposit_event",
+				params: vec![ValueType::I32; 4],
+				return_type: None,
+			},
+			vec![(0, topics)],
+			vec![
+				Instruction::I32Const(0),
+				Instruction::I32Const(if t == 0 { 0 } else { topics_len }),
+				Instruction::I32Const(topics_len),
+				Instruction::I32Const((n * 1024) as i32),
+				Instruction::Call(0),
+			],
+		);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	seal_set_rent_allowance {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let value = T::Currency::minimum_balance().encode();
+		let value_len = value.len() as i32;
+		let (caller, addr) = instantiate_host_fn_contract::<T>(
+			ImportedFunction {
+				name: "seal_set_rent_allowance",
+				params: vec![ValueType::I32; 2],
+				return_type: None,
+			},
+			vec![(0, value)],
+			repeated(&[
+				Instruction::I32Const(0),
+				Instruction::I32Const(value_len),
+				Instruction::Call(0),
+			], r * API_BENCHMARK_BATCH_SIZE),
+		);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// Stores empty values under `r` batches of distinct keys.
+	seal_set_storage {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let keys = (0 .. r * API_BENCHMARK_BATCH_SIZE).map(storage_key).collect::<Vec<_>>();
+		let body = (0 .. keys.len() as i32)
+			.flat_map(|i| vec![
+				Instruction::I32Const(i * 32),
+				Instruction::I32Const(0),
+				Instruction::I32Const(0),
+				Instruction::Call(0),
+			])
+			.collect();
+		let (caller, addr) = instantiate_host_fn_contract::<T>(
+			ImportedFunction {
+				name: "seal_set_storage",
+				params: vec![ValueType::I32; 3],
+				return_type: None,
+			},
+			vec![(0, keys.iter().flatten().cloned().collect())],
+			body,
+		);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// Stores a single value of `n` kilobytes.
+	seal_set_storage_per_kb {
+		let n in 0 .. T::MaxValueSize::get() / 1024;
+		let (caller, addr) = instantiate_host_fn_contract::<T>(
+			ImportedFunction {
+				name: "seal_set_storage",
+				params: vec![ValueType::I32; 3],
+				return_type: None,
+			},
+			vec![],
+			vec![
+				Instruction::I32Const(0),
+				Instruction::I32Const(32),
+				Instruction::I32Const((n * 1024) as i32),
+				Instruction::Call(0),
+			],
+		);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// Clears `r` batches of distinct keys which are present in storage.
+	seal_clear_storage {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let keys = (0 .. r * API_BENCHMARK_BATCH_SIZE).map(storage_key).collect::<Vec<_>>();
+		let body = (0 .. keys.len() as i32)
+			.flat_map(|i| vec![
+				Instruction::I32Const(i * 32),
+				Instruction::Call(0),
+			])
+			.collect();
+		let (caller, addr) = instantiate_host_fn_contract::<T>(
+			ImportedFunction {
+				name: "seal_clear_storage",
+				params: vec![ValueType::I32],
+				return_type: None,
+			},
+			vec![(0, keys.iter().flatten().cloned().collect())],
+			body,
+		);
+		populate_storage::<T>(&addr, &keys, 0);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// Reads empty values from `r` batches of distinct keys which are present in storage.
+	seal_get_storage {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let keys = (0 .. r * API_BENCHMARK_BATCH_SIZE).map(storage_key).collect::<Vec<_>>();
+		let out_len_ptr = keys.len() as i32 * 32;
+		let body = (0 .. keys.len() as i32)
+			.flat_map(|i| vec![
+				Instruction::I32Const(i * 32),
+				Instruction::I32Const(out_len_ptr + 4),
+				Instruction::I32Const(out_len_ptr),
+				Instruction::Call(0),
+				Instruction::Drop,
+			])
+			.collect();
+		let (caller, addr) = instantiate_host_fn_contract::<T>(
+			ImportedFunction {
+				name: "seal_get_storage",
+				params: vec![ValueType::I32; 3],
+				return_type: Some(ValueType::I32),
+			},
+			vec![
+				(0, keys.iter().flatten().cloned().collect()),
+				(out_len_ptr as u32, 0u32.encode()),
+			],
+			body,
+		);
+		populate_storage::<T>(&addr, &keys, 0);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// Reads a single value of `n` kilobytes.
+	seal_get_storage_per_kb {
+		let n in 0 .. T::MaxValueSize::get() / 1024;
+		let key = storage_key(0);
+		let (caller, addr) = instantiate_host_fn_contract::<T>(
+			ImportedFunction {
+				name: "seal_get_storage",
+				params: vec![ValueType::I32; 3],
+				return_type: Some(ValueType::I32),
+			},
+			vec![(0, key.to_vec()), (32, (n * 1024).encode())],
+			vec![
+				Instruction::I32Const(0),
+				Instruction::I32Const(36),
+				Instruction::I32Const(32),
+				Instruction::Call(0),
+				Instruction::Drop,
+			],
+		);
+		populate_storage::<T>(&addr, &[key], n * 1024);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// Checks `r` batches of distinct keys which are present in storage.
+	seal_contains_storage {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let keys = (0 .. r * API_BENCHMARK_BATCH_SIZE).map(storage_key).collect::<Vec<_>>();
+		let out_len_ptr = keys.len() as i32 * 32;
+		let body = (0 .. keys.len() as i32)
+			.flat_map(|i| vec![
+				Instruction::I32Const(i * 32),
+				Instruction::I32Const(32),
+				Instruction::I32Const(out_len_ptr),
+				Instruction::Call(0),
+				Instruction::Drop,
+			])
+			.collect();
+		let (caller, addr) = instantiate_host_fn_contract::<T>(
+			ImportedFunction {
+				name: "seal_contains_storage",
+				params: vec![ValueType::I32; 3],
+				return_type: Some(ValueType::I32),
+			},
+			vec![(0, keys.iter().flatten().cloned().collect())],
+			body,
+		);
+		populate_storage::<T>(&addr, &keys, 0);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// Takes empty values from `r` batches of distinct keys which are present in storage.
+	seal_take_storage {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let keys = (0 .. r * API_BENCHMARK_BATCH_SIZE).map(storage_key).collect::<Vec<_>>();
+		let out_len_ptr = keys.len() as i32 * 32;
+		let body = (0 .. keys.len() as i32)
+			.flat_map(|i| vec![
+				Instruction::I32Const(i * 32),
+				Instruction::I32Const(32),
+				Instruction::I32Const(out_len_ptr + 4),
+				Instruction::I32Const(out_len_ptr),
+				Instruction::Call(0),
+				Instruction::Drop,
+			])
+			.collect();
+		let (caller, addr) = instantiate_host_fn_contract::<T>(
+			ImportedFunction {
+				name: "seal_take_storage",
+				params: vec![ValueType::I32; 4],
+				return_type: Some(ValueType::I32),
+			},
+			vec![
+				(0, keys.iter().flatten().cloned().collect()),
+				(out_len_ptr as u32, 0u32.encode()),
+			],
+			body,
+		);
+		populate_storage::<T>(&addr, &keys, 0);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// Takes a single value of `n` kilobytes.
+	seal_take_storage_per_kb {
+		let n in 0 .. T::MaxValueSize::get() / 1024;
+		let key = storage_key(0);
+		let (caller, addr) = instantiate_host_fn_contract::<T>(
+			ImportedFunction {
+				name: "seal_take_storage",
+				params: vec![ValueType::I32; 4],
+				return_type: Some(ValueType::I32),
+			},
+			vec![(0, key.to_vec()), (32, (n * 1024).encode())],
+			vec![
+				Instruction::I32Const(0),
+				Instruction::I32Const(32),
+				Instruction::I32Const(36),
+				Instruction::I32Const(32),
+				Instruction::Call(0),
+				Instruction::Drop,
+			],
+		);
+		populate_storage::<T>(&addr, &[key], n * 1024);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// Iterates over `r` batches of keys of a contract in unhashed storage mode, one key
+	// per step.
+	seal_storage_next_key {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let keys = (0 .. r * API_BENCHMARK_BATCH_SIZE).map(storage_key).collect::<Vec<_>>();
+		let out_len_ptr = keys.len() as i32 * 32;
+		let body = (0 .. keys.len() as i32)
+			.flat_map(|i| vec![
+				Instruction::I32Const(0),
+				Instruction::I32Const(0),
+				Instruction::I32Const(if i == 0 { -1 } else { (i - 1) * 32 }),
+				Instruction::I32Const(32),
+				Instruction::I32Const(out_len_ptr + 4),
+				Instruction::I32Const(out_len_ptr),
+				Instruction::Call(0),
+				Instruction::Drop,
+			])
+			.collect();
+		let (caller, addr) = instantiate_host_fn_contract::<T>(
+			ImportedFunction {
+				name: "seal_storage_next_key",
+				params: vec![ValueType::I32; 6],
+				return_type: Some(ValueType::I32),
+			},
+			vec![
+				(0, keys.iter().flatten().cloned().collect()),
+				(out_len_ptr as u32, 32u32.encode()),
+			],
+			body,
+		);
+		let trie_id = ContractInfoOf::<T>::get(&addr).and_then(|c| c.get_alive()).unwrap().trie_id;
+		<StorageModeOf>::insert(&trie_id, StorageMode::Unhashed);
+		populate_storage::<T>(&addr, &keys, 0);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// Transfers the minimum balance to `r` batches of distinct accounts.
+	seal_transfer {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let accounts = (0 .. r * API_BENCHMARK_BATCH_SIZE)
+			.map(|i| account::<T::AccountId>("receiver", i, 0).encode())
+			.collect::<Vec<_>>();
+		let account_len = accounts.get(0).map(|a| a.len()).unwrap_or(0) as i32;
+		let value = T::Currency::minimum_balance().encode();
+		let value_ptr = account_len * accounts.len() as i32;
+		let value_len = value.len() as i32;
+		let body = (0 .. accounts.len() as i32)
+			.flat_map(|i| vec![
+				Instruction::I32Const(i * account_len),
+				Instruction::I32Const(account_len),
+				Instruction::I32Const(value_ptr),
+				Instruction::I32Const(value_len),
+				Instruction::Call(0),
+				Instruction::Drop,
+			])
+			.collect();
+		let (caller, addr) = instantiate_host_fn_contract::<T>(
+			ImportedFunction {
+				name: "seal_transfer",
+				params: vec![ValueType::I32; 4],
+				return_type: Some(ValueType::I32),
+			},
+			vec![
+				(0, accounts.into_iter().flatten().collect()),
+				(value_ptr as u32, value),
+			],
+			body,
+		);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// Calls the dummy contract `r` batches of times without transferring value and without
+	// copying back its output.
+	seal_call {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let caller = create_funded_user::<T>("caller", 0);
+		let callee = instantiate_contract::<T>(
+			&caller,
+			load_module!("dummy"),
+			Config::<T>::subsistence_threshold_uncached(),
+			vec![],
+		).encode();
+		let callee_len = callee.len() as i32;
+		let value = BalanceOf::<T>::from(0u32).encode();
+		let value_len = value.len() as i32;
+		let addr = instantiate_contract::<T>(
+			&caller,
+			host_fn_contract::<T>(
+				ImportedFunction {
+					name: "seal_call",
+					params: vec![
+						ValueType::I32,
+						ValueType::I32,
+						ValueType::I64,
+						ValueType::I32,
+						ValueType::I32,
+						ValueType::I32,
+						ValueType::I32,
+						ValueType::I32,
+						ValueType::I32,
+					],
+					return_type: Some(ValueType::I32),
+				},
+				vec![(0, callee), (callee_len as u32, value)],
+				repeated(&[
+					Instruction::I32Const(0),
+					Instruction::I32Const(callee_len),
+					Instruction::I64Const(0),
+					Instruction::I32Const(callee_len),
+					Instruction::I32Const(value_len),
+					Instruction::I32Const(0),
+					Instruction::I32Const(0),
+					// Sentinel value to skip the copy of the output buffer.
+					Instruction::I32Const(u32::max_value() as i32),
+					Instruction::I32Const(0),
+					Instruction::Call(0),
+					Instruction::Drop,
+				], r * API_BENCHMARK_BATCH_SIZE),
+			),
+			funding::<T>() / 2.into(),
+			vec![],
+		);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// Instantiates the dummy contract `r` batches of times. Each instantiation passes a
+	// distinct input in order to create distinct accounts.
+	seal_instantiate {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let caller = create_funded_user::<T>("caller", 0);
+		let (dummy_binary, dummy_hash) = load_module!("dummy");
+		Contracts::<T>::put_code(RawOrigin::Signed(caller.clone()).into(), dummy_binary)
+			.unwrap();
+		let count = r * API_BENCHMARK_BATCH_SIZE;
+		let endowment = Config::<T>::subsistence_threshold_uncached();
+		let code_hash = dummy_hash.encode();
+		let code_hash_len = code_hash.len() as i32;
+		let value = endowment.encode();
+		let value_len = value.len() as i32;
+		let inputs_ptr = code_hash_len + value_len;
+		let body = (0 .. count as i32)
+			.flat_map(|i| vec![
+				Instruction::I32Const(0),
+				Instruction::I32Const(code_hash_len),
+				Instruction::I64Const(0),
+				Instruction::I32Const(code_hash_len),
+				Instruction::I32Const(value_len),
+				Instruction::I32Const(inputs_ptr + i * 4),
+				Instruction::I32Const(4),
+				// Sentinel values to skip the copy of the address and the output buffer.
+				Instruction::I32Const(u32::max_value() as i32),
+				Instruction::I32Const(0),
+				Instruction::I32Const(u32::max_value() as i32),
+				Instruction::I32Const(0),
+				Instruction::Call(0),
+				Instruction::Drop,
+			])
+			.collect();
+		// Keep the subsistence threshold after all instantiations.
+		let contract_endowment = endowment * (count + 2).into();
+		T::Currency::make_free_balance_be(&caller, contract_endowment * 2.into());
+		let addr = instantiate_contract::<T>(
+			&caller,
+			host_fn_contract::<T>(
+				ImportedFunction {
+					name: "seal_instantiate",
+					params: vec![
+						ValueType::I32,
+						ValueType::I32,
+						ValueType::I64,
+						ValueType::I32,
+						ValueType::I32,
+						ValueType::I32,
+						ValueType::I32,
+						ValueType::I32,
+						ValueType::I32,
+						ValueType::I32,
+						ValueType::I32,
+					],
+					return_type: Some(ValueType::I32),
+				},
+				vec![
+					(0, code_hash),
+					(code_hash_len as u32, value),
+					(inputs_ptr as u32, (0 .. count).flat_map(|i| i.encode()).collect()),
+				],
+				body,
+			),
+			contract_endowment,
+			vec![],
+		);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// `seal_println` can only be imported when the schedule allows it.
+	seal_println {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let schedule = Contracts::<T>::current_schedule();
+		Contracts::<T>::update_schedule(RawOrigin::Root.into(), Schedule {
+			version: schedule.version + 1,
+			enable_println: true,
+			.. schedule
+		}).unwrap();
+		let (caller, addr) = instantiate_host_fn_contract::<T>(
+			ImportedFunction {
+				name: "seal_println",
+				params: vec![ValueType::I32; 2],
+				return_type: None,
+			},
+			vec![],
+			repeated(&[
+				Instruction::I32Const(0),
+				Instruction::I32Const(0),
+				Instruction::Call(0),
+			], r * API_BENCHMARK_BATCH_SIZE),
+		);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// Hashes empty inputs.
+	seal_hash_sha2_256 {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let (caller, addr) = instantiate_hash_contract::<T>(
+			"seal_hash_sha2_256", r * API_BENCHMARK_BATCH_SIZE, 0
+		);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// Hashes a single input of `n` kilobytes.
+	seal_hash_sha2_256_per_kb {
+		let n in 0 .. 32;
+		let (caller, addr) = instantiate_hash_contract::<T>("seal_hash_sha2_256", 1, n * 1024);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// Hashes empty inputs.
+	seal_hash_keccak_256 {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let (caller, addr) = instantiate_hash_contract::<T>(
+			"seal_hash_keccak_256", r * API_BENCHMARK_BATCH_SIZE, 0
+		);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// Hashes a single input of `n` kilobytes.
+	seal_hash_keccak_256_per_kb {
+		let n in 0 .. 32;
+		let (caller, addr) = instantiate_hash_contract::<T>("seal_hash_keccak_256", 1, n * 1024);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// Hashes empty inputs.
+	seal_hash_blake2_256 {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let (caller, addr) = instantiate_hash_contract::<T>(
+			"seal_hash_blake2_256", r * API_BENCHMARK_BATCH_SIZE, 0
+		);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// Hashes a single input of `n` kilobytes.
+	seal_hash_blake2_256_per_kb {
+		let n in 0 .. 32;
+		let (caller, addr) = instantiate_hash_contract::<T>("seal_hash_blake2_256", 1, n * 1024);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// Hashes empty inputs.
+	seal_hash_blake2_128 {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let (caller, addr) = instantiate_hash_contract::<T>(
+			"seal_hash_blake2_128", r * API_BENCHMARK_BATCH_SIZE, 0
+		);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// Hashes a single input of `n` kilobytes.
+	seal_hash_blake2_128_per_kb {
+		let n in 0 .. 32;
+		let (caller, addr) = instantiate_hash_contract::<T>("seal_hash_blake2_128", 1, n * 1024);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// Recovers the public key from a valid signature `r` batches of times.
+	seal_ecdsa_recover {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let (caller, addr) = instantiate_host_fn_contract::<T>(
+			ImportedFunction {
+				name: "seal_ecdsa_recover",
+				params: vec![ValueType::I32; 3],
+				return_type: Some(ValueType::I32),
+			},
+			vec![(0, ECDSA_SIGNATURE.to_vec()), (65, ECDSA_MESSAGE.to_vec())],
+			repeated(&[
+				Instruction::I32Const(0),
+				Instruction::I32Const(65),
+				Instruction::I32Const(97),
+				Instruction::Call(0),
+				Instruction::Drop,
+			], r * API_BENCHMARK_BATCH_SIZE),
+		);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// Converts a valid public key into an Ethereum address `r` batches of times.
+	seal_ecdsa_to_eth_address {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let public_key = sp_io::crypto::secp256k1_ecdsa_recover_compressed(
+			&ECDSA_SIGNATURE,
+			&ECDSA_MESSAGE,
+		).unwrap();
+		let (caller, addr) = instantiate_host_fn_contract::<T>(
+			ImportedFunction {
+				name: "seal_ecdsa_to_eth_address",
+				params: vec![ValueType::I32; 2],
+				return_type: Some(ValueType::I32),
+			},
+			vec![(0, public_key.to_vec())],
+			repeated(&[
+				Instruction::I32Const(0),
+				Instruction::I32Const(33),
+				Instruction::Call(0),
+				Instruction::Drop,
+			], r * API_BENCHMARK_BATCH_SIZE),
+		);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// Verifies a valid sr25519 signature of a short message `r` batches of times.
+	seal_sr25519_verify {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let (caller, addr) = instantiate_verify_contract::<T>(
+			"seal_sr25519_verify", SignatureScheme::Sr25519, r * API_BENCHMARK_BATCH_SIZE, 32,
+		);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// Verifies a valid sr25519 signature of a message of `n` kilobytes.
+	seal_sr25519_verify_per_kb {
+		let n in 0 .. 32;
+		let (caller, addr) = instantiate_verify_contract::<T>(
+			"seal_sr25519_verify", SignatureScheme::Sr25519, 1, n * 1024,
+		);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// Verifies a valid ed25519 signature of a short message `r` batches of times.
+	seal_ed25519_verify {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let (caller, addr) = instantiate_verify_contract::<T>(
+			"seal_ed25519_verify", SignatureScheme::Ed25519, r * API_BENCHMARK_BATCH_SIZE, 32,
+		);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
+
+	// Verifies a valid ed25519 signature of a message of `n` kilobytes.
+	seal_ed25519_verify_per_kb {
+		let n in 0 .. 32;
+		let (caller, addr) = instantiate_verify_contract::<T>(
+			"seal_ed25519_verify", SignatureScheme::Ed25519, 1, n * 1024,
+		);
+	}: call(
+			RawOrigin::Signed(caller),
+			T::Lookup::unlookup(addr),
+			0.into(),
+			Weight::max_value(),
+			vec![]
+		)
 }
 
 #[cfg(test)]
@@ -241,6 +2232,13 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn update_schedule_at() {
+		ExtBuilder::default().build().execute_with(|| {
+			assert_ok!(test_benchmark_update_schedule_at::<Test>());
+		});
+	}
+
 	#[test]
 	fn put_code() {
 		ExtBuilder::default().build().execute_with(|| {
@@ -248,6 +2246,13 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn reinstrument_code() {
+		ExtBuilder::default().build().execute_with(|| {
+			assert_ok!(test_benchmark_reinstrument_code::<Test>());
+		});
+	}
+
 	#[test]
 	fn instantiate() {
 		ExtBuilder::default().build().execute_with(|| {
@@ -268,4 +2273,113 @@ mod tests {
 			assert_ok!(test_benchmark_claim_surcharge::<Test>());
 		});
 	}
+
+	#[test]
+	fn deposit_rent() {
+		ExtBuilder::default().build().execute_with(|| {
+			assert_ok!(test_benchmark_deposit_rent::<Test>());
+		});
+	}
+
+	#[test]
+	fn restore_contract() {
+		ExtBuilder::default().build().execute_with(|| {
+			assert_ok!(test_benchmark_restore_contract::<Test>());
+		});
+	}
+
+	#[test]
+	fn restore_contract_with_proof() {
+		ExtBuilder::default().build().execute_with(|| {
+			assert_ok!(test_benchmark_restore_contract_with_proof::<Test>());
+		});
+	}
+
+	#[test]
+	fn import_storage() {
+		ExtBuilder::default().build().execute_with(|| {
+			assert_ok!(test_benchmark_import_storage::<Test>());
+		});
+	}
+
+	macro_rules! create_test {
+		($name:ident, $test:ident) => {
+			#[test]
+			fn $name() {
+				ExtBuilder::default().build().execute_with(|| {
+					assert_ok!($test::<Test>());
+				});
+			}
+		}
+	}
+
+	create_test!(instr_const, test_benchmark_instr_const);
+	create_test!(instr_local, test_benchmark_instr_local);
+	create_test!(instr_global, test_benchmark_instr_global);
+	create_test!(instr_load, test_benchmark_instr_load);
+	create_test!(instr_store, test_benchmark_instr_store);
+	create_test!(instr_branch, test_benchmark_instr_branch);
+	create_test!(instr_call, test_benchmark_instr_call);
+	create_test!(instr_call_indirect, test_benchmark_instr_call_indirect);
+	create_test!(instr_integer_comparison, test_benchmark_instr_integer_comparison);
+	create_test!(instr_bit, test_benchmark_instr_bit);
+	create_test!(instr_add, test_benchmark_instr_add);
+	create_test!(instr_mul, test_benchmark_instr_mul);
+	create_test!(instr_div, test_benchmark_instr_div);
+	create_test!(instr_conversion, test_benchmark_instr_conversion);
+	create_test!(instr_nop, test_benchmark_instr_nop);
+	create_test!(instr_current_memory, test_benchmark_instr_current_memory);
+	create_test!(instr_grow_memory, test_benchmark_instr_grow_memory);
+	create_test!(seal_caller, test_benchmark_seal_caller);
+	create_test!(seal_address, test_benchmark_seal_address);
+	create_test!(seal_origin, test_benchmark_seal_origin);
+	create_test!(seal_caller_is_origin, test_benchmark_seal_caller_is_origin);
+	create_test!(seal_is_contract, test_benchmark_seal_is_contract);
+	create_test!(seal_code_hash, test_benchmark_seal_code_hash);
+	create_test!(seal_own_code_hash, test_benchmark_seal_own_code_hash);
+	create_test!(seal_gas_left, test_benchmark_seal_gas_left);
+	create_test!(seal_balance, test_benchmark_seal_balance);
+	create_test!(seal_value_transferred, test_benchmark_seal_value_transferred);
+	create_test!(seal_minimum_balance, test_benchmark_seal_minimum_balance);
+	create_test!(seal_tombstone_deposit, test_benchmark_seal_tombstone_deposit);
+	create_test!(seal_rent_allowance, test_benchmark_seal_rent_allowance);
+	create_test!(seal_block_number, test_benchmark_seal_block_number);
+	create_test!(seal_now, test_benchmark_seal_now);
+	create_test!(seal_weight_to_fee, test_benchmark_seal_weight_to_fee);
+	create_test!(seal_input, test_benchmark_seal_input);
+	create_test!(seal_return, test_benchmark_seal_return);
+	create_test!(seal_return_per_kb, test_benchmark_seal_return_per_kb);
+	create_test!(seal_terminate, test_benchmark_seal_terminate);
+	create_test!(seal_restore_to, test_benchmark_seal_restore_to);
+	create_test!(seal_random, test_benchmark_seal_random);
+	create_test!(seal_deposit_event, test_benchmark_seal_deposit_event);
+	create_test!(seal_deposit_event_per_topic_and_kb, test_benchmark_seal_deposit_event_per_topic_and_kb);
+	create_test!(seal_set_rent_allowance, test_benchmark_seal_set_rent_allowance);
+	create_test!(seal_set_storage, test_benchmark_seal_set_storage);
+	create_test!(seal_set_storage_per_kb, test_benchmark_seal_set_storage_per_kb);
+	create_test!(seal_clear_storage, test_benchmark_seal_clear_storage);
+	create_test!(seal_get_storage, test_benchmark_seal_get_storage);
+	create_test!(seal_get_storage_per_kb, test_benchmark_seal_get_storage_per_kb);
+	create_test!(seal_contains_storage, test_benchmark_seal_contains_storage);
+	create_test!(seal_take_storage, test_benchmark_seal_take_storage);
+	create_test!(seal_take_storage_per_kb, test_benchmark_seal_take_storage_per_kb);
+	create_test!(seal_storage_next_key, test_benchmark_seal_storage_next_key);
+	create_test!(seal_transfer, test_benchmark_seal_transfer);
+	create_test!(seal_call, test_benchmark_seal_call);
+	create_test!(seal_instantiate, test_benchmark_seal_instantiate);
+	create_test!(seal_println, test_benchmark_seal_println);
+	create_test!(seal_hash_sha2_256, test_benchmark_seal_hash_sha2_256);
+	create_test!(seal_hash_sha2_256_per_kb, test_benchmark_seal_hash_sha2_256_per_kb);
+	create_test!(seal_hash_keccak_256, test_benchmark_seal_hash_keccak_256);
+	create_test!(seal_hash_keccak_256_per_kb, test_benchmark_seal_hash_keccak_256_per_kb);
+	create_test!(seal_hash_blake2_256, test_benchmark_seal_hash_blake2_256);
+	create_test!(seal_hash_blake2_256_per_kb, test_benchmark_seal_hash_blake2_256_per_kb);
+	create_test!(seal_hash_blake2_128, test_benchmark_seal_hash_blake2_128);
+	create_test!(seal_hash_blake2_128_per_kb, test_benchmark_seal_hash_blake2_128_per_kb);
+	create_test!(seal_ecdsa_recover, test_benchmark_seal_ecdsa_recover);
+	create_test!(seal_ecdsa_to_eth_address, test_benchmark_seal_ecdsa_to_eth_address);
+	create_test!(seal_sr25519_verify, test_benchmark_seal_sr25519_verify);
+	create_test!(seal_sr25519_verify_per_kb, test_benchmark_seal_sr25519_verify_per_kb);
+	create_test!(seal_ed25519_verify, test_benchmark_seal_ed25519_verify);
+	create_test!(seal_ed25519_verify_per_kb, test_benchmark_seal_ed25519_verify_per_kb);
 }
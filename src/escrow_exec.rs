@@ -18,7 +18,7 @@ use crate::exec::*;
 
 use crate::{
     gas::{GasMeter},
-    rent, BalanceOf, CodeHash, Config, ContractInfo, ContractInfoOf,
+    rent, storage, BalanceOf, CodeHash, Config, ContractInfo, ContractInfoOf,
     Error, Trait,
 };
 
@@ -43,7 +43,7 @@ use sp_std::{convert::TryInto, prelude::*};
 pub struct DeferredStorageWrite {
     pub dest: Vec<u8>,
     pub trie_id: Vec<u8>,
-    pub key: [u8; 32],
+    pub key: Vec<u8>,
     pub value: Option<Vec<u8>>,
 }
 
@@ -76,11 +76,16 @@ impl<'a, 'b: 'a, T, E, V, L> Ext for EscrowCallContext<'a, 'b, T, V, L>
 {
     type T = T;
 
-    fn get_storage(&self, key: &StorageKey) -> Option<Vec<u8>> {
+    fn get_storage(&self, key: &[u8]) -> Option<Vec<u8>> {
         self.call_context.get_storage(key)
     }
 
-    fn set_storage(&mut self, key: StorageKey, value: Option<Vec<u8>>) {
+    fn set_storage(
+        &mut self,
+        key: &[u8],
+        value: Option<Vec<u8>>,
+    ) -> Result<Option<u32>, DispatchError> {
+        self.call_context.ctx.ensure_writable()?;
         let trie_id = self.call_context.ctx.self_trie_id.as_ref().expect(
             "`ctx.self_trie_id` points to an alive contract within the `CallContext`;\
 				it cannot be `None`;\
@@ -91,13 +96,44 @@ impl<'a, 'b: 'a, T, E, V, L> Ext for EscrowCallContext<'a, 'b, T, V, L>
         self.deferred_storage_writes.push(DeferredStorageWrite {
             dest: T::AccountId::encode(&self.call_context.ctx.self_account),
             trie_id: trie_id.to_vec(),
-            key,
+            key: key.to_vec(),
             value: value.clone(),
         });
 
         self.call_context.set_storage(key, value)
     }
 
+    fn get_storage_size(&self, key: &[u8]) -> Option<u32> {
+        self.call_context.get_storage_size(key)
+    }
+
+    fn take_storage(&mut self, key: &[u8]) -> Result<Option<Vec<u8>>, DispatchError> {
+        self.call_context.ctx.ensure_writable()?;
+        let trie_id = self.call_context.ctx.self_trie_id.as_ref().expect(
+            "`ctx.self_trie_id` points to an alive contract within the `CallContext`;\
+				it cannot be `None`;\
+				expect can't fail;\
+				qed",
+        );
+
+        self.deferred_storage_writes.push(DeferredStorageWrite {
+            dest: T::AccountId::encode(&self.call_context.ctx.self_account),
+            trie_id: trie_id.to_vec(),
+            key: key.to_vec(),
+            value: None,
+        });
+
+        self.call_context.take_storage(key)
+    }
+
+    fn next_storage_key(
+        &self,
+        prefix: &[u8],
+        after: Option<&[u8]>,
+    ) -> Result<Option<Vec<u8>>, DispatchError> {
+        self.call_context.next_storage_key(prefix, after)
+    }
+
     fn instantiate(
         &mut self,
         code_hash: &CodeHash<T>,
@@ -115,6 +151,7 @@ impl<'a, 'b: 'a, T, E, V, L> Ext for EscrowCallContext<'a, 'b, T, V, L>
         value: BalanceOf<T>,
         _gas_meter: &mut GasMeter<T>,
     ) -> Result<(), DispatchError> {
+        self.call_context.ctx.ensure_writable()?;
         escrow_transfer::<T>(
             &self.caller.clone(),
             &self.requester,
@@ -138,18 +175,22 @@ impl<'a, 'b: 'a, T, E, V, L> Ext for EscrowCallContext<'a, 'b, T, V, L>
         value: BalanceOf<T>,
         gas_meter: &mut GasMeter<T>,
         input_data: Vec<u8>,
+        allows_reentry: bool,
+        read_only: bool,
     ) -> ExecResult {
+        let load_read_only = self.call_context.ctx.read_only || read_only;
         let executable = if let Some(ContractInfo::Alive(info)) = <ContractInfoOf<T>>::get(to) {
             self.call_context
                 .ctx
                 .loader
-                .load_main(&info.code_hash)
-                .map_err(|_| Error::<T>::CodeNotFound)?
+                .load_main(&info.code_hash, gas_meter, load_read_only)?
         } else {
             Err(Error::<T>::NotCallable)?
         };
 
-        self.call_context.ctx.escrow_call(
+        // Protect the current contract against reentrancy for the duration of this call.
+        self.call_context.ctx.allows_reentry = allows_reentry;
+        let result = self.call_context.ctx.escrow_call(
             &self.caller.clone(),
             &self.requester.clone(),
             &to,
@@ -161,7 +202,11 @@ impl<'a, 'b: 'a, T, E, V, L> Ext for EscrowCallContext<'a, 'b, T, V, L>
             self.deferred_storage_writes,
             self.call_stamps,
             &executable,
-        )
+            read_only,
+        );
+        // The protection only applies to a single call.
+        self.call_context.ctx.allows_reentry = true;
+        result
     }
 
     fn restore_to(
@@ -170,7 +215,7 @@ impl<'a, 'b: 'a, T, E, V, L> Ext for EscrowCallContext<'a, 'b, T, V, L>
         code_hash: CodeHash<Self::T>,
         rent_allowance: BalanceOf<Self::T>,
         delta: Vec<StorageKey>,
-    ) -> Result<(), &'static str> {
+    ) -> Result<(), DispatchError> {
         self.call_context
             .restore_to(dest, code_hash, rent_allowance, delta)
     }
@@ -183,6 +228,26 @@ impl<'a, 'b: 'a, T, E, V, L> Ext for EscrowCallContext<'a, 'b, T, V, L>
         &self.call_context.ctx.self_account
     }
 
+    fn origin(&self) -> &T::AccountId {
+        self.call_context.origin()
+    }
+
+    fn caller_is_origin(&self) -> bool {
+        self.caller() == self.origin()
+    }
+
+    fn is_contract(&self, address: &T::AccountId) -> bool {
+        self.call_context.is_contract(address)
+    }
+
+    fn code_hash(&self, address: &T::AccountId) -> Option<CodeHash<T>> {
+        self.call_context.code_hash(address)
+    }
+
+    fn own_code_hash(&self) -> CodeHash<T> {
+        self.call_context.own_code_hash()
+    }
+
     fn balance(&self) -> BalanceOf<T> {
         <T as Trait>::Currency::free_balance(&self.call_context.ctx.self_account)
     }
@@ -207,12 +272,12 @@ impl<'a, 'b: 'a, T, E, V, L> Ext for EscrowCallContext<'a, 'b, T, V, L>
         <T as Trait>::Randomness::random(subject)
     }
 
-    fn deposit_event(&mut self, topics: Vec<T::Hash>, data: Vec<u8>) {
-        self.call_context.deposit_event(topics, data);
+    fn deposit_event(&mut self, topics: Vec<T::Hash>, data: Vec<u8>) -> Result<(), DispatchError> {
+        self.call_context.deposit_event(topics, data)
     }
 
-    fn set_rent_allowance(&mut self, rent_allowance: BalanceOf<T>) {
-        self.call_context.set_rent_allowance(rent_allowance);
+    fn set_rent_allowance(&mut self, rent_allowance: BalanceOf<T>) -> Result<(), DispatchError> {
+        self.call_context.set_rent_allowance(rent_allowance)
     }
 
     fn rent_allowance(&self) -> BalanceOf<T> {
@@ -252,11 +317,21 @@ impl<'a, T, E, V, L> ExecutionContext<'a, T, V, L>
         deferred_storage_writes: &mut Vec<DeferredStorageWrite>,
         call_stamps: &mut Vec<CallStamp>,
         executable: &E,
+        read_only: bool,
     ) -> ExecResult {
         if self.depth == self.config.max_depth as usize {
             Err(Error::<T>::MaxCallDepthReached)?
         }
 
+        if !self.is_reentry_allowed(dest) {
+            Err(Error::<T>::ReentranceDenied)?
+        }
+
+        let read_only = self.read_only || read_only;
+        if read_only && !value.is_zero() {
+            Err(Error::<T>::StateChangeDenied)?
+        }
+
         if gas_meter
             .charge(self.config, ExecFeeToken::Call)
             .is_out_of_gas()
@@ -269,12 +344,21 @@ impl<'a, T, E, V, L> ExecutionContext<'a, T, V, L>
         // cannot be changed before the first call
         // We do not allow 'calling' plain accounts. For transfering value
         // `seal_transfer` must be used.
-        let contract = if let Some(ContractInfo::Alive(info)) = rent::collect_rent::<T>(dest) {
+        //
+        // A read-only call must not change any state. Therefore no rent is collected from the
+        // callee and a callee that can't pay its rent is executed rather than evicted.
+        let contract_info = if read_only {
+            <ContractInfoOf<T>>::get(dest)
+        } else {
+            rent::collect_rent::<T>(dest)
+        };
+        let contract = if let Some(ContractInfo::Alive(info)) = contract_info {
             info
         } else {
             Err(Error::<T>::NotCallable)?
         };
 
+        let storage_mode = storage::storage_mode(&contract.trie_id);
         let pre_storage = child::root(&contract.child_trie_info());
         let mut post_storage = vec![];
 
@@ -285,7 +369,9 @@ impl<'a, T, E, V, L> ExecutionContext<'a, T, V, L>
         let mut output_data = vec![];
 
         let escrow_exec_result =
-            self.with_nested_context(dest.clone(), contract.trie_id.clone(), |nested| {
+            self.with_nested_context(dest.clone(), contract.trie_id.clone(), storage_mode, |nested| {
+                nested.read_only = read_only;
+
                 if value > BalanceOf::<T>::zero() {
                     escrow_transfer::<T>(
                         &escrow_account.clone(),
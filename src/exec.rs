@@ -17,7 +17,7 @@
 use crate::{
 	CodeHash, Config, ContractAddressFor, Event, RawEvent, Trait,
 	TrieId, BalanceOf, ContractInfo, TrieIdGenerator,
-	gas::{Gas, GasMeter, Token}, rent, storage, Error, ContractInfoOf
+	gas::{Gas, GasMeter, Token}, rent, storage, Error, ContractInfoOf, StorageMode,
 };
 use bitflags::bitflags;
 use sp_std::prelude::*;
@@ -46,6 +46,43 @@ bitflags! {
 	}
 }
 
+bitflags! {
+	/// Flags used by a contract to customize the behaviour of `seal_call`.
+	pub struct CallFlags: u32 {
+		/// Forward the input of the current contract to the callee.
+		///
+		/// The supplied input buffer is ignored. The input is moved to the callee and
+		/// therefore no longer available to the current contract.
+		const FORWARD_INPUT = 0x0000_0001;
+		/// Identical to `FORWARD_INPUT` but the input is cloned so that it is still
+		/// available to the current contract afterwards.
+		///
+		/// Can't be combined with `FORWARD_INPUT`.
+		const CLONE_INPUT = 0x0000_0002;
+		/// Do not return from the call but rather return the output of the callee to the
+		/// caller of the current contract.
+		///
+		/// The current contract does not resume execution after a successful call. This
+		/// includes a callee that reverted: its output and flags are returned as they are.
+		/// If the call fails the current contract resumes execution and receives the error
+		/// just as without this flag.
+		const TAIL_CALL = 0x0000_0004;
+		/// Allow the callee to reenter the current contract.
+		///
+		/// Without this flag any reentrancy into the current contract that originates from
+		/// the callee (or any of its callees) is denied. This includes the current contract
+		/// calling itself. `seal0::seal_call` always passes this flag.
+		const ALLOW_REENTRY = 0x0000_0008;
+		/// Deny the callee to change any state.
+		///
+		/// This applies to the whole call tree spawned by the callee. Any attempt to change
+		/// state fails with `StateChangeDenied`. The flag can't be revoked by a nested call.
+		/// No rent is collected from contracts called read-only: a contract that can't pay
+		/// its rent is executed rather than evicted.
+		const READ_ONLY = 0x0000_0010;
+	}
+}
+
 /// Describes whether we deal with a contract or a plain account.
 pub enum TransactorKind {
 	/// Transaction was initiated from a plain account. That can be either be through a
@@ -117,11 +154,41 @@ pub trait Ext {
 	///
 	/// Returns `None` if the `key` wasn't previously set by `set_storage` or
 	/// was deleted.
-	fn get_storage(&self, key: &StorageKey) -> Option<Vec<u8>>;
+	fn get_storage(&self, key: &[u8]) -> Option<Vec<u8>>;
 
 	/// Sets the storage entry by the given key to the specified value. If `value` is `None` then
 	/// the storage entry is deleted.
-	fn set_storage(&mut self, key: StorageKey, value: Option<Vec<u8>>);
+	///
+	/// Returns the size of the previous value or `None` if there was no entry. Fails with
+	/// `StateChangeDenied` within a read-only call.
+	fn set_storage(
+		&mut self,
+		key: &[u8],
+		value: Option<Vec<u8>>,
+	) -> Result<Option<u32>, DispatchError>;
+
+	/// Returns the size of the storage entry of the executing account by the given `key`.
+	///
+	/// Returns `None` if the `key` wasn't previously set by `set_storage` or
+	/// was deleted.
+	fn get_storage_size(&self, key: &[u8]) -> Option<u32>;
+
+	/// Deletes the storage entry by the given key and returns its value.
+	///
+	/// Returns `None` if the `key` wasn't previously set by `set_storage` or
+	/// was deleted. Fails with `StateChangeDenied` within a read-only call.
+	fn take_storage(&mut self, key: &[u8]) -> Result<Option<Vec<u8>>, DispatchError>;
+
+	/// Returns the smallest key of the executing account that starts with `prefix` and is
+	/// greater than `after`.
+	///
+	/// If `after` is `None` the smallest key starting with `prefix` is returned. Fails with
+	/// `StorageNotIterable` if the contract wasn't instantiated with `StorageMode::Unhashed`.
+	fn next_storage_key(
+		&self,
+		prefix: &[u8],
+		after: Option<&[u8]>,
+	) -> Result<Option<Vec<u8>>, DispatchError>;
 
 	/// Instantiate a contract from the given code.
 	///
@@ -157,12 +224,20 @@ pub trait Ext {
 	) -> Result<(), DispatchError>;
 
 	/// Call (possibly transferring some amount of funds) into the specified account.
+	///
+	/// Unless `allows_reentry` is set the callee (and any contract it calls in turn) is not
+	/// allowed to call back into the current contract for the duration of this call.
+	///
+	/// If `read_only` is set the callee (and any contract it calls in turn) is not allowed
+	/// to change any state.
 	fn call(
 		&mut self,
 		to: &AccountIdOf<Self::T>,
 		value: BalanceOf<Self::T>,
 		gas_meter: &mut GasMeter<Self::T>,
 		input_data: Vec<u8>,
+		allows_reentry: bool,
+		read_only: bool,
 	) -> ExecResult;
 
 	/// Restores the given destination contract sacrificing the current one.
@@ -178,7 +253,7 @@ pub trait Ext {
 		code_hash: CodeHash<Self::T>,
 		rent_allowance: BalanceOf<Self::T>,
 		delta: Vec<StorageKey>,
-	) -> Result<(), &'static str>;
+	) -> Result<(), DispatchError>;
 
 	/// Returns a reference to the account id of the caller.
 	fn caller(&self) -> &AccountIdOf<Self::T>;
@@ -186,6 +261,25 @@ pub trait Ext {
 	/// Returns a reference to the account id of the current contract.
 	fn address(&self) -> &AccountIdOf<Self::T>;
 
+	/// Returns a reference to the account id of the origin of the call stack.
+	///
+	/// This is the account that signed the transaction which started the execution.
+	fn origin(&self) -> &AccountIdOf<Self::T>;
+
+	/// Returns `true` if the caller of the current contract is the origin of the call stack.
+	fn caller_is_origin(&self) -> bool;
+
+	/// Returns `true` if `address` is an alive contract.
+	///
+	/// Tombstoned contracts are not considered contracts by this function.
+	fn is_contract(&self, address: &AccountIdOf<Self::T>) -> bool;
+
+	/// Returns the code hash of the alive contract at `address`, or `None` if there is none.
+	fn code_hash(&self, address: &AccountIdOf<Self::T>) -> Option<CodeHash<Self::T>>;
+
+	/// Returns the code hash of the current contract.
+	fn own_code_hash(&self) -> CodeHash<Self::T>;
+
 	/// Returns the balance of the current contract.
 	///
 	/// The `value_transferred` is already added.
@@ -209,10 +303,14 @@ pub trait Ext {
 	/// Deposit an event with the given topics.
 	///
 	/// There should not be any duplicates in `topics`.
-	fn deposit_event(&mut self, topics: Vec<TopicOf<Self::T>>, data: Vec<u8>);
+	fn deposit_event(
+		&mut self,
+		topics: Vec<TopicOf<Self::T>>,
+		data: Vec<u8>,
+	) -> Result<(), DispatchError>;
 
 	/// Set rent allowance of the contract
-	fn set_rent_allowance(&mut self, rent_allowance: BalanceOf<Self::T>);
+	fn set_rent_allowance(&mut self, rent_allowance: BalanceOf<Self::T>) -> Result<(), DispatchError>;
 
 	/// Rent allowance of the contract
 	fn rent_allowance(&self) -> BalanceOf<Self::T>;
@@ -234,10 +332,25 @@ pub trait Loader<T: Trait> {
 
 	/// Load the initializer portion of the code specified by the `code_hash`. This
 	/// executable is called upon instantiation.
-	fn load_init(&self, code_hash: &CodeHash<T>) -> Result<Self::Executable, &'static str>;
+	///
+	/// Code that needs to be re-instrumented before it can be executed is charged
+	/// to the `gas_meter`.
+	fn load_init(
+		&self,
+		code_hash: &CodeHash<T>,
+		gas_meter: &mut GasMeter<T>,
+	) -> Result<Self::Executable, DispatchError>;
 	/// Load the main portion of the code specified by the `code_hash`. This executable
 	/// is called for each call to a contract.
-	fn load_main(&self, code_hash: &CodeHash<T>) -> Result<Self::Executable, &'static str>;
+	///
+	/// If `read_only` is set loading the code must not change any state. Code that needs
+	/// to be re-instrumented is still charged but not written back in this case.
+	fn load_main(
+		&self,
+		code_hash: &CodeHash<T>,
+		gas_meter: &mut GasMeter<T>,
+		read_only: bool,
+	) -> Result<Self::Executable, DispatchError>;
 }
 
 /// A trait that represent a virtual machine.
@@ -284,12 +397,21 @@ pub struct ExecutionContext<'a, T: Trait + 'a, V, L> {
 	pub caller: Option<&'a ExecutionContext<'a, T, V, L>>,
 	pub self_account: T::AccountId,
 	pub self_trie_id: Option<TrieId>,
+	/// The storage mode of the contract of this context.
+	///
+	/// It is read once when the context is created so that storage accesses don't need to
+	/// look it up again.
+	pub storage_mode: StorageMode,
 	pub depth: usize,
 	pub config: &'a Config<T>,
 	pub vm: &'a V,
 	pub loader: &'a L,
 	pub timestamp: MomentOf<T>,
 	pub block_number: T::BlockNumber,
+	/// Whether the contract of this context can be reentered by the call it currently makes.
+	pub allows_reentry: bool,
+	/// Whether the contract of this context is denied to change any state.
+	pub read_only: bool,
 }
 
 impl<'a, T, E, V, L> ExecutionContext<'a, T, V, L>
@@ -306,6 +428,7 @@ where
 		ExecutionContext {
 			caller: None,
 			self_trie_id: None,
+			storage_mode: StorageMode::default(),
 			self_account: origin,
 			depth: 0,
 			config: &cfg,
@@ -313,15 +436,18 @@ where
 			loader: &loader,
 			timestamp: T::Time::now(),
 			block_number: <frame_system::Module<T>>::block_number(),
+			allows_reentry: true,
+			read_only: false,
 		}
 	}
 
-	fn nested<'b, 'c: 'b>(&'c self, dest: T::AccountId, trie_id: TrieId)
+	fn nested<'b, 'c: 'b>(&'c self, dest: T::AccountId, trie_id: TrieId, storage_mode: StorageMode)
 		-> ExecutionContext<'b, T, V, L>
 	{
 		ExecutionContext {
 			caller: Some(self),
 			self_trie_id: Some(trie_id),
+			storage_mode,
 			self_account: dest,
 			depth: self.depth + 1,
 			config: self.config,
@@ -329,21 +455,36 @@ where
 			loader: self.loader,
 			timestamp: self.timestamp.clone(),
 			block_number: self.block_number.clone(),
+			allows_reentry: true,
+			read_only: self.read_only,
 		}
 	}
 
 	/// Make a call to the specified address, optionally transferring some funds.
+	///
+	/// If `read_only` is set the callee and all contracts it calls are denied to change
+	/// any state. A call made from a read-only context is always read-only.
 	pub fn call(
 		&mut self,
 		dest: T::AccountId,
 		value: BalanceOf<T>,
 		gas_meter: &mut GasMeter<T>,
 		input_data: Vec<u8>,
+		read_only: bool,
 	) -> ExecResult {
 		if self.depth == self.config.max_depth as usize {
 			Err(Error::<T>::MaxCallDepthReached)?
 		}
 
+		if !self.is_reentry_allowed(&dest) {
+			Err(Error::<T>::ReentranceDenied)?
+		}
+
+		let read_only = self.read_only || read_only;
+		if read_only && !value.is_zero() {
+			Err(Error::<T>::StateChangeDenied)?
+		}
+
 		if gas_meter
 			.charge(self.config, ExecFeeToken::Call)
 			.is_out_of_gas()
@@ -356,7 +497,15 @@ where
 		// cannot be changed before the first call
 		// We do not allow 'calling' plain accounts. For transfering value
 		// `seal_transfer` must be used.
-		let contract = if let Some(ContractInfo::Alive(info)) = rent::collect_rent::<T>(&dest) {
+		//
+		// A read-only call must not change any state. Therefore no rent is collected from the
+		// callee and a callee that can't pay its rent is executed rather than evicted.
+		let contract_info = if read_only {
+			<ContractInfoOf<T>>::get(&dest)
+		} else {
+			rent::collect_rent::<T>(&dest)
+		};
+		let contract = if let Some(ContractInfo::Alive(info)) = contract_info {
 			info
 		} else {
 			Err(Error::<T>::NotCallable)?
@@ -365,7 +514,10 @@ where
 		let transactor_kind = self.transactor_kind();
 		let caller = self.self_account.clone();
 
-		self.with_nested_context(dest.clone(), contract.trie_id.clone(), |nested| {
+		let storage_mode = storage::storage_mode(&contract.trie_id);
+		self.with_nested_context(dest.clone(), contract.trie_id.clone(), storage_mode, |nested| {
+			nested.read_only = read_only;
+
 			if value > BalanceOf::<T>::zero() {
 				transfer(
 					gas_meter,
@@ -378,8 +530,7 @@ where
 				)?
 			}
 
-			let executable = nested.loader.load_main(&contract.code_hash)
-				.map_err(|_| Error::<T>::CodeNotFound)?;
+			let executable = nested.loader.load_main(&contract.code_hash, gas_meter, read_only)?;
 			let output = nested.vm.execute(
 				&executable,
 				nested.new_call_context(caller, value),
@@ -396,6 +547,24 @@ where
 		gas_meter: &mut GasMeter<T>,
 		code_hash: &CodeHash<T>,
 		input_data: Vec<u8>,
+	) -> Result<(T::AccountId, ExecReturnValue), ExecError> {
+		self.instantiate_with_storage_mode(
+			endowment,
+			gas_meter,
+			code_hash,
+			input_data,
+			StorageMode::default(),
+		)
+	}
+
+	/// Instantiate a contract whose storage keys are mapped according to `storage_mode`.
+	pub fn instantiate_with_storage_mode(
+		&mut self,
+		endowment: BalanceOf<T>,
+		gas_meter: &mut GasMeter<T>,
+		code_hash: &CodeHash<T>,
+		input_data: Vec<u8>,
+		storage_mode: StorageMode,
 	) -> Result<(T::AccountId, ExecReturnValue), ExecError> {
 		if self.depth == self.config.max_depth as usize {
 			Err(Error::<T>::MaxCallDepthReached)?
@@ -421,14 +590,15 @@ where
 		// Generate it now.
 		let dest_trie_id = <T as Trait>::TrieIdGenerator::trie_id(&dest);
 
-		let output = self.with_nested_context(dest.clone(), dest_trie_id, |nested| {
+		let output = self.with_nested_context(dest.clone(), dest_trie_id, storage_mode, |nested| {
 			storage::place_contract::<T>(
 				&dest,
 				nested
 					.self_trie_id
 					.clone()
 					.expect("the nested context always has to have self_trie_id"),
-				code_hash.clone()
+				code_hash.clone(),
+				storage_mode,
 			)?;
 
 			// Send funds unconditionally here. If the `endowment` is below existential_deposit
@@ -443,8 +613,7 @@ where
 				nested,
 			)?;
 
-			let executable = nested.loader.load_init(&code_hash)
-				.map_err(|_| Error::<T>::CodeNotFound)?;
+			let executable = nested.loader.load_init(&code_hash, gas_meter)?;
 			let output = nested.vm
 				.execute(
 					&executable,
@@ -469,6 +638,18 @@ where
 		Ok((dest, output))
 	}
 
+	/// The account that started the call stack this context belongs to.
+	///
+	/// The top level context is created on behalf of the transaction signer, so walking
+	/// the `caller` chain up to it yields the origin.
+	fn origin(&self) -> &T::AccountId {
+		let mut ctx = self;
+		while let Some(caller) = ctx.caller {
+			ctx = caller;
+		}
+		&ctx.self_account
+	}
+
 	pub fn new_call_context<'b>(
 		&'b mut self,
 		caller: T::AccountId,
@@ -486,12 +667,19 @@ where
 	}
 
 	/// Execute the given closure within a nested execution context.
-	pub fn with_nested_context<F>(&mut self, dest: T::AccountId, trie_id: TrieId, func: F)
-		-> ExecResult
+	///
+	/// The `storage_mode` must be the storage mode of the contract owning `trie_id`.
+	pub fn with_nested_context<F>(
+		&mut self,
+		dest: T::AccountId,
+		trie_id: TrieId,
+		storage_mode: StorageMode,
+		func: F,
+	) -> ExecResult
 		where F: FnOnce(&mut ExecutionContext<T, V, L>) -> ExecResult
 	{
 		use frame_support::storage::TransactionOutcome::*;
-		let mut nested = self.nested(dest, trie_id);
+		let mut nested = self.nested(dest, trie_id, storage_mode);
 		frame_support::storage::with_transaction(|| {
 			let output = func(&mut nested);
 			match output {
@@ -508,6 +696,21 @@ where
 			self.caller.map_or(false, |caller| caller.is_live(account))
 	}
 
+	/// Fails with `StateChangeDenied` if the contract of this context is executed read-only.
+	pub fn ensure_writable(&self) -> Result<(), Error<T>> {
+		ensure!(!self.read_only, Error::<T>::StateChangeDenied);
+		Ok(())
+	}
+
+	/// Returns whether a call into the contract at `account` is allowed in regard to reentrancy.
+	///
+	/// This is not the case if the contract is present in the execution stack and did not
+	/// allow to be reentered by the call it is currently making.
+	pub fn is_reentry_allowed(&self, account: &T::AccountId) -> bool {
+		(&self.self_account != account || self.allows_reentry) &&
+			self.caller.map_or(true, |caller| caller.is_reentry_allowed(account))
+	}
+
 	fn transactor_kind(&self) -> TransactorKind {
 		if self.depth == 0 {
 			debug_assert!(self.self_trie_id.is_none());
@@ -647,35 +850,94 @@ where
 {
 	type T = T;
 
-	fn get_storage(&self, key: &StorageKey) -> Option<Vec<u8>> {
+	fn get_storage(&self, key: &[u8]) -> Option<Vec<u8>> {
 		let trie_id = self.ctx.self_trie_id.as_ref().expect(
 			"`ctx.self_trie_id` points to an alive contract within the `CallContext`;\
 				it cannot be `None`;\
 				expect can't fail;\
 				qed",
 		);
-		storage::read_contract_storage(trie_id, key)
+		storage::read_contract_storage(trie_id, self.ctx.storage_mode, key)
 	}
 
-	fn set_storage(&mut self, key: StorageKey, value: Option<Vec<u8>>) {
+	fn set_storage(
+		&mut self,
+		key: &[u8],
+		value: Option<Vec<u8>>,
+	) -> Result<Option<u32>, DispatchError> {
+		self.ctx.ensure_writable()?;
 		let trie_id = self.ctx.self_trie_id.as_ref().expect(
 			"`ctx.self_trie_id` points to an alive contract within the `CallContext`;\
 				it cannot be `None`;\
 				expect can't fail;\
 				qed",
 		);
-		if let Err(storage::ContractAbsentError) =
-			storage::write_contract_storage::<T>(&self.ctx.self_account, trie_id, &key, value)
-		{
-			panic!(
+		match storage::write_contract_storage::<T>(
+			&self.ctx.self_account,
+			trie_id,
+			self.ctx.storage_mode,
+			key,
+			value,
+		) {
+			Ok(prev_len) => Ok(prev_len),
+			Err(storage::ContractAbsentError) => panic!(
 				"the contract must be in the alive state within the `CallContext`;\
 				the contract cannot be absent in storage;
 				write_contract_storage cannot return `None`;
 				qed"
-			);
+			),
 		}
 	}
 
+	fn get_storage_size(&self, key: &[u8]) -> Option<u32> {
+		let trie_id = self.ctx.self_trie_id.as_ref().expect(
+			"`ctx.self_trie_id` points to an alive contract within the `CallContext`;\
+				it cannot be `None`;\
+				expect can't fail;\
+				qed",
+		);
+		storage::contract_storage_size(trie_id, self.ctx.storage_mode, key)
+	}
+
+	fn take_storage(&mut self, key: &[u8]) -> Result<Option<Vec<u8>>, DispatchError> {
+		self.ctx.ensure_writable()?;
+		let trie_id = self.ctx.self_trie_id.as_ref().expect(
+			"`ctx.self_trie_id` points to an alive contract within the `CallContext`;\
+				it cannot be `None`;\
+				expect can't fail;\
+				qed",
+		);
+		Ok(storage::take_contract_storage::<T>(
+			&self.ctx.self_account,
+			trie_id,
+			self.ctx.storage_mode,
+			key,
+		).unwrap_or_else(|storage::ContractAbsentError| panic!(
+			"the contract must be in the alive state within the `CallContext`;\
+			the contract cannot be absent in storage;
+			take_contract_storage cannot return `None`;
+			qed"
+		)))
+	}
+
+	fn next_storage_key(
+		&self,
+		prefix: &[u8],
+		after: Option<&[u8]>,
+	) -> Result<Option<Vec<u8>>, DispatchError> {
+		let trie_id = self.ctx.self_trie_id.as_ref().expect(
+			"`ctx.self_trie_id` points to an alive contract within the `CallContext`;\
+				it cannot be `None`;\
+				expect can't fail;\
+				qed",
+		);
+		ensure!(
+			self.ctx.storage_mode == StorageMode::Unhashed,
+			Error::<T>::StorageNotIterable,
+		);
+		Ok(storage::next_child_key(trie_id, prefix, after))
+	}
+
 	fn instantiate(
 		&mut self,
 		code_hash: &CodeHash<T>,
@@ -683,6 +945,7 @@ where
 		gas_meter: &mut GasMeter<T>,
 		input_data: Vec<u8>,
 	) -> Result<(AccountIdOf<T>, ExecReturnValue), ExecError> {
+		self.ctx.ensure_writable()?;
 		self.ctx.instantiate(endowment, gas_meter, code_hash, input_data)
 	}
 
@@ -692,6 +955,7 @@ where
 		value: BalanceOf<T>,
 		gas_meter: &mut GasMeter<T>,
 	) -> Result<(), DispatchError> {
+		self.ctx.ensure_writable()?;
 		transfer(
 			gas_meter,
 			TransferCause::Call,
@@ -708,6 +972,7 @@ where
 		beneficiary: &AccountIdOf<Self::T>,
 		gas_meter: &mut GasMeter<Self::T>,
 	) -> Result<(), DispatchError> {
+		self.ctx.ensure_writable()?;
 		let self_id = self.ctx.self_account.clone();
 		let value = T::Currency::free_balance(&self_id);
 		if let Some(caller_ctx) = self.ctx.caller {
@@ -741,8 +1006,16 @@ where
 		value: BalanceOf<T>,
 		gas_meter: &mut GasMeter<T>,
 		input_data: Vec<u8>,
+		allows_reentry: bool,
+		read_only: bool,
 	) -> ExecResult {
-		self.ctx.call(to.clone(), value, gas_meter, input_data)
+		// Protect the current contract against reentrancy for the duration of this call.
+		// This is done before the call so that a direct recursion is caught as well.
+		self.ctx.allows_reentry = allows_reentry;
+		let result = self.ctx.call(to.clone(), value, gas_meter, input_data, read_only);
+		// The protection only applies to a single call.
+		self.ctx.allows_reentry = true;
+		result
 	}
 
 	fn restore_to(
@@ -751,12 +1024,13 @@ where
 		code_hash: CodeHash<Self::T>,
 		rent_allowance: BalanceOf<Self::T>,
 		delta: Vec<StorageKey>,
-	) -> Result<(), &'static str> {
+	) -> Result<(), DispatchError> {
+		self.ctx.ensure_writable()?;
 		if let Some(caller_ctx) = self.ctx.caller {
 			if caller_ctx.is_live(&self.ctx.self_account) {
-				return Err(
+				return Err(DispatchError::Other(
 					"Cannot perform restoration of a contract that is present on the call stack",
-				);
+				));
 			}
 		}
 
@@ -778,7 +1052,7 @@ where
 				),
 			);
 		}
-		result
+		result.map_err(DispatchError::Other)
 	}
 
 	fn address(&self) -> &T::AccountId {
@@ -789,6 +1063,31 @@ where
 		&self.caller
 	}
 
+	fn origin(&self) -> &T::AccountId {
+		self.ctx.origin()
+	}
+
+	fn caller_is_origin(&self) -> bool {
+		self.caller() == self.origin()
+	}
+
+	fn is_contract(&self, address: &T::AccountId) -> bool {
+		ContractInfoOf::<T>::get(address).and_then(|c| c.get_alive()).is_some()
+	}
+
+	fn code_hash(&self, address: &T::AccountId) -> Option<CodeHash<T>> {
+		ContractInfoOf::<T>::get(address).and_then(|c| c.get_alive()).map(|c| c.code_hash)
+	}
+
+	fn own_code_hash(&self) -> CodeHash<T> {
+		self.code_hash(&self.ctx.self_account).expect(
+			"the contract must be in the alive state within the `CallContext`;\
+				its code hash is always present;\
+				expect can't fail;\
+				qed",
+		)
+	}
+
 	fn balance(&self) -> BalanceOf<T> {
 		T::Currency::free_balance(&self.ctx.self_account)
 	}
@@ -813,14 +1112,17 @@ where
 		self.ctx.config.tombstone_deposit
 	}
 
-	fn deposit_event(&mut self, topics: Vec<T::Hash>, data: Vec<u8>) {
+	fn deposit_event(&mut self, topics: Vec<T::Hash>, data: Vec<u8>) -> Result<(), DispatchError> {
+		self.ctx.ensure_writable()?;
 		deposit_event::<Self::T>(
 			topics,
 			RawEvent::ContractExecution(self.ctx.self_account.clone(), data)
 		);
+		Ok(())
 	}
 
-	fn set_rent_allowance(&mut self, rent_allowance: BalanceOf<T>) {
+	fn set_rent_allowance(&mut self, rent_allowance: BalanceOf<T>) -> Result<(), DispatchError> {
+		self.ctx.ensure_writable()?;
 		if let Err(storage::ContractAbsentError) =
 			storage::set_rent_allowance::<T>(&self.ctx.self_account, rent_allowance)
 		{
@@ -829,6 +1131,7 @@ where
 					set_rent_allowance cannot return `Err`; qed"
 			);
 		}
+		Ok(())
 	}
 
 	fn rent_allowance(&self) -> BalanceOf<T> {
@@ -882,6 +1185,7 @@ mod tests {
 	const ALICE: u64 = 1;
 	const BOB: u64 = 2;
 	const CHARLIE: u64 = 3;
+	const DJANGO: u64 = 4;
 
 	const GAS_LIMIT: Gas = 10_000_000_000;
 
@@ -946,17 +1250,26 @@ mod tests {
 	impl<'a> Loader<Test> for MockLoader<'a> {
 		type Executable = MockExecutable<'a>;
 
-		fn load_init(&self, code_hash: &CodeHash<Test>) -> Result<Self::Executable, &'static str> {
+		fn load_init(
+			&self,
+			code_hash: &CodeHash<Test>,
+			_gas_meter: &mut GasMeter<Test>,
+		) -> Result<Self::Executable, DispatchError> {
 			self.map
 				.get(code_hash)
 				.cloned()
-				.ok_or_else(|| "code not found")
+				.ok_or_else(|| Error::<Test>::CodeNotFound.into())
 		}
-		fn load_main(&self, code_hash: &CodeHash<Test>) -> Result<Self::Executable, &'static str> {
+		fn load_main(
+			&self,
+			code_hash: &CodeHash<Test>,
+			_gas_meter: &mut GasMeter<Test>,
+			_read_only: bool,
+		) -> Result<Self::Executable, DispatchError> {
 			self.map
 				.get(code_hash)
 				.cloned()
-				.ok_or_else(|| "code not found")
+				.ok_or_else(|| Error::<Test>::CodeNotFound.into())
 		}
 	}
 
@@ -1004,7 +1317,7 @@ mod tests {
 			place_contract(&BOB, exec_ch);
 
 			assert_matches!(
-				ctx.call(BOB, value, &mut gas_meter, data),
+				ctx.call(BOB, value, &mut gas_meter, data, false),
 				Ok(_)
 			);
 		});
@@ -1122,6 +1435,7 @@ mod tests {
 				55,
 				&mut GasMeter::<Test>::new(GAS_LIMIT),
 				vec![],
+				false,
 			).unwrap();
 
 			assert!(!output.is_success());
@@ -1286,6 +1600,7 @@ mod tests {
 				0,
 				&mut GasMeter::<Test>::new(GAS_LIMIT),
 				vec![],
+				false,
 			);
 
 			let output = result.unwrap();
@@ -1317,6 +1632,7 @@ mod tests {
 				0,
 				&mut GasMeter::<Test>::new(GAS_LIMIT),
 				vec![],
+				false,
 			);
 
 			let output = result.unwrap();
@@ -1345,6 +1661,7 @@ mod tests {
 				0,
 				&mut GasMeter::<Test>::new(GAS_LIMIT),
 				vec![1, 2, 3, 4],
+				false,
 			);
 			assert_matches!(result, Ok(_));
 		});
@@ -1387,7 +1704,7 @@ mod tests {
 		let mut loader = MockLoader::empty();
 		let recurse_ch = loader.insert(|ctx| {
 			// Try to call into yourself.
-			let r = ctx.ext.call(&BOB, 0, ctx.gas_meter, vec![]);
+			let r = ctx.ext.call(&BOB, 0, ctx.gas_meter, vec![], true, false);
 
 			let mut reached_bottom = reached_bottom.borrow_mut();
 			if !*reached_bottom {
@@ -1417,6 +1734,7 @@ mod tests {
 				value,
 				&mut GasMeter::<Test>::new(GAS_LIMIT),
 				vec![],
+				false,
 			);
 
 			assert_matches!(result, Ok(_));
@@ -1440,7 +1758,7 @@ mod tests {
 
 			// Call into CHARLIE contract.
 			assert_matches!(
-				ctx.ext.call(&CHARLIE, 0, ctx.gas_meter, vec![]),
+				ctx.ext.call(&CHARLIE, 0, ctx.gas_meter, vec![], true, false),
 				Ok(_)
 			);
 			exec_success()
@@ -1463,6 +1781,7 @@ mod tests {
 				0,
 				&mut GasMeter::<Test>::new(GAS_LIMIT),
 				vec![],
+				false,
 			);
 
 			assert_matches!(result, Ok(_));
@@ -1483,7 +1802,7 @@ mod tests {
 
 			// Call into charlie contract.
 			assert_matches!(
-				ctx.ext.call(&CHARLIE, 0, ctx.gas_meter, vec![]),
+				ctx.ext.call(&CHARLIE, 0, ctx.gas_meter, vec![], true, false),
 				Ok(_)
 			);
 			exec_success()
@@ -1504,12 +1823,223 @@ mod tests {
 				0,
 				&mut GasMeter::<Test>::new(GAS_LIMIT),
 				vec![],
+				false,
+			);
+
+			assert_matches!(result, Ok(_));
+		});
+	}
+
+	#[test]
+	fn origin_returns_proper_values() {
+		let vm = MockVm::new();
+
+		let mut loader = MockLoader::empty();
+		let bob_ch = loader.insert(|ctx| {
+			// BOB is called directly by the origin.
+			assert_eq!(*ctx.ext.origin(), ALICE);
+			assert!(ctx.ext.caller_is_origin());
+
+			// Call into charlie contract.
+			assert_matches!(
+				ctx.ext.call(&CHARLIE, 0, ctx.gas_meter, vec![], true, false),
+				Ok(_)
+			);
+			exec_success()
+		});
+		let charlie_ch = loader.insert(|ctx| {
+			// The origin stays the same while the caller is now BOB.
+			assert_eq!(*ctx.ext.origin(), ALICE);
+			assert!(!ctx.ext.caller_is_origin());
+			exec_success()
+		});
+
+		ExtBuilder::default().build().execute_with(|| {
+			let cfg = Config::preload();
+			let mut ctx = ExecutionContext::top_level(ALICE, &cfg, &vm, &loader);
+			place_contract(&BOB, bob_ch);
+			place_contract(&CHARLIE, charlie_ch);
+
+			let result = ctx.call(
+				BOB,
+				0,
+				&mut GasMeter::<Test>::new(GAS_LIMIT),
+				vec![],
+				false,
 			);
 
 			assert_matches!(result, Ok(_));
 		});
 	}
 
+	#[test]
+	fn code_hash_returns_proper_values() {
+		let vm = MockVm::new();
+
+		let mut loader = MockLoader::empty();
+		let bob_ch = loader.insert(|ctx| {
+			// ALICE is a plain account.
+			assert!(!ctx.ext.is_contract(&ALICE));
+			assert_eq!(ctx.ext.code_hash(&ALICE), None);
+
+			// BOB and CHARLIE are contracts running different code.
+			assert!(ctx.ext.is_contract(&BOB));
+			assert!(ctx.ext.is_contract(&CHARLIE));
+			assert_eq!(ctx.ext.code_hash(&BOB), Some(ctx.ext.own_code_hash()));
+			assert!(ctx.ext.code_hash(&CHARLIE).is_some());
+			assert_ne!(ctx.ext.code_hash(&CHARLIE), ctx.ext.code_hash(&BOB));
+			exec_success()
+		});
+		let charlie_ch = loader.insert(|_| exec_success());
+
+		ExtBuilder::default().build().execute_with(|| {
+			let cfg = Config::preload();
+			let mut ctx = ExecutionContext::top_level(ALICE, &cfg, &vm, &loader);
+			place_contract(&BOB, bob_ch);
+			place_contract(&CHARLIE, charlie_ch);
+
+			let result = ctx.call(
+				BOB,
+				0,
+				&mut GasMeter::<Test>::new(GAS_LIMIT),
+				vec![],
+				false,
+			);
+
+			assert_matches!(result, Ok(_));
+		});
+	}
+
+	#[test]
+	fn reentrance_is_denied_unless_allowed() {
+		let vm = MockVm::new();
+
+		let witnessed_reentry = RefCell::new(None::<ExecResult>);
+
+		let mut loader = MockLoader::empty();
+		let bob_ch = loader.insert(|ctx| {
+			match ctx.input_data[0] {
+				// Call into CHARLIE which tries to reenter BOB.
+				0 => assert_matches!(
+					ctx.ext.call(&CHARLIE, 0, ctx.gas_meter, vec![], false, false),
+					Ok(_)
+				),
+				1 => assert_matches!(
+					ctx.ext.call(&CHARLIE, 0, ctx.gas_meter, vec![], true, false),
+					Ok(_)
+				),
+				// A direct recursion is denied as well.
+				2 => assert_eq!(
+					ctx.ext.call(&BOB, 0, ctx.gas_meter, vec![3], false, false),
+					Err(Error::<Test>::ReentranceDenied.into())
+				),
+				_ => (),
+			}
+			exec_success()
+		});
+		let charlie_ch = loader.insert(|ctx| {
+			*witnessed_reentry.borrow_mut() =
+				Some(ctx.ext.call(&BOB, 0, ctx.gas_meter, vec![3], true, false));
+			exec_success()
+		});
+
+		ExtBuilder::default().build().execute_with(|| {
+			let cfg = Config::preload();
+			let mut ctx = ExecutionContext::top_level(ALICE, &cfg, &vm, &loader);
+			place_contract(&BOB, bob_ch);
+			place_contract(&CHARLIE, charlie_ch);
+
+			assert_matches!(
+				ctx.call(BOB, 0, &mut GasMeter::<Test>::new(GAS_LIMIT), vec![0], false),
+				Ok(_)
+			);
+			assert_eq!(
+				witnessed_reentry.borrow_mut().take(),
+				Some(Err(Error::<Test>::ReentranceDenied.into()))
+			);
+
+			assert_matches!(
+				ctx.call(BOB, 0, &mut GasMeter::<Test>::new(GAS_LIMIT), vec![1], false),
+				Ok(_)
+			);
+			assert_matches!(witnessed_reentry.borrow_mut().take(), Some(Ok(_)));
+
+			assert_matches!(
+				ctx.call(BOB, 0, &mut GasMeter::<Test>::new(GAS_LIMIT), vec![2], false),
+				Ok(_)
+			);
+		});
+	}
+
+	#[test]
+	fn read_only_call_denies_state_changes() {
+		let vm = MockVm::new();
+
+		let mut loader = MockLoader::empty();
+		let bob_ch = loader.insert(|ctx| {
+			let denied: DispatchError = Error::<Test>::StateChangeDenied.into();
+			let read_only = ctx.input_data[0] == 1;
+
+			// Value can't be transferred along with a read-only call.
+			assert_eq!(
+				ctx.ext.call(&CHARLIE, 1, ctx.gas_meter, vec![], false, true),
+				Err(denied.into())
+			);
+
+			// CHARLIE can't change state when called read-only.
+			assert_matches!(
+				ctx.ext.call(&CHARLIE, 0, ctx.gas_meter, vec![], false, true),
+				Ok(_)
+			);
+
+			// BOB itself is only restricted if it was called read-only.
+			assert_eq!(ctx.ext.set_storage(&[1; 32], Some(vec![1])).is_err(), read_only);
+			exec_success()
+		});
+		let charlie_ch = loader.insert(|ctx| {
+			let denied: DispatchError = Error::<Test>::StateChangeDenied.into();
+			assert_eq!(ctx.ext.set_storage(&[1; 32], Some(vec![1])), Err(denied));
+			assert_eq!(ctx.ext.take_storage(&[1; 32]), Err(denied));
+			assert_eq!(ctx.ext.transfer(&ALICE, 0, ctx.gas_meter), Err(denied));
+			assert_eq!(ctx.ext.deposit_event(vec![], vec![]), Err(denied));
+			assert_eq!(ctx.ext.set_rent_allowance(10), Err(denied));
+
+			// Nested calls are read-only as well, even if they don't ask for it.
+			assert_eq!(
+				ctx.ext.call(&DJANGO, 0, ctx.gas_meter, vec![], false, false)
+					.map_err(|err| err.error),
+				Err(denied)
+			);
+			exec_success()
+		});
+		let django_ch = loader.insert(|ctx| {
+			ctx.ext.set_storage(&[1; 32], Some(vec![1]))?;
+			exec_success()
+		});
+
+		ExtBuilder::default().build().execute_with(|| {
+			let cfg = Config::preload();
+			let mut ctx = ExecutionContext::top_level(ALICE, &cfg, &vm, &loader);
+			set_balance(&ALICE, 100);
+			place_contract(&BOB, bob_ch);
+			place_contract(&CHARLIE, charlie_ch);
+			place_contract(&DJANGO, django_ch);
+
+			assert_matches!(
+				ctx.call(BOB, 0, &mut GasMeter::<Test>::new(GAS_LIMIT), vec![0], false),
+				Ok(_)
+			);
+			assert_matches!(
+				ctx.call(BOB, 0, &mut GasMeter::<Test>::new(GAS_LIMIT), vec![1], true),
+				Ok(_)
+			);
+			assert_eq!(
+				ctx.call(BOB, 1, &mut GasMeter::<Test>::new(GAS_LIMIT), vec![1], true),
+				Err(Error::<Test>::StateChangeDenied.into())
+			);
+		});
+	}
+
 	#[test]
 	fn refuse_instantiate_with_value_below_existential_deposit() {
 		let vm = MockVm::new();
@@ -1628,7 +2158,7 @@ mod tests {
 			place_contract(&BOB, instantiator_ch);
 
 			assert_matches!(
-				ctx.call(BOB, 20, &mut GasMeter::<Test>::new(GAS_LIMIT), vec![]),
+				ctx.call(BOB, 20, &mut GasMeter::<Test>::new(GAS_LIMIT), vec![], false),
 				Ok(_)
 			);
 
@@ -1680,7 +2210,7 @@ mod tests {
 			place_contract(&BOB, instantiator_ch);
 
 			assert_matches!(
-				ctx.call(BOB, 20, &mut GasMeter::<Test>::new(GAS_LIMIT), vec![]),
+				ctx.call(BOB, 20, &mut GasMeter::<Test>::new(GAS_LIMIT), vec![], false),
 				Ok(_)
 			);
 
@@ -1732,7 +2262,7 @@ mod tests {
 		let mut loader = MockLoader::empty();
 		let rent_allowance_ch = loader.insert(|ctx| {
 			assert_eq!(ctx.ext.rent_allowance(), <BalanceOf<Test>>::max_value());
-			ctx.ext.set_rent_allowance(10);
+			ctx.ext.set_rent_allowance(10).unwrap();
 			assert_eq!(ctx.ext.rent_allowance(), 10);
 			exec_success()
 		});
@@ -189,12 +189,18 @@ impl<T: Trait> GasMeter<T> {
 	}
 
 	/// Turn this GasMeter into a DispatchResult that contains the actually used gas.
-	pub fn into_dispatch_result<R, E>(self, result: Result<R, E>) -> DispatchResultWithPostInfo
+	///
+	/// The `base_weight` of the dispatchable is added to the used gas.
+	pub fn into_dispatch_result<R, E>(
+		self,
+		result: Result<R, E>,
+		base_weight: Gas,
+	) -> DispatchResultWithPostInfo
 	where
 		E: Into<ExecError>,
 	{
 		let post_info = PostDispatchInfo {
-			actual_weight: Some(self.gas_spent()),
+			actual_weight: Some(self.gas_spent().saturating_add(base_weight)),
 			pays_fee: Default::default(),
 		};
 
@@ -54,15 +54,32 @@
 //! and the call will only revert at the specific contract level. For example, if contract A calls contract B, and B
 //! fails, A can decide how to handle that failure, either proceeding or reverting A's changes.
 //!
+//! ### Reentrancy
+//!
+//! A contract that calls another contract with `seal1::seal_call` can't be reentered by the callee (or any contract
+//! called by it) unless it passes the `ALLOW_REENTRY` flag. A reentrant call fails with `ReentranceDenied`. The
+//! protection only applies to `seal1::seal_call`: `seal0::seal_call` implicitly passes `ALLOW_REENTRY` so that
+//! existing contracts keep working as before.
+//!
 //! ## Interface
 //!
 //! ### Dispatchable functions
 //!
 //! * `put_code` - Stores the given binary Wasm code into the chain's storage and returns its `code_hash`.
+//! * `reinstrument_code` - Re-instruments stored code with the current schedule so that the next caller
+//! doesn't have to pay for it.
 //! * `instantiate` - Deploys a new contract from the given `code_hash`, optionally transferring some balance.
 //! This instantiates a new smart contract account and calls its contract deploy handler to
 //! initialize the contract.
+//! * `instantiate_with_storage_mode` - Like `instantiate` but allows to choose an unhashed storage mode
+//! which makes the contract's storage iterable.
 //! * `call` - Makes a call to an account, optionally transferring some balance.
+//! * `restore_contract` - Restores an evicted contract from its tombstone and the supplied storage.
+//! * `restore_contract_with_proof` - Restores an evicted contract from a proven subset of its storage.
+//! * `deposit_rent` - Deposits funds into a contract's account to pay for its rent, optionally raising
+//! its rent allowance.
+//! * `import_storage` - Seeds the empty storage of a contract with items exported by `export_storage`.
+//! Can only be called by root.
 //!
 //! ## Usage
 //!
@@ -87,16 +104,20 @@ pub mod exec;
 pub mod rent;
 pub mod storage;
 pub mod wasm;
+pub mod weights;
 
 #[cfg(test)]
 mod tests;
 
-use crate::exec::ExecutionContext;
+use crate::exec::{ExecutionContext, StorageKey};
 use crate::wasm::{WasmLoader, WasmVm};
 
 pub use crate::gas::{Gas, GasMeter};
-pub use crate::exec::{ExecResult, ExecReturnValue};
+pub use crate::exec::{ExecResult, ExecReturnValue, CallFlags};
 pub use crate::wasm::ReturnCode as RuntimeReturnCode;
+pub use crate::wasm::{PrepareError, ValidatedCode};
+pub use crate::rent::{RentCurve, LinearRentCurve};
+pub use crate::weights::WeightInfo;
 
 #[cfg(feature = "std")]
 use serde::{Serialize, Deserialize};
@@ -106,18 +127,20 @@ use codec::{Codec, Encode, Decode};
 use sp_runtime::{
 	traits::{
 		Hash, StaticLookup, Zero, MaybeSerializeDeserialize, Member, Convert, Saturating,
+		SaturatedConversion,
 	},
 	RuntimeDebug,
 };
 use frame_support::{
 	decl_module, decl_event, decl_storage, decl_error, ensure,
-	parameter_types, storage::child::ChildInfo,
+	parameter_types, storage::{child::{self, ChildInfo}, StoragePrefixedMap, IterableStorageMap},
 	dispatch::{DispatchResult, DispatchResultWithPostInfo},
-	traits::{OnUnbalanced, Currency, Get, Time, Randomness},
+	traits::{OnUnbalanced, Currency, Get, Time, Randomness, ExistenceRequirement},
 };
 use frame_system::{ensure_signed, ensure_root};
 use pallet_contracts_primitives::{RentProjection, ContractAccessError};
 use frame_support::weights::Weight;
+use sp_io::hashing::blake2_256;
 
 pub type CodeHash<T> = <T as frame_system::Trait>::Hash;
 pub type TrieId = Vec<u8>;
@@ -187,6 +210,29 @@ impl<T: Trait> ContractInfo<T> {
 	}
 }
 
+/// The layout of `ContractInfo` before tombstones kept the storage root of the contract.
+/// Only used to migrate the entries of `ContractInfoOf`.
+#[derive(Encode, Decode)]
+enum ContractInfoV0<T: Trait> {
+	Alive(AliveContractInfo<T>),
+	Tombstone(T::Hash),
+}
+
+/// The versions of the storage layout of this module.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+enum Releases {
+	/// Tombstones consist of a hash only.
+	V2_0_0,
+	/// Tombstones keep the storage root and the storage mode of the evicted contract.
+	V3_0_0,
+}
+
+impl Default for Releases {
+	fn default() -> Self {
+		Releases::V2_0_0
+	}
+}
+
 pub type AliveContractInfo<T> =
 	RawAliveContractInfo<CodeHash<T>, BalanceOf<T>, <T as frame_system::Trait>::BlockNumber>;
 
@@ -227,11 +273,69 @@ pub(crate) fn child_trie_info(trie_id: &[u8]) -> ChildInfo {
 	ChildInfo::new_default(trie_id)
 }
 
+/// Describes how the keys used by a contract are mapped to the keys of its child trie.
+///
+/// The mode is chosen when the contract is instantiated and can't be changed afterwards.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum StorageMode {
+	/// Keys are hashed with `blake2_256` before they are used. The original keys can't be
+	/// recovered from the child trie.
+	Hashed,
+	/// Keys are used as they are. This allows to iterate the storage of a contract in the
+	/// order of its keys.
+	Unhashed,
+}
+
+impl Default for StorageMode {
+	fn default() -> Self {
+		StorageMode::Hashed
+	}
+}
+
+impl StorageMode {
+	/// Returns the key of the child trie under which the value for `key` is stored.
+	pub fn child_key(&self, key: &[u8]) -> Vec<u8> {
+		match self {
+			StorageMode::Hashed => blake2_256(key).to_vec(),
+			StorageMode::Unhashed => key.to_vec(),
+		}
+	}
+}
+
+/// A snapshot of the storage of a contract as returned by [`Module::export_storage`].
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct ExportedStorage {
+	/// The storage mode of the contract. The keys of `items` are the keys used by the contract
+	/// only for `StorageMode::Unhashed`. Otherwise they are their hashes.
+	pub storage_mode: StorageMode,
+	/// All key-value pairs of the contract's child trie in ascending order of their keys.
+	pub items: Vec<(Vec<u8>, Vec<u8>)>,
+	/// The root of the contract's child trie.
+	pub child_root: Vec<u8>,
+}
+
 pub type TombstoneContractInfo<T> =
 	RawTombstoneContractInfo<<T as frame_system::Trait>::Hash, <T as frame_system::Trait>::Hashing>;
 
-#[derive(Encode, Decode, PartialEq, Eq, RuntimeDebug)]
-pub struct RawTombstoneContractInfo<H, Hasher>(H, PhantomData<Hasher>);
+/// The remains of an evicted contract.
+///
+/// Besides the hash of the storage root and the code hash of the evicted contract the storage
+/// root itself is kept so that restorations can be verified against storage proofs. The storage
+/// mode is kept because the storage root was computed over the keys as written by this mode.
+///
+/// Tombstones are compared by their hash and storage mode only. Tombstones that were created
+/// before the storage root was kept are migrated with an empty storage root (see
+/// `Module::migrate_tombstones`). Hence, they can't be restored from storage proofs.
+#[derive(Encode, Decode, RuntimeDebug)]
+pub struct RawTombstoneContractInfo<H, Hasher>(H, Vec<u8>, StorageMode, PhantomData<Hasher>);
+
+impl<H: PartialEq, Hasher> PartialEq for RawTombstoneContractInfo<H, Hasher> {
+	fn eq(&self, other: &Self) -> bool {
+		self.0 == other.0 && self.2 == other.2
+	}
+}
+
+impl<H: Eq, Hasher> Eq for RawTombstoneContractInfo<H, Hasher> {}
 
 impl<H, Hasher> RawTombstoneContractInfo<H, Hasher>
 where
@@ -240,14 +344,53 @@ where
 		+ sp_std::hash::Hash + Codec,
 	Hasher: Hash<Output=H>,
 {
-	fn new(storage_root: &[u8], code_hash: H) -> Self {
+	fn new(storage_root: &[u8], code_hash: H, storage_mode: StorageMode) -> Self {
 		let mut buf = Vec::new();
 		storage_root.using_encoded(|encoded| buf.extend_from_slice(encoded));
 		buf.extend_from_slice(code_hash.as_ref());
-		RawTombstoneContractInfo(<Hasher as Hash>::hash(&buf[..]), PhantomData)
+		let hash = <Hasher as Hash>::hash(&buf[..]);
+		RawTombstoneContractInfo(hash, storage_root.to_vec(), storage_mode, PhantomData)
+	}
+
+	/// The root of the child trie the contract had at the moment of its eviction.
+	pub fn storage_root(&self) -> &[u8] {
+		&self.1
+	}
+
+	/// The storage mode the contract had at the moment of its eviction.
+	pub fn storage_mode(&self) -> StorageMode {
+		self.2
 	}
 }
 
+pub type RentStatus<T> =
+	RawRentStatus<BalanceOf<T>, <T as frame_system::Trait>::BlockNumber>;
+
+/// A detailed breakdown of the rent situation of an alive contract.
+///
+/// All values are relevant for the current block, i.e. as if the contract was accessed
+/// at the beginning of it.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct RawRentStatus<Balance, BlockNumber> {
+	/// The amount of rent that is charged for each block as determined by [`Trait::RentCurve`].
+	pub fee_per_block: Balance,
+	/// The number of bytes the contract pays rent for as determined by [`Trait::RentCurve`].
+	pub effective_storage_size: Balance,
+	/// The number of bytes that are offset by the balance of the contract as determined by
+	/// [`Trait::RentCurve`].
+	pub free_storage: Balance,
+	/// The maximum amount of rent that can be paid by the contract before it is evicted.
+	pub rent_budget: Balance,
+	/// The remaining rent allowance of the contract.
+	pub rent_allowance: Balance,
+	/// The block at which the contract will be evicted or `None` if it is exempt from rent.
+	pub eviction_at: Option<BlockNumber>,
+	/// The amount that needs to be deposited into the contract's account so that it
+	/// becomes exempt from rent as determined by [`Trait::RentCurve`].
+	pub exemption_deposit: Balance,
+}
+
 impl<T: Trait> From<AliveContractInfo<T>> for ContractInfo<T> {
 	fn from(alive_info: AliveContractInfo<T>) -> Self {
 		Self::Alive(alive_info)
@@ -312,6 +455,8 @@ parameter_types! {
 	pub const DefaultMaxDepth: u32 = 32;
 	/// A reasonable default value for [`Trait::MaxValueSize`].
 	pub const DefaultMaxValueSize: u32 = 16_384;
+	/// A reasonable default value for [`Trait::MaxReinstrumentWeight`].
+	pub const DefaultMaxReinstrumentWeight: Weight = 100_000_000_000;
 }
 
 pub trait Trait: frame_system::Trait {
@@ -333,6 +478,12 @@ pub trait Trait: frame_system::Trait {
 	/// Handler for rent payments.
 	type RentPayment: OnUnbalanced<NegativeImbalanceOf<Self>>;
 
+	/// The pricing model that determines the rent a contract has to pay per block.
+	///
+	/// [`LinearRentCurve`] implements a pricing based on [`Self::RentByteFee`] and
+	/// [`Self::RentDepositOffset`].
+	type RentCurve: RentCurve<Self>;
+
 	/// Number of block delay an extrinsic claim surcharge has.
 	///
 	/// When claim surcharge is called by an extrinsic the rent is checked
@@ -371,9 +522,37 @@ pub trait Trait: frame_system::Trait {
 	/// The maximum size of a storage value in bytes.
 	type MaxValueSize: Get<u32>;
 
+	/// The maximum weight that is spent per block on re-instrumenting outdated code
+	/// in the background after the schedule was updated.
+	type MaxReinstrumentWeight: Get<Weight>;
+
+	/// Whether custom sections are stripped from the pristine code before it is stored.
+	///
+	/// The code hash is then the hash of the stripped code which differs from the hash of
+	/// the uploaded code if it contains custom sections. Clients that derive the code hash
+	/// from the uploaded bytes must take it from the `CodeStored` event instead. Custom
+	/// sections are always stripped from the instrumented code.
+	type StripPristineCode: Get<bool>;
+
 	/// Used to answer contracts's queries regarding the current weight price. This is **not**
 	/// used to calculate the actual fee and is only for informational purposes.
 	type WeightPrice: Convert<Weight, BalanceOf<Self>>;
+
+	/// Weight information for the dispatchables of this pallet.
+	type WeightInfo: WeightInfo;
+}
+
+/// Check whether a chain with the given `schedule` would accept the given code.
+///
+/// This is the same as [`Module::validate_code`] but doesn't require access to the chain's
+/// storage. It allows tooling to check contracts against a schedule that was fetched from
+/// the chain before deploying them.
+#[cfg(feature = "std")]
+pub fn validate_code_with_schedule(
+	code: &[u8],
+	schedule: &Schedule,
+) -> sp_std::result::Result<ValidatedCode, PrepareError> {
+	wasm::validate_code(code, schedule)
 }
 
 /// Simple contract address determiner.
@@ -404,6 +583,13 @@ decl_error! {
 	pub enum Error for Module<T: Trait> {
 		/// A new schedule must have a greater version than the current one.
 		InvalidScheduleVersion,
+		/// A limit of the new schedule is outside of its sane bounds.
+		InvalidScheduleLimit,
+		/// A limit of the new schedule is lower than in the current one. Such an update
+		/// can break existing contracts and must be forced.
+		ScheduleLimitDecreased,
+		/// Another schedule update is already scheduled for activation.
+		ScheduleUpdatePending,
 		/// An origin must be signed or inherent and auxiliary sender only provided on inherent.
 		InvalidSurchargeClaim,
 		/// Cannot restore from nonexisting or tombstone contract.
@@ -446,6 +632,69 @@ decl_error! {
 		DecodingFailed,
 		/// Contract trapped during execution.
 		ContractTrapped,
+		/// The code can not be decoded as a wasm module.
+		CodeDecodingFailed,
+		/// The code is not a valid wasm module according to the wasm specification.
+		InvalidModule,
+		/// The code declares an internal memory instead of importing it.
+		InternalMemory,
+		/// The memory is not imported as `env.memory` or it is imported multiple times.
+		InvalidMemoryImport,
+		/// The imported memory declares no maximum or an initial size above its maximum.
+		InvalidMemoryLimits,
+		/// The maximum of the imported memory exceeds the limit of the current schedule.
+		MemoryLimitExceeded,
+		/// The code declares more than one table.
+		MultipleTables,
+		/// The initial size of the table exceeds the limit of the current schedule.
+		TableLimitExceeded,
+		/// The code uses floating point types or instructions.
+		FloatingPointUsed,
+		/// The code doesn't export the `deploy` or the `call` function.
+		MissingExport,
+		/// An exported entry point is not a function defined by the code or has the
+		/// wrong signature.
+		InvalidEntryPoint,
+		/// The code declares the `contract-metadata` custom section more than once.
+		DuplicateMetadata,
+		/// The code imports a table or a global.
+		ForbiddenImport,
+		/// The code imports a function from another module than `seal0` or one of its
+		/// later versions.
+		InvalidImportModule,
+		/// The code imports a function that is not available to contracts or imports it
+		/// with the wrong signature.
+		UnknownImport,
+		/// The code imports `seal_println` which is not enabled by the current schedule.
+		PrintlnDisabled,
+		/// Injecting the gas metering into the code failed.
+		GasInstrumentationFailed,
+		/// Injecting the stack height metering into the code failed.
+		StackHeightInstrumentationFailed,
+		/// The instrumented code could not be serialized.
+		SerializationFailed,
+		/// A storage key exceeds `Schedule::max_storage_key_len`.
+		StorageKeyTooLarge,
+		/// The storage of the contract can't be iterated because it wasn't instantiated
+		/// with `StorageMode::Unhashed`.
+		StorageNotIterable,
+		/// Storage can only be imported into a contract which doesn't store anything yet.
+		StorageNotEmpty,
+		/// The child trie root of the imported storage doesn't match the expected root.
+		InvalidStorageRoot,
+		/// `seal_call` was invoked with flags that contain reserved bits or that
+		/// request to forward and clone the input at the same time.
+		InvalidCallFlags,
+		/// `seal_call` was asked to forward or clone the input of the current contract
+		/// but the input was already forwarded or read by `seal_input`.
+		InputForwarded,
+		/// The called contract is already on the call stack and did not allow to be reentered.
+		ReentranceDenied,
+		/// A contract tried to change state while being executed read-only.
+		StateChangeDenied,
+		/// A cost of the new schedule is lower than its floor. Such a cost would make the
+		/// priced operation (almost) free.
+		InvalidScheduleWeight,
 	}
 }
 
@@ -494,27 +743,79 @@ decl_module! {
 		/// The maximum size of a storage value in bytes. A reasonable default is 16 KiB.
 		const MaxValueSize: u32 = T::MaxValueSize::get();
 
+		/// The maximum weight that is spent per block on re-instrumenting outdated code
+		/// in the background after the schedule was updated.
+		const MaxReinstrumentWeight: Weight = T::MaxReinstrumentWeight::get();
+
+		/// Whether custom sections are stripped from the pristine code before it is stored.
+		const StripPristineCode: bool = T::StripPristineCode::get();
+
 		fn deposit_event() = default;
 
+		fn on_runtime_upgrade() -> Weight {
+			Self::migrate_schedule().saturating_add(Self::migrate_tombstones())
+		}
+
+		fn on_initialize(now: T::BlockNumber) -> Weight {
+			Self::activate_pending_schedule(now)
+				.saturating_add(wasm::migrate_code::<T>(T::MaxReinstrumentWeight::get()))
+		}
+
 		/// Updates the schedule for metering contracts.
 		///
-		/// The schedule must have a greater version than the stored schedule.
-		#[weight = 0]
+		/// The schedule must have a greater version than the stored schedule. Its limits must be
+		/// within their sane bounds and must not be lower than the limits of the stored schedule.
+		/// Its costs must not be lower than their floors (see `Schedule::respects_weight_floors`).
+		/// Fails if another update is scheduled for activation.
+		///
+		/// All stored code is re-instrumented with the new schedule in the background
+		/// over the following blocks.
+		#[weight = T::WeightInfo::update_schedule()]
 		pub fn update_schedule(origin, schedule: Schedule) -> DispatchResult {
 			ensure_root(origin)?;
-			if <Module<T>>::current_schedule().version >= schedule.version {
-				Err(Error::<T>::InvalidScheduleVersion)?
-			}
-
-			Self::deposit_event(RawEvent::ScheduleUpdated(schedule.version));
-			CurrentSchedule::put(schedule);
+			ensure!(!PendingSchedule::<T>::exists(), Error::<T>::ScheduleUpdatePending);
+			Self::validate_schedule(&schedule, false)?;
+			Self::activate_schedule(schedule);
+			Ok(())
+		}
 
+		/// Schedules the activation of a new schedule for metering contracts at the
+		/// beginning of the block `activate_at`.
+		///
+		/// The schedule is validated like in `update_schedule` but is allowed to lower limits
+		/// if `force` is set. It replaces any update that is already scheduled. If `activate_at`
+		/// is not in the future the schedule is activated immediately.
+		#[weight = T::WeightInfo::update_schedule_at()]
+		pub fn update_schedule_at(
+			origin,
+			schedule: Schedule,
+			activate_at: T::BlockNumber,
+			force: bool,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+			Self::validate_schedule(&schedule, force)?;
+			if activate_at <= <frame_system::Module<T>>::block_number() {
+				PendingSchedule::<T>::kill();
+				Self::activate_schedule(schedule);
+			} else {
+				Self::deposit_event(RawEvent::ScheduleUpdateScheduled(schedule.version, activate_at));
+				PendingSchedule::<T>::put((activate_at, schedule));
+			}
 			Ok(())
 		}
 
 		/// Stores the given binary Wasm code into the chain's storage and returns its `codehash`.
 		/// You can instantiate contracts only with stored code.
-		#[weight = Module::<T>::calc_code_put_costs(&code)]
+		///
+		/// Exports other than `deploy` and `call` are removed from the instrumented code. The
+		/// payload of an optional `contract-metadata` custom section can be retrieved with
+		/// `code_metadata`. If `T::StripPristineCode` is set the code is stored under the hash
+		/// of the code without custom sections which is reported by the `CodeStored` event.
+		///
+		/// The code is deserialized only once for the checks and the instrumentation which the
+		/// `put_code` benchmark measures. Serializing the stripped pristine code is linear in
+		/// the code size as well and not benchmarked separately.
+		#[weight = T::WeightInfo::put_code(kilobytes(code.len() as u32))]
 		pub fn put_code(
 			origin,
 			code: Vec<u8>
@@ -523,12 +824,28 @@ decl_module! {
 			let schedule = <Module<T>>::current_schedule();
 			ensure!(code.len() as u32 <= schedule.max_code_size, Error::<T>::CodeTooLarge);
 			let result = wasm::save_code::<T>(code, &schedule);
-			if let Ok(code_hash) = result {
-				Self::deposit_event(RawEvent::CodeStored(code_hash));
+			if let Ok((code_hash, bytes_saved)) = result {
+				Self::deposit_event(RawEvent::CodeStored(code_hash, bytes_saved));
 			}
 			result.map(|_| ()).map_err(Into::into)
 		}
 
+		/// Re-instruments the code stored under `code_hash` with the current schedule.
+		///
+		/// Code that was instrumented with an outdated schedule is re-instrumented when it
+		/// is executed next and the caller is charged for it. This allows anyone to pay for
+		/// the re-instrumentation upfront. Nothing happens if the code is already up to date.
+		#[weight = T::WeightInfo::reinstrument(kilobytes(<Module<T>>::current_schedule().max_code_size))]
+		pub fn reinstrument_code(origin, code_hash: CodeHash<T>) -> DispatchResultWithPostInfo {
+			ensure_signed(origin)?;
+			let schedule = <Module<T>>::current_schedule();
+			let code_len = wasm::reinstrument_code::<T>(&code_hash, &schedule)?;
+			if code_len.is_some() {
+				Self::deposit_event(RawEvent::CodeReinstrumented(code_hash));
+			}
+			Ok(Some(T::WeightInfo::reinstrument(code_len.map(kilobytes).unwrap_or(0))).into())
+		}
+
 		/// Makes a call to an account, optionally transferring some balance.
 		///
 		/// * If the account is a smart-contract account, the associated code will be
@@ -536,7 +853,7 @@ decl_module! {
 		/// * If the account is a regular account, any value will be transferred.
 		/// * If no account exists and the call value is not less than `existential_deposit`,
 		/// a regular account will be created and any value will be transferred.
-		#[weight = *gas_limit]
+		#[weight = T::WeightInfo::call().saturating_add(*gas_limit)]
 		pub fn call(
 			origin,
 			dest: <T::Lookup as StaticLookup>::Source,
@@ -549,9 +866,9 @@ decl_module! {
 			let mut gas_meter = GasMeter::new(gas_limit);
 
 			let result = Self::execute_wasm(origin, &mut gas_meter, |ctx, gas_meter| {
-				ctx.call(dest, value, gas_meter, data)
+				ctx.call(dest, value, gas_meter, data, false)
 			});
-			gas_meter.into_dispatch_result(result)
+			gas_meter.into_dispatch_result(result, T::WeightInfo::call())
 		}
 
 		/// Instantiates a new contract from the `codehash` generated by `put_code`, optionally transferring some balance.
@@ -564,7 +881,7 @@ decl_module! {
 		///   after the execution is saved as the `code` of the account. That code will be invoked
 		///   upon any call received by this account.
 		/// - The contract is initialized.
-		#[weight = *gas_limit]
+		#[weight = T::WeightInfo::instantiate().saturating_add(*gas_limit)]
 		pub fn instantiate(
 			origin,
 			#[compact] endowment: BalanceOf<T>,
@@ -579,7 +896,154 @@ decl_module! {
 				ctx.instantiate(endowment, gas_meter, &code_hash, data)
 					.map(|(_address, output)| output)
 			});
-			gas_meter.into_dispatch_result(result)
+			gas_meter.into_dispatch_result(result, T::WeightInfo::instantiate())
+		}
+
+		/// Instantiates a new contract like `instantiate` but with the given `storage_mode`.
+		///
+		/// Contracts instantiated with `StorageMode::Unhashed` store their values under the
+		/// original keys. This allows them to use `seal_storage_next_key` and allows off-chain
+		/// tools to list their storage with `storage_keys`.
+		#[weight = T::WeightInfo::instantiate().saturating_add(*gas_limit)]
+		pub fn instantiate_with_storage_mode(
+			origin,
+			#[compact] endowment: BalanceOf<T>,
+			#[compact] gas_limit: Gas,
+			code_hash: CodeHash<T>,
+			data: Vec<u8>,
+			storage_mode: StorageMode
+		) -> DispatchResultWithPostInfo {
+			let origin = ensure_signed(origin)?;
+			let mut gas_meter = GasMeter::new(gas_limit);
+
+			let result = Self::execute_wasm(origin, &mut gas_meter, |ctx, gas_meter| {
+				ctx.instantiate_with_storage_mode(endowment, gas_meter, &code_hash, data, storage_mode)
+					.map(|(_address, output)| output)
+			});
+			gas_meter.into_dispatch_result(result, T::WeightInfo::instantiate())
+		}
+
+		/// Deposits `amount` from the sender into the account of the contract at `dest` so that
+		/// it can be used to pay rent.
+		///
+		/// If `raise_allowance` is set the rent allowance of the contract is raised by the same
+		/// amount. Outstanding rent is collected before the deposit is made. Depositing into
+		/// a plain account or a tombstone fails with `NotCallable`.
+		#[weight = T::WeightInfo::deposit_rent()]
+		pub fn deposit_rent(
+			origin,
+			dest: <T::Lookup as StaticLookup>::Source,
+			#[compact] amount: BalanceOf<T>,
+			raise_allowance: bool
+		) -> DispatchResult {
+			let origin = ensure_signed(origin)?;
+			let dest = T::Lookup::lookup(dest)?;
+
+			let mut contract = match rent::collect_rent::<T>(&dest) {
+				Some(ContractInfo::Alive(contract)) => contract,
+				_ => Err(Error::<T>::NotCallable)?,
+			};
+
+			T::Currency::transfer(&origin, &dest, amount, ExistenceRequirement::KeepAlive)?;
+
+			if raise_allowance {
+				contract.rent_allowance = contract.rent_allowance.saturating_add(amount);
+				<ContractInfoOf<T>>::insert(&dest, ContractInfo::Alive(contract.clone()));
+			}
+
+			Self::deposit_event(
+				RawEvent::RentDeposited(origin, dest, amount, contract.rent_allowance)
+			);
+
+			Ok(())
+		}
+
+		/// Restores the tombstone at `dest` from the supplied storage items.
+		///
+		/// The child trie of the contract is rebuilt from `storage_items` in the storage mode kept
+		/// in the tombstone and has to match the tombstone together with `code_hash`. Upon success
+		/// the contract at `dest` becomes alive again with the given `rent_allowance`.
+		/// See [`rent::restore_from_storage`].
+		#[weight = Module::<T>::calc_restore_costs(&storage_items)]
+		pub fn restore_contract(
+			origin,
+			dest: <T::Lookup as StaticLookup>::Source,
+			code_hash: CodeHash<T>,
+			#[compact] rent_allowance: BalanceOf<T>,
+			storage_items: Vec<(StorageKey, Vec<u8>)>
+		) -> DispatchResult {
+			let origin = ensure_signed(origin)?;
+			let dest = T::Lookup::lookup(dest)?;
+
+			rent::restore_from_storage::<T>(&dest, code_hash, rent_allowance, storage_items)?;
+
+			Self::deposit_event(RawEvent::Restored(origin, dest, code_hash, rent_allowance));
+
+			Ok(())
+		}
+
+		/// Restores the tombstone at `dest` from a subset of its storage.
+		///
+		/// In contrast to `restore_contract` only the supplied `storage_items` need to be
+		/// provided. They are verified against the storage root kept in the tombstone by
+		/// the given `proof` which has to be generated by `sp_trie::generate_trie_proof` for
+		/// the keys of the child trie. These are the hashed keys unless the storage mode kept
+		/// in the tombstone is `StorageMode::Unhashed`. Storage items which are not supplied
+		/// are not restored.
+		/// See [`rent::restore_with_proof`].
+		///
+		/// The contract can't know that some of its storage is missing which can break its
+		/// invariants. Therefore, the dispatch origin of this call must be root.
+		#[weight = Module::<T>::calc_restore_with_proof_costs(&storage_items, &proof)]
+		pub fn restore_contract_with_proof(
+			origin,
+			dest: <T::Lookup as StaticLookup>::Source,
+			code_hash: CodeHash<T>,
+			#[compact] rent_allowance: BalanceOf<T>,
+			storage_items: Vec<(StorageKey, Vec<u8>)>,
+			proof: Vec<Vec<u8>>
+		) -> DispatchResult {
+			ensure_root(origin)?;
+			let dest = T::Lookup::lookup(dest)?;
+
+			rent::restore_with_proof::<T>(&dest, code_hash, rent_allowance, storage_items, proof)?;
+
+			Self::deposit_event(
+				RawEvent::Restored(dest.clone(), dest, code_hash, rent_allowance)
+			);
+
+			Ok(())
+		}
+
+		/// Seeds the storage of the contract at `dest` with `items`.
+		///
+		/// The keys of `items` are used as keys of the child trie as they are, which is the form
+		/// returned by `export_storage`. The contract must not store anything yet and the root
+		/// of its child trie after the import must equal `expected_root`. Otherwise nothing is
+		/// imported. On success the storage mode of the contract is set to `storage_mode`, which
+		/// must be the exported mode so that the contract finds its keys again.
+		/// See [`storage::import_contract_storage`].
+		#[weight = Module::<T>::calc_import_storage_costs(&items)]
+		pub fn import_storage(
+			origin,
+			dest: <T::Lookup as StaticLookup>::Source,
+			storage_mode: StorageMode,
+			items: Vec<(Vec<u8>, Vec<u8>)>,
+			expected_root: Vec<u8>
+		) -> DispatchResult {
+			ensure_root(origin)?;
+			let dest = T::Lookup::lookup(dest)?;
+
+			let count = storage::import_contract_storage::<T>(
+				&dest,
+				storage_mode,
+				items,
+				&expected_root,
+			)?;
+
+			Self::deposit_event(RawEvent::StorageImported(dest, count));
+
+			Ok(())
 		}
 
 		/// Allows block producers to claim a small reward for evicting a contract. If a block producer
@@ -587,7 +1051,7 @@ decl_module! {
 		///
 		/// If contract is not evicted as a result of this call, no actions are taken and
 		/// the sender is not eligible for the reward.
-		#[weight = 0]
+		#[weight = T::WeightInfo::claim_surcharge()]
 		fn claim_surcharge(origin, dest: T::AccountId, aux_sender: Option<T::AccountId>) {
 			let origin = origin.into();
 			let (signed, rewarded) = match (origin, aux_sender) {
@@ -635,7 +1099,30 @@ impl<T: Trait> Module<T> {
 		let mut gas_meter = GasMeter::new(gas_limit);
 		(
 			Self::execute_wasm(origin, &mut gas_meter, |ctx, gas_meter| {
-				ctx.call(dest, value, gas_meter, input_data)
+				ctx.call(dest, value, gas_meter, input_data, false)
+			}),
+			gas_meter.gas_spent(),
+		)
+	}
+
+	/// Perform a read-only call to a specified contract.
+	///
+	/// This function is similar to `Self::bare_call`, but the called contract and all contracts
+	/// called by it are denied to change any state. Every attempt to do so fails with
+	/// `StateChangeDenied`. No rent is collected from the called contracts, so a contract
+	/// that can't pay its rent is executed rather than evicted.
+	///
+	/// It returns the exection result and the amount of used weight.
+	pub fn bare_call_read_only(
+		origin: T::AccountId,
+		dest: T::AccountId,
+		gas_limit: Gas,
+		input_data: Vec<u8>,
+	) -> (ExecResult, Gas) {
+		let mut gas_meter = GasMeter::new(gas_limit);
+		(
+			Self::execute_wasm(origin, &mut gas_meter, |ctx, gas_meter| {
+				ctx.call(dest, Zero::zero(), gas_meter, input_data, true)
 			}),
 			gas_meter.gas_spent(),
 		)
@@ -651,20 +1138,268 @@ impl<T: Trait> Module<T> {
 			.get_alive()
 			.ok_or(ContractAccessError::IsTombstone)?;
 
-		let maybe_value = storage::read_contract_storage(&contract_info.trie_id, &key);
+		let storage_mode = storage::storage_mode(&contract_info.trie_id);
+		let maybe_value = storage::read_contract_storage(&contract_info.trie_id, storage_mode, &key);
 		Ok(maybe_value)
 	}
 
+	/// Export all key-value pairs of a specified contract together with its child trie root.
+	///
+	/// The result can be passed to `import_storage` in order to recreate the storage of the
+	/// contract on another chain.
+	pub fn export_storage(
+		address: T::AccountId,
+	) -> sp_std::result::Result<ExportedStorage, ContractAccessError> {
+		let contract_info = ContractInfoOf::<T>::get(&address)
+			.ok_or(ContractAccessError::DoesntExist)?
+			.get_alive()
+			.ok_or(ContractAccessError::IsTombstone)?;
+
+		Ok(ExportedStorage {
+			storage_mode: storage::storage_mode(&contract_info.trie_id),
+			items: storage::contract_storage_items(&contract_info.trie_id),
+			child_root: child::root(&contract_info.child_trie_info()),
+		})
+	}
+
+	/// Query the keys of a specified contract that start with `prefix`.
+	///
+	/// At most `limit` keys are returned in ascending order. `limit` is capped at
+	/// [`MAX_STORAGE_KEYS_PER_PAGE`]. If `start` is supplied only keys that are greater
+	/// than `start` are returned, which allows to page through the storage.
+	/// Keys of contracts in `StorageMode::Hashed` are the hashes under which the values are
+	/// stored, so only the full listing (an empty `prefix`) is meaningful for those.
+	pub fn storage_keys(
+		address: T::AccountId,
+		prefix: Vec<u8>,
+		start: Option<Vec<u8>>,
+		limit: u32,
+	) -> sp_std::result::Result<Vec<Vec<u8>>, ContractAccessError> {
+		let contract_info = ContractInfoOf::<T>::get(&address)
+			.ok_or(ContractAccessError::DoesntExist)?
+			.get_alive()
+			.ok_or(ContractAccessError::IsTombstone)?;
+
+		let limit = limit.min(MAX_STORAGE_KEYS_PER_PAGE) as usize;
+		let mut keys = Vec::new();
+		let mut after = start;
+		while keys.len() < limit {
+			let next = storage::next_child_key(
+				&contract_info.trie_id,
+				&prefix,
+				after.as_ref().map(|key| &key[..]),
+			);
+			match next {
+				Some(key) => {
+					keys.push(key.clone());
+					after = Some(key);
+				}
+				None => break,
+			}
+		}
+		Ok(keys)
+	}
+
 	pub fn rent_projection(
 		address: T::AccountId,
 	) -> sp_std::result::Result<RentProjection<T::BlockNumber>, ContractAccessError> {
 		rent::compute_rent_projection::<T>(&address)
 	}
+
+	/// Check whether `put_code` would accept the given code without storing it.
+	///
+	/// The code is checked and instrumented with the current schedule. On success details
+	/// about the instrumented code are returned. Otherwise the reason for its rejection.
+	pub fn validate_code(code: Vec<u8>) -> sp_std::result::Result<ValidatedCode, PrepareError> {
+		wasm::validate_code(&code, &<Module<T>>::current_schedule())
+	}
+
+	/// Query a detailed breakdown of the rent situation of the specified contract.
+	///
+	/// In contrast to [`Self::rent_projection`] this function has no side effects.
+	pub fn rent_status(
+		address: T::AccountId,
+	) -> sp_std::result::Result<RentStatus<T>, ContractAccessError> {
+		rent::compute_rent_status::<T>(&address)
+	}
 }
 
 impl<T: Trait> Module<T> {
-	fn calc_code_put_costs(code: &Vec<u8>) -> Gas {
-		<Module<T>>::current_schedule().put_code_per_byte_cost.saturating_mul(code.len() as Gas)
+	/// The storage mode of a contract in `StorageMode::Unhashed` is written in addition to what
+	/// the benchmark measures. It is always accounted for as the mode is only known after
+	/// the tombstone was read.
+	fn calc_restore_costs(storage_items: &[(StorageKey, Vec<u8>)]) -> Weight {
+		let (count, kilobytes) = Self::storage_items_size(storage_items);
+		T::WeightInfo::restore_contract(count, kilobytes)
+			.saturating_add(Self::calc_storage_root_costs(storage_items))
+			.saturating_add(T::DbWeight::get().writes(1))
+	}
+
+	/// Costs of hashing every key of `storage_items` and of computing the storage root
+	/// from them. Building the trie hashes every value at least once.
+	fn calc_storage_root_costs(storage_items: &[(StorageKey, Vec<u8>)]) -> Weight {
+		let host_fn_weights = <Module<T>>::current_schedule().host_fn_weights;
+		let hashed_bytes = storage_items.iter()
+			.map(|(key, value)| (key.len() + value.len()) as Gas)
+			.fold(0, |acc: Gas, len| acc.saturating_add(len));
+		host_fn_weights.hash_blake2_256
+			.saturating_mul(storage_items.len() as Gas)
+			.saturating_add(host_fn_weights.hash_blake2_256_per_byte.saturating_mul(hashed_bytes))
+	}
+
+	/// In contrast to a restore the keys of imported items are of arbitrary length. They are
+	/// written to the child trie together with the values and therefore counted with them.
+	fn calc_import_storage_costs(items: &[(Vec<u8>, Vec<u8>)]) -> Weight {
+		let bytes = items.iter()
+			.map(|(key, value)| key.len().saturating_add(value.len()) as u32)
+			.fold(0u32, |acc, len| acc.saturating_add(len));
+		T::WeightInfo::import_storage(items.len() as u32, kilobytes(bytes))
+	}
+
+	fn calc_restore_with_proof_costs(
+		storage_items: &[(StorageKey, Vec<u8>)],
+		proof: &[Vec<u8>],
+	) -> Weight {
+		let (count, kilobytes) = Self::storage_items_size(storage_items);
+		let proof_bytes = proof.iter()
+			.map(|node| node.len() as Gas)
+			.sum::<Gas>();
+		T::WeightInfo::restore_contract_with_proof(count, kilobytes)
+			.saturating_add(
+				<Module<T>>::current_schedule().sandbox_data_read_cost.saturating_mul(proof_bytes)
+			)
+			// The storage mode as in `calc_restore_costs`.
+			.saturating_add(T::DbWeight::get().writes(1))
+	}
+
+	/// Checks that `schedule` can replace the current schedule.
+	///
+	/// The limits of a schedule protect the chain against contracts that are too costly to
+	/// execute. Lowering them can render existing contracts unusable, which is only allowed
+	/// if `force` is set.
+	fn validate_schedule(schedule: &Schedule, force: bool) -> DispatchResult {
+		let current = <Module<T>>::current_schedule();
+		ensure!(schedule.version > current.version, Error::<T>::InvalidScheduleVersion);
+		ensure!(
+			schedule.max_memory_pages > 0 &&
+			schedule.max_memory_pages <= MAX_WASM_MEMORY_PAGES &&
+			schedule.max_stack_height > 0 &&
+			schedule.max_stack_height <= MAX_STACK_HEIGHT_CEILING &&
+			schedule.max_event_topics <= MAX_EVENT_TOPICS_CEILING &&
+			schedule.max_table_size <= MAX_TABLE_SIZE_CEILING &&
+			schedule.max_subject_len > 0 &&
+			schedule.max_subject_len <= MAX_SUBJECT_LEN_CEILING &&
+			schedule.max_storage_key_len > 0 &&
+			schedule.max_storage_key_len <= MAX_STORAGE_KEY_LEN_CEILING &&
+			schedule.max_code_size > 0 &&
+			schedule.max_code_size <= MAX_CODE_SIZE_CEILING,
+			Error::<T>::InvalidScheduleLimit,
+		);
+		ensure!(schedule.respects_weight_floors(), Error::<T>::InvalidScheduleWeight);
+		if !force {
+			ensure!(
+				schedule.max_event_topics >= current.max_event_topics &&
+				schedule.max_stack_height >= current.max_stack_height &&
+				schedule.max_memory_pages >= current.max_memory_pages &&
+				schedule.max_table_size >= current.max_table_size &&
+				schedule.max_subject_len >= current.max_subject_len &&
+				schedule.max_storage_key_len >= current.max_storage_key_len &&
+				schedule.max_code_size >= current.max_code_size,
+				Error::<T>::ScheduleLimitDecreased,
+			);
+		}
+		Ok(())
+	}
+
+	/// Replaces the current schedule and starts re-instrumenting all code in the background.
+	fn activate_schedule(schedule: Schedule) {
+		let changes = <Module<T>>::current_schedule().diff(&schedule);
+		Self::deposit_event(RawEvent::ScheduleUpdated(schedule.version, changes));
+		CurrentSchedule::put(schedule);
+		CodeMigrationCursor::put(<CodeStorage<T>>::final_prefix().to_vec());
+	}
+
+	/// Activates the pending schedule if it is due at block `now`.
+	fn activate_pending_schedule(now: T::BlockNumber) -> Weight {
+		let db_weight = T::DbWeight::get();
+		match PendingSchedule::<T>::get() {
+			Some((activate_at, schedule)) if activate_at <= now => {
+				PendingSchedule::<T>::kill();
+				Self::activate_schedule(schedule);
+				T::WeightInfo::update_schedule().saturating_add(db_weight.reads_writes(1, 1))
+			},
+			_ => db_weight.reads(1),
+		}
+	}
+
+	/// Migrates the current and the pending schedule from the layout of `ScheduleV0`.
+	///
+	/// Schedules that already have the current layout are left untouched. A migrated current
+	/// schedule is activated so that all code is re-instrumented in the background. A pending
+	/// schedule that can be decoded with neither layout is dropped.
+	fn migrate_schedule() -> Weight {
+		let db_weight = T::DbWeight::get();
+		let mut weight = db_weight.reads(2);
+
+		let key = CurrentSchedule::hashed_key();
+		if let Some(raw) = frame_support::storage::unhashed::get_raw(&key) {
+			if decode_exactly::<Schedule>(&raw).is_none() {
+				if let Some(old) = decode_exactly::<ScheduleV0>(&raw) {
+					Self::activate_schedule(old.migrate());
+					weight = weight.saturating_add(T::WeightInfo::update_schedule());
+				}
+			}
+		}
+
+		let key = PendingSchedule::<T>::hashed_key();
+		if let Some(raw) = frame_support::storage::unhashed::get_raw(&key) {
+			if decode_exactly::<(T::BlockNumber, Schedule)>(&raw).is_none() {
+				match decode_exactly::<(T::BlockNumber, ScheduleV0)>(&raw) {
+					Some((activate_at, old)) => PendingSchedule::<T>::put((activate_at, old.migrate())),
+					None => PendingSchedule::<T>::kill(),
+				}
+				weight = weight.saturating_add(db_weight.writes(1));
+			}
+		}
+
+		weight
+	}
+
+	/// Migrates all tombstones to the layout that keeps the storage root of the contract.
+	///
+	/// The storage root of the migrated tombstones is unknown and left empty. They can still
+	/// be restored from their complete storage. Their storage mode is `StorageMode::Hashed`
+	/// which was the only mode at the time. Runs only once as tracked by `StorageVersion`.
+	fn migrate_tombstones() -> Weight {
+		let db_weight = T::DbWeight::get();
+		if StorageVersion::get() != Releases::V2_0_0 {
+			return db_weight.reads(1);
+		}
+
+		let entries = sp_std::cell::Cell::new(0u64);
+		<ContractInfoOf<T>>::translate::<ContractInfoV0<T>, _>(|_, info| {
+			entries.set(entries.get() + 1);
+			Some(match info {
+				ContractInfoV0::Alive(alive) => ContractInfo::Alive(alive),
+				ContractInfoV0::Tombstone(hash) =>
+					ContractInfo::Tombstone(RawTombstoneContractInfo(
+						hash,
+						Vec::new(),
+						StorageMode::Hashed,
+						PhantomData,
+					)),
+			})
+		});
+		StorageVersion::put(Releases::V3_0_0);
+		db_weight.reads_writes(entries.get().saturating_add(1), entries.get().saturating_add(1))
+	}
+
+	/// Returns the number of `storage_items` and the size of their values in started kilobytes.
+	fn storage_items_size<K>(storage_items: &[(K, Vec<u8>)]) -> (u32, u32) {
+		let bytes = storage_items.iter()
+			.map(|(_, value)| value.len() as u32)
+			.fold(0u32, |acc, len| acc.saturating_add(len));
+		(storage_items.len() as u32, kilobytes(bytes))
 	}
 
 	fn execute_wasm(
@@ -680,12 +1415,30 @@ impl<T: Trait> Module<T> {
 	}
 }
 
+/// The maximum number of keys returned by a single call to [`Module::storage_keys`].
+pub const MAX_STORAGE_KEYS_PER_PAGE: u32 = 1000;
+
+/// Returns the number of kilobytes that are needed to hold `bytes`.
+///
+/// Weights are benchmarked per kilobyte. Rounding up makes sure that a started kilobyte is
+/// paid for in full.
+pub(crate) fn kilobytes(bytes: u32) -> u32 {
+	bytes.saturating_add(1023) / 1024
+}
+
+/// Decodes `raw` as `V` if it is an encoding of `V` without trailing bytes.
+fn decode_exactly<V: Decode>(raw: &[u8]) -> Option<V> {
+	let mut input = raw;
+	V::decode(&mut input).ok().filter(|_| input.is_empty())
+}
+
 decl_event! {
 	pub enum Event<T>
 	where
 		Balance = BalanceOf<T>,
 		<T as frame_system::Trait>::AccountId,
-		<T as frame_system::Trait>::Hash
+		<T as frame_system::Trait>::Hash,
+		<T as frame_system::Trait>::BlockNumber
 	{
 		/// Contract deployed by address at the specified address. \[owner, contract\]
 		Instantiated(AccountId, AccountId),
@@ -704,22 +1457,57 @@ decl_event! {
 		/// 
 		/// # Params
 		///
-		/// - `donor`: `AccountId`: Account ID of the restoring contract
+		/// - `donor`: `AccountId`: Account ID of the restoring contract or of the account that
+		///   supplied the storage via `restore_contract`. The restored contract itself if it was
+		///   restored by root via `restore_contract_with_proof`.
 		/// - `dest`: `AccountId`: Account ID of the restored contract
 		/// - `code_hash`: `Hash`: Code hash of the restored contract
 		/// - `rent_allowance: `Balance`: Rent allowance of the restored contract
 		Restored(AccountId, AccountId, Hash, Balance),
 
+		/// Funds have been deposited into the account of a contract to pay for its rent.
+		/// \[depositor, contract, amount, rent_allowance\]
+		///
+		/// # Params
+		///
+		/// - `depositor`: `AccountId`: Account ID of the account that made the deposit
+		/// - `contract`: `AccountId`: Account ID of the contract that received the deposit
+		/// - `amount`: `Balance`: The amount that was deposited
+		/// - `rent_allowance`: `Balance`: The rent allowance of the contract after the deposit
+		RentDeposited(AccountId, AccountId, Balance, Balance),
+
 		/// Code with the specified hash has been stored.
-		/// \[code_hash\]
-		CodeStored(Hash),
+		/// \[code_hash, bytes_saved\]
+		///
+		/// `bytes_saved` is the number of bytes that were not stored because custom sections
+		/// were stripped from the instrumented and, if `T::StripPristineCode` is set, from the
+		/// pristine code. In the latter case `code_hash` is the hash of the stripped code.
+		CodeStored(Hash, u32),
 
-		/// Triggered when the current \[schedule\] is updated.
-		ScheduleUpdated(u32),
+		/// The current schedule has been updated.
+		/// \[version, changes\]
+		///
+		/// # Params
+		///
+		/// - `version`: `u32`: The version of the new schedule.
+		/// - `changes`: `Vec<ScheduleChange>`: The fields that differ from the previous schedule.
+		ScheduleUpdated(u32, Vec<ScheduleChange>),
+
+		/// A schedule update has been scheduled for activation.
+		/// \[version, activate_at\]
+		ScheduleUpdateScheduled(u32, BlockNumber),
 
 		/// An event deposited upon execution of a contract from the account.
 		/// \[account, data\]
 		ContractExecution(AccountId, Vec<u8>),
+
+		/// Code with the specified hash has been re-instrumented with the current schedule.
+		/// \[code_hash\]
+		CodeReinstrumented(Hash),
+
+		/// Storage has been imported into a contract by `import_storage`.
+		/// \[contract, item_count\]
+		StorageImported(AccountId, u32),
 	}
 }
 
@@ -731,12 +1519,24 @@ decl_storage! {
 		pub PristineCode: map hasher(identity) CodeHash<T> => Option<Vec<u8>>;
 		/// A mapping between an original code hash and instrumented wasm code, ready for execution.
 		pub CodeStorage: map hasher(identity) CodeHash<T> => Option<wasm::PrefabWasmModule>;
+		/// A mapping from an original code hash to the payload of its `contract-metadata` section.
+		pub CodeMetadata get(fn code_metadata): map hasher(identity) CodeHash<T> => Option<Vec<u8>>;
+		/// The storage key of the last `CodeStorage` entry visited by the background
+		/// re-instrumentation. `None` if there is no migration in progress.
+		CodeMigrationCursor: Option<Vec<u8>>;
+		/// A schedule update that is activated at the beginning of the specified block.
+		PendingSchedule: Option<(T::BlockNumber, Schedule)>;
 		/// The subtrie counter.
 		pub AccountCounter: u64 = 0;
 		/// The code associated with a given account.
 		///
 		/// TWOX-NOTE: SAFE since `AccountId` is a secure hash.
 		pub ContractInfoOf: map hasher(twox_64_concat) T::AccountId => Option<ContractInfo<T>>;
+		/// The storage mode of a child trie. Only tries that don't use the default
+		/// `StorageMode::Hashed` have an entry.
+		pub StorageModeOf: map hasher(twox_64_concat) TrieId => StorageMode;
+		/// The version of the storage layout. New chains start with the latest version.
+		StorageVersion build(|_: &GenesisConfig| Releases::V3_0_0): Releases;
 	}
 }
 
@@ -790,26 +1590,14 @@ pub struct Schedule {
 	/// Version of the schedule.
 	pub version: u32,
 
-	/// Cost of putting a byte of code into storage.
-	pub put_code_per_byte_cost: Gas,
-
 	/// Gas cost of a growing memory by single page.
 	pub grow_mem_cost: Gas,
 
-	/// Gas cost of a regular operation.
-	pub regular_op_cost: Gas,
+	/// Gas cost of executing wasm instructions.
+	pub instruction_weights: InstructionWeights,
 
-	/// Gas cost per one byte returned.
-	pub return_data_per_byte_cost: Gas,
-
-	/// Gas cost to deposit an event; the per-byte portion.
-	pub event_data_per_byte_cost: Gas,
-
-	/// Gas cost to deposit an event; the cost per topic.
-	pub event_per_topic_cost: Gas,
-
-	/// Gas cost to deposit an event; the base.
-	pub event_base_cost: Gas,
+	/// Gas cost of calling the host functions.
+	pub host_fn_weights: HostFnWeights,
 
 	/// Base gas cost to call into a contract.
 	pub call_base_cost: Gas,
@@ -854,6 +1642,10 @@ pub struct Schedule {
 	/// The maximum length of a subject used for PRNG generation.
 	pub max_subject_len: u32,
 
+	/// The maximum length of a storage key in bytes that can be passed to the versions of the
+	/// storage functions that accept keys of variable length.
+	pub max_storage_key_len: u32,
+
 	/// The maximum length of a contract code in bytes. This limit applies to the uninstrumented
 	// and pristine form of the code as supplied to `put_code`.
 	pub max_code_size: u32,
@@ -864,17 +1656,46 @@ pub struct Schedule {
 // Proper benchmarks are needed before this value and its derivatives can be used in production.
 const WASM_INSTRUCTION_COST: Gas = 500_000;
 
+// Native code processes a byte (e.g. when hashing it or writing it to storage) about three
+// orders of magnitude faster than wasmi executes an instruction. Copying the bytes from and
+// into the sandbox memory is charged separately and dominates these costs.
+// Like the instruction cost this is an estimation until the `seal_*` benchmarks were run.
+const NATIVE_BYTE_COST: Gas = WASM_INSTRUCTION_COST / 1000;
+
+/// The number of times the benchmarked instruction is repeated per component step of
+/// the `instr_*` benchmarks.
+pub(crate) const INSTR_BENCHMARK_BATCH_SIZE: u32 = 100;
+
+/// The number of times the benchmarked host function is called per component step of
+/// the `seal_*` benchmarks.
+pub(crate) const API_BENCHMARK_BATCH_SIZE: u32 = 20;
+
+/// The maximum number of memory pages that a wasm module can address (4 GiB).
+const MAX_WASM_MEMORY_PAGES: u32 = 65536;
+
+// The ceilings of the schedule limits. They are orders of magnitude above the defaults and
+// only guard against updates that would allow contracts to exhaust the resources of a node.
+const MAX_STACK_HEIGHT_CEILING: u32 = 1024 * 1024;
+const MAX_EVENT_TOPICS_CEILING: u32 = 64;
+const MAX_TABLE_SIZE_CEILING: u32 = 1024 * 1024;
+const MAX_SUBJECT_LEN_CEILING: u32 = 1024;
+const MAX_STORAGE_KEY_LEN_CEILING: u32 = 4 * 1024;
+const MAX_CODE_SIZE_CEILING: u32 = 4 * 1024 * 1024;
+
+// The floors of the schedule costs. A cost of zero makes an operation free which allows
+// a contract to fill a block with an unbounded amount of work. The floors are well below
+// the estimated defaults in order to leave room for the benchmarked values.
+const MIN_INSTRUCTION_WEIGHT: u32 = 1_000;
+const MIN_BASE_WEIGHT: Gas = WASM_INSTRUCTION_COST / 10;
+const MIN_PER_ITEM_WEIGHT: Gas = NATIVE_BYTE_COST / 10;
+
 impl Default for Schedule {
 	fn default() -> Schedule {
 		Schedule {
 			version: 0,
-			put_code_per_byte_cost: WASM_INSTRUCTION_COST,
 			grow_mem_cost: WASM_INSTRUCTION_COST,
-			regular_op_cost: WASM_INSTRUCTION_COST,
-			return_data_per_byte_cost: WASM_INSTRUCTION_COST,
-			event_data_per_byte_cost: WASM_INSTRUCTION_COST,
-			event_per_topic_cost: WASM_INSTRUCTION_COST,
-			event_base_cost: WASM_INSTRUCTION_COST,
+			instruction_weights: Default::default(),
+			host_fn_weights: Default::default(),
 			call_base_cost: 135 * WASM_INSTRUCTION_COST,
 			dispatch_base_cost: 135 * WASM_INSTRUCTION_COST,
 			instantiate_base_cost: 175 * WASM_INSTRUCTION_COST,
@@ -888,7 +1709,582 @@ impl Default for Schedule {
 			max_table_size: 16 * 1024,
 			enable_println: false,
 			max_subject_len: 32,
+			max_storage_key_len: 128,
 			max_code_size: 512 * 1024,
 		}
 	}
 }
+
+/// Appends a [`ScheduleChange`] to `$changes` for each of the listed fields that differs
+/// between `$old` and `$new`.
+macro_rules! diff_fields {
+	($changes:ident, $prefix:expr, $old:expr, $new:expr, [$($field:ident),* $(,)?]) => {
+		$(
+			if $old.$field != $new.$field {
+				let mut field = $prefix.as_bytes().to_vec();
+				field.extend_from_slice(stringify!($field).trim_start_matches("r#").as_bytes());
+				$changes.push(ScheduleChange {
+					field,
+					old: u64::from($old.$field),
+					new: u64::from($new.$field),
+				});
+			}
+		)*
+	};
+}
+
+impl Schedule {
+	/// Returns whether every cost of the schedule is at least its floor.
+	///
+	/// Base costs and instructions have a higher floor than the costs that are charged per
+	/// byte or item. The fields are destructured so that a new cost can't be forgotten here.
+	pub fn respects_weight_floors(&self) -> bool {
+		let Schedule {
+			version: _, grow_mem_cost, instruction_weights, host_fn_weights, call_base_cost,
+			instantiate_base_cost, dispatch_base_cost, sandbox_data_read_cost,
+			sandbox_data_write_cost, transfer_cost, instantiate_cost, max_event_topics: _,
+			max_stack_height: _, max_memory_pages: _, max_table_size: _, enable_println: _,
+			max_subject_len: _, max_storage_key_len: _, max_code_size: _,
+		} = self;
+		let InstructionWeights {
+			constant, local, global, load, store, branch, call: call_instr, call_indirect,
+			integer_comparison, bit, add, mul, div, conversion, unreachable, nop,
+			current_memory, grow_memory,
+		} = instruction_weights;
+		let HostFnWeights {
+			caller, address, origin, caller_is_origin, is_contract, code_hash, own_code_hash,
+			gas_left, balance, value_transferred, minimum_balance, tombstone_deposit,
+			rent_allowance, block_number, now, weight_to_fee, input, r#return, return_per_byte,
+			terminate, restore_to, restore_to_per_delta, random, deposit_event,
+			deposit_event_per_topic, deposit_event_per_byte, set_rent_allowance, set_storage,
+			set_storage_per_byte, clear_storage, get_storage, get_storage_per_byte,
+			contains_storage, take_storage, take_storage_per_byte, storage_next_key,
+			storage_next_key_per_byte, storage_key_per_byte, transfer, call, instantiate, println,
+			hash_sha2_256, hash_sha2_256_per_byte, hash_keccak_256, hash_keccak_256_per_byte,
+			hash_blake2_256, hash_blake2_256_per_byte, hash_blake2_128, hash_blake2_128_per_byte,
+			ecdsa_recover, ecdsa_to_eth_address, sr25519_verify, sr25519_verify_per_byte,
+			ed25519_verify, ed25519_verify_per_byte,
+		} = host_fn_weights;
+
+		let instructions = [
+			constant, local, global, load, store, branch, call_instr, call_indirect,
+			integer_comparison, bit, add, mul, div, conversion, unreachable, nop,
+			current_memory, grow_memory,
+		];
+		let base = [
+			grow_mem_cost, call_base_cost, instantiate_base_cost, dispatch_base_cost,
+			transfer_cost, instantiate_cost, caller, address, origin, caller_is_origin,
+			is_contract, code_hash, own_code_hash, gas_left, balance, value_transferred,
+			minimum_balance, tombstone_deposit, rent_allowance, block_number, now, weight_to_fee,
+			input, r#return, terminate, restore_to, random, deposit_event, set_rent_allowance,
+			set_storage, clear_storage, get_storage, contains_storage, take_storage,
+			storage_next_key, transfer, call, instantiate, println, hash_sha2_256,
+			hash_keccak_256, hash_blake2_256, hash_blake2_128, ecdsa_recover,
+			ecdsa_to_eth_address, sr25519_verify, ed25519_verify,
+		];
+		let per_item = [
+			sandbox_data_read_cost, sandbox_data_write_cost, return_per_byte,
+			restore_to_per_delta, deposit_event_per_topic, deposit_event_per_byte,
+			set_storage_per_byte, get_storage_per_byte, take_storage_per_byte,
+			storage_next_key_per_byte, storage_key_per_byte, hash_sha2_256_per_byte,
+			hash_keccak_256_per_byte, hash_blake2_256_per_byte, hash_blake2_128_per_byte,
+			sr25519_verify_per_byte, ed25519_verify_per_byte,
+		];
+
+		instructions.iter().all(|weight| **weight >= MIN_INSTRUCTION_WEIGHT) &&
+			base.iter().all(|weight| **weight >= MIN_BASE_WEIGHT) &&
+			per_item.iter().all(|weight| **weight >= MIN_PER_ITEM_WEIGHT)
+	}
+
+	/// Returns the fields of `new` that differ from `self` except for the version.
+	pub fn diff(&self, new: &Schedule) -> Vec<ScheduleChange> {
+		// Every field except for `version` needs to be listed here.
+		let mut changes = Vec::new();
+		diff_fields!(changes, "", self, new, [
+			grow_mem_cost, call_base_cost, instantiate_base_cost, dispatch_base_cost,
+			sandbox_data_read_cost, sandbox_data_write_cost, transfer_cost, instantiate_cost,
+			max_event_topics, max_stack_height, max_memory_pages, max_table_size, enable_println,
+			max_subject_len, max_storage_key_len, max_code_size
+		]);
+		diff_fields!(
+			changes, "instruction_weights.", self.instruction_weights, new.instruction_weights,
+			[
+				constant, local, global, load, store, branch, call, call_indirect,
+				integer_comparison, bit, add, mul, div, conversion, unreachable, nop, current_memory,
+				grow_memory
+			]
+		);
+		diff_fields!(changes, "host_fn_weights.", self.host_fn_weights, new.host_fn_weights, [
+			caller, address, origin, caller_is_origin, is_contract, code_hash, own_code_hash,
+			gas_left, balance, value_transferred, minimum_balance, tombstone_deposit,
+			rent_allowance, block_number, now, weight_to_fee, input, r#return, return_per_byte,
+			terminate, restore_to, restore_to_per_delta, random, deposit_event,
+			deposit_event_per_topic, deposit_event_per_byte, set_rent_allowance, set_storage,
+			set_storage_per_byte, clear_storage, get_storage, get_storage_per_byte,
+			contains_storage, take_storage, take_storage_per_byte, storage_next_key,
+			storage_next_key_per_byte, storage_key_per_byte, transfer, call, instantiate, println,
+			hash_sha2_256, hash_sha2_256_per_byte, hash_keccak_256, hash_keccak_256_per_byte,
+			hash_blake2_256, hash_blake2_256_per_byte, hash_blake2_128, hash_blake2_128_per_byte,
+			ecdsa_recover, ecdsa_to_eth_address, sr25519_verify, sr25519_verify_per_byte,
+			ed25519_verify, ed25519_verify_per_byte
+		]);
+		changes
+	}
+}
+
+/// The layout of `Schedule` before the costs were split into instruction and host function
+/// weight tables. Only used to migrate schedules that were stored with this layout.
+#[derive(Encode, Decode)]
+struct ScheduleV0 {
+	version: u32,
+	put_code_per_byte_cost: Gas,
+	grow_mem_cost: Gas,
+	regular_op_cost: Gas,
+	return_data_per_byte_cost: Gas,
+	event_data_per_byte_cost: Gas,
+	event_per_topic_cost: Gas,
+	event_base_cost: Gas,
+	call_base_cost: Gas,
+	instantiate_base_cost: Gas,
+	dispatch_base_cost: Gas,
+	sandbox_data_read_cost: Gas,
+	sandbox_data_write_cost: Gas,
+	transfer_cost: Gas,
+	instantiate_cost: Gas,
+	max_event_topics: u32,
+	max_stack_height: u32,
+	max_memory_pages: u32,
+	max_table_size: u32,
+	enable_println: bool,
+	max_subject_len: u32,
+	max_code_size: u32,
+}
+
+impl ScheduleV0 {
+	/// Converts the schedule to the current layout with a bumped version.
+	///
+	/// The limits and flags are carried over so that code which was accepted before is still
+	/// accepted after the re-instrumentation. The costs are replaced by the defaults because
+	/// they were derived for charging every instruction the same `regular_op_cost`.
+	fn migrate(self) -> Schedule {
+		Schedule {
+			version: self.version.saturating_add(1),
+			max_event_topics: self.max_event_topics,
+			max_stack_height: self.max_stack_height,
+			max_memory_pages: self.max_memory_pages,
+			max_table_size: self.max_table_size,
+			enable_println: self.enable_println,
+			max_subject_len: self.max_subject_len,
+			max_code_size: self.max_code_size,
+			.. Default::default()
+		}
+	}
+}
+
+/// A field that differs between two schedules as reported by [`Schedule::diff`].
+///
+/// Flags are reported as `0` or `1`.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug)]
+pub struct ScheduleChange {
+	/// The path of the field, e.g. `host_fn_weights.call`.
+	pub field: Vec<u8>,
+
+	/// The value in the previous schedule.
+	pub old: u64,
+
+	/// The value in the new schedule.
+	pub new: u64,
+}
+
+/// Describes the gas cost of calling each host function.
+///
+/// Every host function is charged its base cost as soon as it is called. The functions whose
+/// work depends on the size of their arguments are additionally charged the per byte (or per
+/// item) cost. Copying data from and into the sandbox memory is charged separately by
+/// `Schedule::sandbox_data_read_cost` and `Schedule::sandbox_data_write_cost`.
+///
+/// The default values are derived from the `seal_*` benchmarks in `benchmarking.rs` as
+/// reported by the `WeightInfo` of this pallet (see [`HostFnWeights::from_weight_info`]).
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug)]
+pub struct HostFnWeights {
+	/// Cost of `seal_caller`.
+	pub caller: Gas,
+
+	/// Cost of `seal_address`.
+	pub address: Gas,
+
+	/// Cost of `seal_origin`.
+	pub origin: Gas,
+
+	/// Cost of `seal_caller_is_origin`.
+	pub caller_is_origin: Gas,
+
+	/// Cost of `seal_is_contract`.
+	pub is_contract: Gas,
+
+	/// Cost of `seal_code_hash`.
+	pub code_hash: Gas,
+
+	/// Cost of `seal_own_code_hash`.
+	pub own_code_hash: Gas,
+
+	/// Cost of `seal_gas_left`.
+	pub gas_left: Gas,
+
+	/// Cost of `seal_balance`.
+	pub balance: Gas,
+
+	/// Cost of `seal_value_transferred`.
+	pub value_transferred: Gas,
+
+	/// Cost of `seal_minimum_balance`.
+	pub minimum_balance: Gas,
+
+	/// Cost of `seal_tombstone_deposit`.
+	pub tombstone_deposit: Gas,
+
+	/// Cost of `seal_rent_allowance`.
+	pub rent_allowance: Gas,
+
+	/// Cost of `seal_block_number`.
+	pub block_number: Gas,
+
+	/// Cost of `seal_now`.
+	pub now: Gas,
+
+	/// Cost of `seal_weight_to_fee`.
+	pub weight_to_fee: Gas,
+
+	/// Cost of `seal_input`.
+	pub input: Gas,
+
+	/// Cost of `seal_return`.
+	pub r#return: Gas,
+
+	/// Cost per byte returned by `seal_return`.
+	pub return_per_byte: Gas,
+
+	/// Cost of `seal_terminate`.
+	pub terminate: Gas,
+
+	/// Cost of `seal_restore_to`.
+	pub restore_to: Gas,
+
+	/// Cost per key supplied as delta to `seal_restore_to`.
+	pub restore_to_per_delta: Gas,
+
+	/// Cost of `seal_random`.
+	pub random: Gas,
+
+	/// Cost of `seal_deposit_event`.
+	pub deposit_event: Gas,
+
+	/// Cost per topic supplied to `seal_deposit_event`.
+	pub deposit_event_per_topic: Gas,
+
+	/// Cost per byte of an event deposited by `seal_deposit_event`.
+	pub deposit_event_per_byte: Gas,
+
+	/// Cost of `seal_set_rent_allowance`.
+	pub set_rent_allowance: Gas,
+
+	/// Cost of `seal_set_storage`.
+	pub set_storage: Gas,
+
+	/// Cost per byte of a value stored by `seal_set_storage`.
+	pub set_storage_per_byte: Gas,
+
+	/// Cost of `seal_clear_storage`.
+	pub clear_storage: Gas,
+
+	/// Cost of `seal_get_storage`.
+	pub get_storage: Gas,
+
+	/// Cost per byte of a value read by `seal_get_storage`.
+	pub get_storage_per_byte: Gas,
+
+	/// Cost of `seal_contains_storage`.
+	pub contains_storage: Gas,
+
+	/// Cost of `seal_take_storage`.
+	pub take_storage: Gas,
+
+	/// Cost per byte of a value taken by `seal_take_storage`.
+	pub take_storage_per_byte: Gas,
+
+	/// Cost of a single step of `seal_storage_next_key`.
+	pub storage_next_key: Gas,
+
+	/// Cost per byte of a key returned by `seal_storage_next_key`.
+	pub storage_next_key_per_byte: Gas,
+
+	/// Cost per byte of a variable length key passed to a storage function.
+	///
+	/// Fixed size keys are covered by the base cost of the function. Longer keys cost more
+	/// to hash or, in `StorageMode::Unhashed`, lengthen the path in the child trie.
+	pub storage_key_per_byte: Gas,
+
+	/// Cost of `seal_transfer`.
+	pub transfer: Gas,
+
+	/// Cost of `seal_call`.
+	pub call: Gas,
+
+	/// Cost of `seal_instantiate`.
+	pub instantiate: Gas,
+
+	/// Cost of `seal_println`.
+	pub println: Gas,
+
+	/// Cost of `seal_hash_sha2_256`.
+	pub hash_sha2_256: Gas,
+
+	/// Cost per byte hashed by `seal_hash_sha2_256`.
+	pub hash_sha2_256_per_byte: Gas,
+
+	/// Cost of `seal_hash_keccak_256`.
+	pub hash_keccak_256: Gas,
+
+	/// Cost per byte hashed by `seal_hash_keccak_256`.
+	pub hash_keccak_256_per_byte: Gas,
+
+	/// Cost of `seal_hash_blake2_256`.
+	pub hash_blake2_256: Gas,
+
+	/// Cost per byte hashed by `seal_hash_blake2_256`.
+	pub hash_blake2_256_per_byte: Gas,
+
+	/// Cost of `seal_hash_blake2_128`.
+	pub hash_blake2_128: Gas,
+
+	/// Cost per byte hashed by `seal_hash_blake2_128`.
+	pub hash_blake2_128_per_byte: Gas,
+
+	/// Cost of `seal_ecdsa_recover`.
+	pub ecdsa_recover: Gas,
+
+	/// Cost of `seal_ecdsa_to_eth_address`.
+	pub ecdsa_to_eth_address: Gas,
+
+	/// Cost of `seal_sr25519_verify`.
+	pub sr25519_verify: Gas,
+
+	/// Cost per byte of the message verified by `seal_sr25519_verify`.
+	pub sr25519_verify_per_byte: Gas,
+
+	/// Cost of `seal_ed25519_verify`.
+	pub ed25519_verify: Gas,
+
+	/// Cost per byte of the message verified by `seal_ed25519_verify`.
+	pub ed25519_verify_per_byte: Gas,
+}
+
+impl Default for HostFnWeights {
+	fn default() -> HostFnWeights {
+		Self::from_weight_info::<()>()
+	}
+}
+
+impl HostFnWeights {
+	/// Derives the costs from the results of the `seal_*` benchmarks as reported by `W`.
+	///
+	/// The benchmarks call the benchmarked function `API_BENCHMARK_BATCH_SIZE` times per
+	/// component step. Functions that can only be called once per contract call are called
+	/// once per step. The per byte costs are derived from the `*_per_kb` benchmarks. There are
+	/// no benchmarks for the length of storage keys which keep an estimated cost.
+	pub fn from_weight_info<W: WeightInfo>() -> Self {
+		let batched = |benchmark: fn(u32) -> Weight| {
+			weight_per_step(benchmark) / Weight::from(API_BENCHMARK_BATCH_SIZE)
+		};
+		let per_byte = |benchmark: fn(u32) -> Weight| weight_per_step(benchmark) / 1024;
+		let event = W::seal_deposit_event_per_topic_and_kb;
+
+		HostFnWeights {
+			caller: batched(W::seal_caller),
+			address: batched(W::seal_address),
+			origin: batched(W::seal_origin),
+			caller_is_origin: batched(W::seal_caller_is_origin),
+			is_contract: batched(W::seal_is_contract),
+			code_hash: batched(W::seal_code_hash),
+			own_code_hash: batched(W::seal_own_code_hash),
+			gas_left: batched(W::seal_gas_left),
+			balance: batched(W::seal_balance),
+			value_transferred: batched(W::seal_value_transferred),
+			minimum_balance: batched(W::seal_minimum_balance),
+			tombstone_deposit: batched(W::seal_tombstone_deposit),
+			rent_allowance: batched(W::seal_rent_allowance),
+			block_number: batched(W::seal_block_number),
+			now: batched(W::seal_now),
+			weight_to_fee: batched(W::seal_weight_to_fee),
+			input: weight_per_step(W::seal_input),
+			r#return: weight_per_step(W::seal_return),
+			return_per_byte: per_byte(W::seal_return_per_kb),
+			terminate: weight_per_step(W::seal_terminate),
+			// The benchmark restores a tombstone once per call.
+			restore_to: W::seal_restore_to(0).saturating_sub(W::call()),
+			restore_to_per_delta: batched(W::seal_restore_to),
+			random: batched(W::seal_random),
+			deposit_event: batched(W::seal_deposit_event),
+			deposit_event_per_topic: event(1, 0).saturating_sub(event(0, 0)),
+			deposit_event_per_byte: event(0, 1).saturating_sub(event(0, 0)) / 1024,
+			set_rent_allowance: batched(W::seal_set_rent_allowance),
+			set_storage: batched(W::seal_set_storage),
+			set_storage_per_byte: per_byte(W::seal_set_storage_per_kb),
+			clear_storage: batched(W::seal_clear_storage),
+			get_storage: batched(W::seal_get_storage),
+			get_storage_per_byte: per_byte(W::seal_get_storage_per_kb),
+			contains_storage: batched(W::seal_contains_storage),
+			take_storage: batched(W::seal_take_storage),
+			take_storage_per_byte: per_byte(W::seal_take_storage_per_kb),
+			storage_next_key: batched(W::seal_storage_next_key),
+			storage_next_key_per_byte: NATIVE_BYTE_COST,
+			storage_key_per_byte: NATIVE_BYTE_COST,
+			transfer: batched(W::seal_transfer),
+			call: batched(W::seal_call),
+			instantiate: batched(W::seal_instantiate),
+			println: batched(W::seal_println),
+			hash_sha2_256: batched(W::seal_hash_sha2_256),
+			hash_sha2_256_per_byte: per_byte(W::seal_hash_sha2_256_per_kb),
+			hash_keccak_256: batched(W::seal_hash_keccak_256),
+			hash_keccak_256_per_byte: per_byte(W::seal_hash_keccak_256_per_kb),
+			hash_blake2_256: batched(W::seal_hash_blake2_256),
+			hash_blake2_256_per_byte: per_byte(W::seal_hash_blake2_256_per_kb),
+			hash_blake2_128: batched(W::seal_hash_blake2_128),
+			hash_blake2_128_per_byte: per_byte(W::seal_hash_blake2_128_per_kb),
+			ecdsa_recover: batched(W::seal_ecdsa_recover),
+			ecdsa_to_eth_address: batched(W::seal_ecdsa_to_eth_address),
+			sr25519_verify: batched(W::seal_sr25519_verify),
+			sr25519_verify_per_byte: per_byte(W::seal_sr25519_verify_per_kb),
+			ed25519_verify: batched(W::seal_ed25519_verify),
+			ed25519_verify_per_byte: per_byte(W::seal_ed25519_verify_per_kb),
+		}
+	}
+}
+
+/// Describes the gas cost of each class of wasm instructions.
+///
+/// The gas instrumentation charges instructions per class which mostly follows
+/// `pwasm_utils::rules::InstructionType`. Only the control flow instructions are split into
+/// branches, `call` and `call_indirect`. Floating point instructions are forbidden and have no
+/// cost assigned. The instructions of WebAssembly proposals like sign-extension or bulk memory
+/// are rejected during preparation because neither `wasmi_validation` nor the sandbox executor
+/// support them yet.
+///
+/// The default values are derived from the `instr_*` benchmarks in `benchmarking.rs` as
+/// reported by the `WeightInfo` of this pallet (see [`InstructionWeights::from_weight_info`]).
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug)]
+pub struct InstructionWeights {
+	/// Cost of `i32.const` and `i64.const`.
+	pub constant: u32,
+
+	/// Cost of `get_local`, `set_local` and `tee_local`.
+	pub local: u32,
+
+	/// Cost of `get_global` and `set_global`.
+	pub global: u32,
+
+	/// Cost of the integer load instructions.
+	pub load: u32,
+
+	/// Cost of the integer store instructions.
+	pub store: u32,
+
+	/// Cost of `block`, `loop`, `if`, `else`, `br`, `br_if`, `br_table` and `return`.
+	///
+	/// `end` is free as it only closes a block which was paid for when it was entered.
+	pub branch: u32,
+
+	/// Cost of `call`.
+	pub call: u32,
+
+	/// Cost of `call_indirect`.
+	pub call_indirect: u32,
+
+	/// Cost of the integer comparisons like `i64.lt_s` and `i32.eqz`.
+	pub integer_comparison: u32,
+
+	/// Cost of the bitwise instructions like `i64.and`, the shifts, rotations and bit counts.
+	pub bit: u32,
+
+	/// Cost of the integer additions and subtractions.
+	pub add: u32,
+
+	/// Cost of the integer multiplications.
+	pub mul: u32,
+
+	/// Cost of the integer divisions and remainders.
+	pub div: u32,
+
+	/// Cost of `i32.wrap/i64` and the integer extensions.
+	pub conversion: u32,
+
+	/// Cost of `unreachable`.
+	pub unreachable: u32,
+
+	/// Cost of `nop`, `drop` and `select`.
+	pub nop: u32,
+
+	/// Cost of `current_memory`.
+	pub current_memory: u32,
+
+	/// Base cost of `grow_memory`. The cost per grown page is `Schedule::grow_mem_cost`.
+	pub grow_memory: u32,
+}
+
+impl Default for InstructionWeights {
+	fn default() -> InstructionWeights {
+		Self::from_weight_info::<()>()
+	}
+}
+
+impl InstructionWeights {
+	/// Derives the costs from the results of the `instr_*` benchmarks as reported by `W`.
+	///
+	/// The benchmarks execute the benchmarked instruction `INSTR_BENCHMARK_BATCH_SIZE` times
+	/// per component step together with the instructions that balance the stack. The costs of
+	/// those are subtracted. `unreachable` traps and can't be benchmarked in batches. It is
+	/// charged like a `nop`.
+	pub fn from_weight_info<W: WeightInfo>() -> Self {
+		let per_instr = |benchmark: fn(u32) -> Weight| -> u32 {
+			(weight_per_step(benchmark) / Weight::from(INSTR_BENCHMARK_BATCH_SIZE)).saturated_into()
+		};
+		// `drop` is a `nop` class instruction.
+		let nop = per_instr(W::instr_nop);
+		let constant = per_instr(W::instr_const).saturating_sub(nop);
+		// The operands are pushed by two `i64.const` and the result is dropped.
+		let binary = |benchmark: fn(u32) -> Weight| {
+			per_instr(benchmark).saturating_sub(constant.saturating_mul(2).saturating_add(nop))
+		};
+
+		InstructionWeights {
+			constant,
+			local: per_instr(W::instr_local) / 2,
+			global: per_instr(W::instr_global) / 2,
+			load: per_instr(W::instr_load).saturating_sub(constant.saturating_add(nop)),
+			store: per_instr(W::instr_store).saturating_sub(constant.saturating_mul(2)),
+			// A `block` left by a `br`. `end` is free.
+			branch: per_instr(W::instr_branch) / 2,
+			call: per_instr(W::instr_call),
+			call_indirect: per_instr(W::instr_call_indirect).saturating_sub(constant),
+			integer_comparison: binary(W::instr_integer_comparison),
+			bit: binary(W::instr_bit),
+			add: binary(W::instr_add),
+			mul: binary(W::instr_mul),
+			div: binary(W::instr_div),
+			conversion: per_instr(W::instr_conversion).saturating_sub(constant.saturating_add(nop)),
+			unreachable: nop,
+			nop,
+			current_memory: per_instr(W::instr_current_memory).saturating_sub(nop),
+			grow_memory: per_instr(W::instr_grow_memory)
+				.saturating_sub(constant.saturating_add(nop)),
+		}
+	}
+}
+
+/// Returns the increase of the weight reported for `benchmark` per component step.
+fn weight_per_step(benchmark: fn(u32) -> Weight) -> Weight {
+	benchmark(1).saturating_sub(benchmark(0))
+}
@@ -18,14 +18,17 @@
 
 use crate::{
 	AliveContractInfo, BalanceOf, ContractInfo, ContractInfoOf, Module, RawEvent,
-	TombstoneContractInfo, Trait, CodeHash, Config
+	TombstoneContractInfo, Trait, CodeHash, Config, RentStatus, CodeStorage, Error,
+	TrieIdGenerator, StorageMode, StorageModeOf,
 };
-use sp_std::prelude::*;
-use sp_io::hashing::blake2_256;
+use crate::exec::StorageKey;
+use sp_std::{prelude::*, collections::btree_map::BTreeMap};
 use frame_support::storage::child;
 use frame_support::traits::{Currency, ExistenceRequirement, Get, OnUnbalanced, WithdrawReason};
 use frame_support::StorageMap;
 use pallet_contracts_primitives::{ContractAccessError, RentProjection, RentProjectionResult};
+use sp_trie::{verify_trie_proof, Layout, TrieConfiguration};
+use codec::Decode;
 use sp_runtime::traits::{Bounded, CheckedDiv, CheckedMul, SaturatedConversion, Saturating, Zero};
 
 /// The amount to charge.
@@ -82,29 +85,131 @@ enum Verdict<T: Trait> {
 	Charge { amount: OutstandingAmount<T> },
 }
 
-/// Returns a fee charged per block from the contract.
+/// Determines the rent a contract has to pay per block.
 ///
-/// This function accounts for the storage rent deposit. I.e. if the contract possesses enough funds
+/// This allows runtimes to plug in their own pricing, e.g. per item fees, tiered byte pricing,
+/// discounts that decay over time or lists of exempt contracts. Rent collection, eviction, the
+/// rent projection and the rent status all use the configured curve.
+///
+/// All functions receive the same arguments: `free_balance` is the free balance of the contract
+/// at `account` and `current_block_number` the block for which the rent is computed.
+pub trait RentCurve<T: Trait> {
+	/// Returns the fee charged per block from the contract at `account`.
+	///
+	/// Returning zero exempts the contract from paying rent.
+	fn fee_per_block(
+		account: &T::AccountId,
+		contract: &AliveContractInfo<T>,
+		free_balance: &BalanceOf<T>,
+		current_block_number: T::BlockNumber,
+	) -> BalanceOf<T>;
+
+	/// Returns the number of bytes the contract at `account` pays rent for.
+	fn effective_storage_size(
+		account: &T::AccountId,
+		contract: &AliveContractInfo<T>,
+		free_balance: &BalanceOf<T>,
+		current_block_number: T::BlockNumber,
+	) -> BalanceOf<T>;
+
+	/// Returns the number of bytes the contract at `account` doesn't pay rent for because
+	/// of its balance.
+	fn free_storage(
+		account: &T::AccountId,
+		contract: &AliveContractInfo<T>,
+		free_balance: &BalanceOf<T>,
+		current_block_number: T::BlockNumber,
+	) -> BalanceOf<T>;
+
+	/// Returns the balance the contract at `account` needs in addition to `free_balance` in
+	/// order to be exempt from rent.
+	///
+	/// Returns the maximum balance if no deposit exempts the contract.
+	fn exemption_deposit(
+		account: &T::AccountId,
+		contract: &AliveContractInfo<T>,
+		free_balance: &BalanceOf<T>,
+		current_block_number: T::BlockNumber,
+	) -> BalanceOf<T>;
+}
+
+/// The default [`RentCurve`].
+///
+/// The fee is linear in the storage used by the contract:
+///
+/// ```ignore
+/// (storage_size + StorageSizeOffset + empty_pairs - free_balance / RentDepositOffset) * RentByteFee
+/// ```
+///
+/// This accounts for the storage rent deposit. I.e. if the contract possesses enough funds
 /// then the fee can drop to zero.
-fn compute_fee_per_block<T: Trait>(
-	free_balance: &BalanceOf<T>,
-	contract: &AliveContractInfo<T>,
-) -> BalanceOf<T> {
-	let free_storage = free_balance
+pub struct LinearRentCurve;
+
+impl<T: Trait> RentCurve<T> for LinearRentCurve {
+	fn fee_per_block(
+		account: &T::AccountId,
+		contract: &AliveContractInfo<T>,
+		free_balance: &BalanceOf<T>,
+		current_block_number: T::BlockNumber,
+	) -> BalanceOf<T> {
+		let effective_storage_size = <Self as RentCurve<T>>::effective_storage_size(
+			account,
+			contract,
+			free_balance,
+			current_block_number,
+		);
+
+		effective_storage_size
+			.checked_mul(&T::RentByteFee::get())
+			.unwrap_or_else(|| <BalanceOf<T>>::max_value())
+	}
+
+	fn effective_storage_size(
+		_account: &T::AccountId,
+		contract: &AliveContractInfo<T>,
+		free_balance: &BalanceOf<T>,
+		_current_block_number: T::BlockNumber,
+	) -> BalanceOf<T> {
+		billed_storage_size::<T>(contract).saturating_sub(free_storage::<T>(free_balance))
+	}
+
+	fn free_storage(
+		_account: &T::AccountId,
+		_contract: &AliveContractInfo<T>,
+		free_balance: &BalanceOf<T>,
+		_current_block_number: T::BlockNumber,
+	) -> BalanceOf<T> {
+		free_storage::<T>(free_balance)
+	}
+
+	fn exemption_deposit(
+		_account: &T::AccountId,
+		contract: &AliveContractInfo<T>,
+		free_balance: &BalanceOf<T>,
+		_current_block_number: T::BlockNumber,
+	) -> BalanceOf<T> {
+		billed_storage_size::<T>(contract)
+			.saturating_mul(T::RentDepositOffset::get())
+			.saturating_sub(*free_balance)
+	}
+}
+
+/// Returns the amount of storage (in bytes) that is offset by the given free balance.
+fn free_storage<T: Trait>(free_balance: &BalanceOf<T>) -> BalanceOf<T> {
+	free_balance
 		.checked_div(&T::RentDepositOffset::get())
-		.unwrap_or_else(Zero::zero);
+		.unwrap_or_else(Zero::zero)
+}
 
+/// Returns the amount of storage (in bytes) the contract is billed for before the rent deposit
+/// is taken into account.
+fn billed_storage_size<T: Trait>(contract: &AliveContractInfo<T>) -> BalanceOf<T> {
 	// For now, we treat every empty KV pair as if it was one byte long.
 	let empty_pairs_equivalent = contract.empty_pair_count;
 
-	let effective_storage_size = <BalanceOf<T>>::from(
+	<BalanceOf<T>>::from(
 		contract.storage_size + T::StorageSizeOffset::get() + empty_pairs_equivalent,
 	)
-	.saturating_sub(free_storage);
-
-	effective_storage_size
-		.checked_mul(&T::RentByteFee::get())
-		.unwrap_or_else(|| <BalanceOf<T>>::max_value())
 }
 
 /// Returns amount of funds available to consume by rent mechanism.
@@ -159,7 +264,12 @@ fn consider_case<T: Trait>(
 	let free_balance = T::Currency::free_balance(account);
 
 	// An amount of funds to charge per block for storage taken up by the contract.
-	let fee_per_block = compute_fee_per_block::<T>(&free_balance, contract);
+	let fee_per_block = T::RentCurve::fee_per_block(
+		account,
+		contract,
+		&free_balance,
+		current_block_number,
+	);
 	if fee_per_block.is_zero() {
 		// The rent deposit offset reduced the fee to 0. This means that the contract
 		// gets the rent for free.
@@ -227,6 +337,7 @@ fn enact_verdict<T: Trait>(
 		Verdict::Exempt => return Some(ContractInfo::Alive(alive_contract_info)),
 		Verdict::Kill => {
 			<ContractInfoOf<T>>::remove(account);
+			<StorageModeOf>::remove(&alive_contract_info.trie_id);
 			child::kill_storage(
 				&alive_contract_info.child_trie_info(),
 			);
@@ -246,6 +357,7 @@ fn enact_verdict<T: Trait>(
 			let tombstone = <TombstoneContractInfo<T>>::new(
 				&child_storage_root[..],
 				alive_contract_info.code_hash,
+				<StorageModeOf>::take(&alive_contract_info.trie_id),
 			);
 			let tombstone_info = ContractInfo::Tombstone(tombstone);
 			<ContractInfoOf<T>>::insert(account, &tombstone_info);
@@ -369,7 +481,12 @@ pub fn compute_rent_projection<T: Trait>(
 	// Compute how much would the fee per block be with the *updated* balance.
 	let total_balance = T::Currency::total_balance(account);
 	let free_balance = T::Currency::free_balance(account);
-	let fee_per_block = compute_fee_per_block::<T>(&free_balance, &alive_contract_info);
+	let fee_per_block = T::RentCurve::fee_per_block(
+		account,
+		&alive_contract_info,
+		&free_balance,
+		current_block_number,
+	);
 	if fee_per_block.is_zero() {
 		return Ok(RentProjection::NoEviction);
 	}
@@ -398,6 +515,94 @@ pub fn compute_rent_projection<T: Trait>(
 	))
 }
 
+/// Returns a detailed breakdown of the rent situation of the given contract.
+///
+/// Just like [`compute_rent_projection`] the returned status is relevant for the current block,
+/// i.e. it is as if the contract was accessed at the beginning of the current block. In contrast
+/// to it this function doesn't touch the storage: the outstanding rent is only deducted from
+/// local copies of the balance and the rent allowance. That makes it safe to be called from
+/// anywhere, including RPC.
+///
+/// Returns `Err(ContractAccessError::IsTombstone)` if the contract is a tombstone or would be
+/// evicted as soon as it is touched.
+pub fn compute_rent_status<T: Trait>(
+	account: &T::AccountId,
+) -> Result<RentStatus<T>, ContractAccessError> {
+	let alive_contract_info = match <ContractInfoOf<T>>::get(account) {
+		None => return Err(ContractAccessError::DoesntExist),
+		Some(ContractInfo::Tombstone(_)) => return Err(ContractAccessError::IsTombstone),
+		Some(ContractInfo::Alive(contract)) => contract,
+	};
+	let current_block_number = <frame_system::Module<T>>::block_number();
+	let verdict = consider_case::<T>(
+		account,
+		current_block_number,
+		Zero::zero(),
+		&alive_contract_info,
+	);
+
+	// Simulate the enaction of the verdict without writing anything to storage.
+	let total_balance = T::Currency::total_balance(account);
+	let free_balance = T::Currency::free_balance(account);
+	let (rent_allowance, total_balance, free_balance) = match verdict {
+		Verdict::Exempt => (alive_contract_info.rent_allowance, total_balance, free_balance),
+		Verdict::Charge { amount } => (
+			alive_contract_info.rent_allowance.saturating_sub(amount.peek()),
+			total_balance.saturating_sub(amount.peek()),
+			free_balance.saturating_sub(amount.peek()),
+		),
+		Verdict::Kill | Verdict::Evict { .. } => return Err(ContractAccessError::IsTombstone),
+	};
+	let contract = AliveContractInfo::<T> {
+		rent_allowance,
+		..alive_contract_info
+	};
+
+	let fee_per_block = T::RentCurve::fee_per_block(
+		account,
+		&contract,
+		&free_balance,
+		current_block_number,
+	);
+	let effective_storage_size = T::RentCurve::effective_storage_size(
+		account,
+		&contract,
+		&free_balance,
+		current_block_number,
+	);
+	let free_storage = T::RentCurve::free_storage(
+		account,
+		&contract,
+		&free_balance,
+		current_block_number,
+	);
+	let exemption_deposit = T::RentCurve::exemption_deposit(
+		account,
+		&contract,
+		&free_balance,
+		current_block_number,
+	);
+	let rent_budget = rent_budget::<T>(&total_balance, &free_balance, &contract)
+		.unwrap_or_else(Zero::zero);
+	let eviction_at = if fee_per_block.is_zero() {
+		None
+	} else {
+		rent_budget.checked_div(&fee_per_block).map(|blocks_left| {
+			current_block_number + blocks_left.saturated_into::<u32>().into()
+		})
+	};
+
+	Ok(RentStatus::<T> {
+		fee_per_block,
+		effective_storage_size,
+		free_storage,
+		rent_budget,
+		rent_allowance,
+		eviction_at,
+		exemption_deposit,
+	})
+}
+
 /// Restores the destination account using the origin as prototype.
 ///
 /// The restoration will be performed iff:
@@ -405,6 +610,7 @@ pub fn compute_rent_projection<T: Trait>(
 /// - the origin's storage is not written in the current block
 /// - the restored account has tombstone
 /// - the tombstone matches the hash of the origin storage root, and code hash.
+/// - the tombstone matches the storage mode of the origin.
 ///
 /// Upon succesful restoration, `origin` will be destroyed, all its funds are transferred to
 /// the restored account. The restored account will inherit the last write block and its last
@@ -414,13 +620,14 @@ pub fn restore_to<T: Trait>(
 	dest: T::AccountId,
 	code_hash: CodeHash<T>,
 	rent_allowance: BalanceOf<T>,
-	delta: Vec<crate::exec::StorageKey>,
+	delta: Vec<StorageKey>,
 ) -> Result<(), &'static str> {
 	let mut origin_contract = <ContractInfoOf<T>>::get(&origin)
 		.and_then(|c| c.get_alive())
 		.ok_or("Cannot restore from inexisting or tombstone contract")?;
 
 	let child_trie_info = origin_contract.child_trie_info();
+	let storage_mode = <StorageModeOf>::get(&origin_contract.trie_id);
 
 	let current_block = <frame_system::Module<T>>::block_number();
 
@@ -440,8 +647,8 @@ pub fn restore_to<T: Trait>(
 
 	let key_values_taken = delta.iter()
 		.filter_map(|key| {
-			child::get_raw(&child_trie_info, &blake2_256(key)).map(|value| {
-				child::kill(&child_trie_info, &blake2_256(key));
+			child::get_raw(&child_trie_info, &storage_mode.child_key(key)).map(|value| {
+				child::kill(&child_trie_info, &storage_mode.child_key(key));
 				(key, value)
 			})
 		})
@@ -452,11 +659,12 @@ pub fn restore_to<T: Trait>(
 		// is not this block as it has been checked earlier.
 		&child::root(&child_trie_info)[..],
 		code_hash,
+		storage_mode,
 	);
 
 	if tombstone != dest_tombstone {
 		for (key, value) in key_values_taken {
-			child::put_raw(&child_trie_info, &blake2_256(key), &value);
+			child::put_raw(&child_trie_info, &storage_mode.child_key(key), &value);
 		}
 
 		return Err("Tombstones don't match");
@@ -484,3 +692,143 @@ pub fn restore_to<T: Trait>(
 
 	Ok(())
 }
+
+/// Restores the destination account from the given storage items.
+///
+/// The restoration will be performed iff:
+/// - the restored account has tombstone
+/// - the code identified by `code_hash` is stored on chain
+/// - the tombstone matches the hash of the storage root built from `storage_items`, and code hash.
+///
+/// In contrast to [`restore_to`] no donor contract is needed: a fresh child trie is filled with
+/// the supplied items. If the same key is supplied more than once the last value wins.
+/// The restored account keeps its balance and its last deduct block will be set to the current
+/// block. The keys of the items are the keys used by the contract. They are written according
+/// to the storage mode kept in the tombstone which the restored contract uses again.
+pub fn restore_from_storage<T: Trait>(
+	dest: &T::AccountId,
+	code_hash: CodeHash<T>,
+	rent_allowance: BalanceOf<T>,
+	storage_items: Vec<(StorageKey, Vec<u8>)>,
+) -> Result<(), Error<T>> {
+	let dest_tombstone = restorable_tombstone::<T>(dest, &code_hash)?;
+	let storage_mode = dest_tombstone.storage_mode();
+
+	let items = storage_items.into_iter().collect::<BTreeMap<_, _>>();
+
+	// The root is computed in memory so that nothing is written before the items
+	// are known to match the tombstone.
+	let storage_root = Layout::<T::Hashing>::trie_root(
+		items.iter().map(|(key, value)| (storage_mode.child_key(key), value))
+	);
+	let tombstone = <TombstoneContractInfo<T>>::new(
+		storage_root.as_ref(),
+		code_hash,
+		storage_mode,
+	);
+
+	if tombstone != dest_tombstone {
+		return Err(Error::<T>::InvalidTombstone);
+	}
+
+	revive::<T>(dest, code_hash, rent_allowance, storage_mode, &items);
+
+	Ok(())
+}
+
+/// Restores the destination account from a subset of its storage.
+///
+/// The restoration will be performed iff:
+/// - the restored account has tombstone
+/// - the code identified by `code_hash` is stored on chain
+/// - the tombstone matches the hash of the storage root kept in it, and code hash. Tombstones
+///   which don't keep their storage root can't be restored this way.
+/// - `proof` proves that every item of `storage_items` is contained in the storage root
+///   kept in the tombstone.
+///
+/// Only the supplied items are written into the child trie of the restored contract, everything
+/// else the contract had stored before its eviction is dropped. This allows to revive contracts
+/// with a large storage without supplying all of it. Otherwise, this function behaves like
+/// [`restore_from_storage`].
+pub fn restore_with_proof<T: Trait>(
+	dest: &T::AccountId,
+	code_hash: CodeHash<T>,
+	rent_allowance: BalanceOf<T>,
+	storage_items: Vec<(StorageKey, Vec<u8>)>,
+	proof: Vec<Vec<u8>>,
+) -> Result<(), Error<T>> {
+	let dest_tombstone = restorable_tombstone::<T>(dest, &code_hash)?;
+
+	let storage_mode = dest_tombstone.storage_mode();
+
+	// The storage root is taken from the tombstone itself. Hence, this only checks that
+	// `code_hash` is the one of the evicted contract and that the tombstone kept its root.
+	let tombstone = <TombstoneContractInfo<T>>::new(
+		dest_tombstone.storage_root(),
+		code_hash,
+		storage_mode,
+	);
+	if tombstone != dest_tombstone {
+		return Err(Error::<T>::InvalidTombstone);
+	}
+
+	let storage_root = <T::Hash as Decode>::decode(&mut &dest_tombstone.storage_root()[..])
+		.map_err(|_| Error::<T>::InvalidTombstone)?;
+	let items = storage_items.into_iter().collect::<BTreeMap<_, _>>();
+	let proven_items = items.iter()
+		.map(|(key, value)| (storage_mode.child_key(key), Some(value)))
+		.collect::<Vec<_>>();
+	verify_trie_proof::<Layout<T::Hashing>, _, _, _>(&storage_root, &proof, &proven_items)
+		.map_err(|_| Error::<T>::InvalidTombstone)?;
+
+	revive::<T>(dest, code_hash, rent_allowance, storage_mode, &items);
+
+	Ok(())
+}
+
+/// Returns the tombstone at `dest` if it can be restored with the code at `code_hash`.
+fn restorable_tombstone<T: Trait>(
+	dest: &T::AccountId,
+	code_hash: &CodeHash<T>,
+) -> Result<TombstoneContractInfo<T>, Error<T>> {
+	let dest_tombstone = <ContractInfoOf<T>>::get(dest)
+		.and_then(|c| c.get_tombstone())
+		.ok_or(Error::<T>::InvalidDestinationContract)?;
+
+	if !<CodeStorage<T>>::contains_key(code_hash) {
+		return Err(Error::<T>::CodeNotFound);
+	}
+
+	Ok(dest_tombstone)
+}
+
+/// Replaces the tombstone at `dest` with an alive contract whose fresh child trie is filled
+/// with `items` according to `storage_mode`.
+fn revive<T: Trait>(
+	dest: &T::AccountId,
+	code_hash: CodeHash<T>,
+	rent_allowance: BalanceOf<T>,
+	storage_mode: StorageMode,
+	items: &BTreeMap<StorageKey, Vec<u8>>,
+) {
+	let trie_id = T::TrieIdGenerator::trie_id(dest);
+	let child_trie_info = crate::child_trie_info(&trie_id);
+	for (key, value) in items.iter() {
+		child::put_raw(&child_trie_info, &storage_mode.child_key(key), value);
+	}
+	if storage_mode != StorageMode::default() {
+		<StorageModeOf>::insert(&trie_id, storage_mode);
+	}
+
+	let current_block = <frame_system::Module<T>>::block_number();
+	<ContractInfoOf<T>>::insert(dest, ContractInfo::Alive(AliveContractInfo::<T> {
+		trie_id,
+		storage_size: items.values().map(|value| value.len() as u32).sum(),
+		empty_pair_count: items.values().filter(|value| value.is_empty()).count() as u32,
+		total_pair_count: items.len() as u32,
+		code_hash,
+		rent_allowance,
+		deduct_block: current_block,
+		last_write: if items.is_empty() { None } else { Some(current_block) },
+	}));
+}
@@ -18,12 +18,12 @@
 
 use crate::{
 	exec::{AccountIdOf, StorageKey},
-	AliveContractInfo, BalanceOf, CodeHash, ContractInfo, ContractInfoOf, Trait, TrieId,
+	AliveContractInfo, BalanceOf, CodeHash, ContractInfo, ContractInfoOf, Error, StorageMode,
+	StorageModeOf, Trait, TrieId,
 };
-use sp_std::prelude::*;
-use sp_io::hashing::blake2_256;
+use sp_std::{prelude::*, collections::btree_map::BTreeMap};
 use sp_runtime::traits::Bounded;
-use frame_support::{storage::child, StorageMap};
+use frame_support::{ensure, storage::child, StorageMap};
 
 /// An error that means that the account requested either doesn't exist or represents a tombstone
 /// account.
@@ -34,13 +34,125 @@ pub struct ContractAbsentError;
 ///
 /// The read is performed from the `trie_id` only. The `address` is not necessary. If the contract
 /// doesn't store under the given `key` `None` is returned.
-pub fn read_contract_storage(trie_id: &TrieId, key: &StorageKey) -> Option<Vec<u8>> {
-	child::get_raw(&crate::child_trie_info(&trie_id), &blake2_256(key))
+///
+/// The `key` can be of any length. A 32 byte key refers to the same entry as the
+/// [`StorageKey`] with the same bytes. The `storage_mode` must be the one returned by
+/// [`storage_mode`] for `trie_id`. It is passed in so that it is only read once per call
+/// frame instead of once per storage access.
+pub fn read_contract_storage(
+	trie_id: &TrieId,
+	storage_mode: StorageMode,
+	key: &[u8],
+) -> Option<Vec<u8>> {
+	child::get_raw(&crate::child_trie_info(&trie_id), &storage_mode.child_key(key))
+}
+
+/// Returns the size of the value stored under the given `key` of a contract without
+/// reading the value.
+///
+/// Returns `None` if the contract doesn't store under the given `key`.
+pub fn contract_storage_size(
+	trie_id: &TrieId,
+	storage_mode: StorageMode,
+	key: &[u8],
+) -> Option<u32> {
+	child::len(&crate::child_trie_info(&trie_id), &storage_mode.child_key(key))
+}
+
+/// Returns the storage mode the contract owning `trie_id` was instantiated with.
+///
+/// This is a storage read of its own. Callers that access the storage of the same contract
+/// repeatedly should read the mode once and pass it on.
+pub fn storage_mode(trie_id: &TrieId) -> StorageMode {
+	<StorageModeOf>::get(trie_id)
+}
+
+/// Returns the smallest key of the child trie that starts with `prefix` and is greater
+/// than `after`.
+///
+/// If `after` is `None` the smallest key starting with `prefix` is returned. The returned
+/// keys are the keys of the child trie: for a contract in `StorageMode::Hashed` these are
+/// the hashes of the keys used by the contract.
+pub fn next_child_key(trie_id: &TrieId, prefix: &[u8], after: Option<&[u8]>) -> Option<Vec<u8>> {
+	let child_trie_info = crate::child_trie_info(&trie_id);
+	let next_key = |key: &[u8]| {
+		sp_io::default_child_storage::next_key(child_trie_info.storage_key(), key)
+	};
+	let next = match after {
+		Some(after) if after >= prefix => next_key(after),
+		_ if child::exists(&child_trie_info, prefix) => Some(prefix.to_vec()),
+		_ => next_key(prefix),
+	};
+	next.filter(|key| key.starts_with(prefix))
+}
+
+/// Returns all key-value pairs of the child trie of a contract in ascending order of their keys.
+pub fn contract_storage_items(trie_id: &TrieId) -> Vec<(Vec<u8>, Vec<u8>)> {
+	let child_trie_info = crate::child_trie_info(&trie_id);
+	let mut items: Vec<(Vec<u8>, Vec<u8>)> = Vec::new();
+	loop {
+		let after = items.last().map(|(key, _)| &key[..]);
+		match next_child_key(trie_id, &[], after) {
+			Some(key) => {
+				let value = child::get_raw(&child_trie_info, &key).unwrap_or_default();
+				items.push((key, value));
+			}
+			None => return items,
+		}
+	}
+}
+
+/// Writes `items` into the empty child trie of the contract at `account`.
+///
+/// The keys are used as keys of the child trie without hashing them. If the same key is
+/// supplied more than once the last value wins. The import is only performed if the root of
+/// the resulting child trie equals `expected_root`. The bookkeeping info of the contract is
+/// updated accordingly and its storage mode is set to `storage_mode`, which has to be the
+/// mode the keys were created with. Returns the number of imported items.
+pub fn import_contract_storage<T: Trait>(
+	account: &AccountIdOf<T>,
+	storage_mode: StorageMode,
+	items: Vec<(Vec<u8>, Vec<u8>)>,
+	expected_root: &[u8],
+) -> Result<u32, Error<T>> {
+	let mut new_info = match <ContractInfoOf<T>>::get(account) {
+		Some(ContractInfo::Alive(alive)) => alive,
+		None | Some(ContractInfo::Tombstone(_)) => return Err(Error::<T>::NotCallable),
+	};
+	ensure!(new_info.total_pair_count == 0, Error::<T>::StorageNotEmpty);
+
+	let items = items.into_iter().collect::<BTreeMap<_, _>>();
+	let child_trie_info = new_info.child_trie_info();
+	for (key, value) in items.iter() {
+		child::put_raw(&child_trie_info, key, value);
+	}
+
+	if &child::root(&child_trie_info)[..] != expected_root {
+		child::kill_storage(&child_trie_info);
+		return Err(Error::<T>::InvalidStorageRoot);
+	}
+
+	// The storage is empty, so switching the mode doesn't orphan any existing items.
+	if storage_mode == StorageMode::default() {
+		<StorageModeOf>::remove(&new_info.trie_id);
+	} else {
+		<StorageModeOf>::insert(&new_info.trie_id, storage_mode);
+	}
+
+	new_info.total_pair_count = items.len() as u32;
+	new_info.empty_pair_count = items.values().filter(|value| value.is_empty()).count() as u32;
+	new_info.storage_size = items.values()
+		.fold(0u32, |acc, value| acc.saturating_add(value.len() as u32));
+	new_info.last_write = Some(<frame_system::Module<T>>::block_number());
+	<ContractInfoOf<T>>::insert(account, ContractInfo::Alive(new_info));
+
+	Ok(items.len() as u32)
 }
 
 /// Update a storage entry into a contract's kv storage.
 ///
-/// If the `opt_new_value` is `None` then the kv pair is removed.
+/// If the `opt_new_value` is `None` then the kv pair is removed. Returns the size of the
+/// previous value or `None` if there was no entry under the given `key`.
 ///
 /// This function also updates the bookkeeping info such as: number of total non-empty pairs a
 /// contract owns, the last block the storage was written to, etc. That's why, in contrast to
@@ -50,15 +162,43 @@ pub fn read_contract_storage(trie_id: &TrieId, key: &StorageKey) -> Option<Vec<u
 pub fn write_contract_storage<T: Trait>(
 	account: &AccountIdOf<T>,
 	trie_id: &TrieId,
-	key: &StorageKey,
+	storage_mode: StorageMode,
+	key: &[u8],
 	opt_new_value: Option<Vec<u8>>,
-) -> Result<(), ContractAbsentError> {
+) -> Result<Option<u32>, ContractAbsentError> {
+	replace_contract_storage::<T>(account, trie_id, storage_mode, key, opt_new_value)
+		.map(|prev_value| prev_value.map(|value| value.len() as u32))
+}
+
+/// Remove a storage entry from a contract's kv storage and return its value.
+///
+/// Returns `Ok(None)` if there was no entry under the given `key`. The bookkeeping info
+/// is updated in the same way as by [`write_contract_storage`].
+///
+/// If the contract specified by the id `account` doesn't exist `Err` is returned.
+pub fn take_contract_storage<T: Trait>(
+	account: &AccountIdOf<T>,
+	trie_id: &TrieId,
+	storage_mode: StorageMode,
+	key: &[u8],
+) -> Result<Option<Vec<u8>>, ContractAbsentError> {
+	replace_contract_storage::<T>(account, trie_id, storage_mode, key, None)
+}
+
+/// Update a storage entry like [`write_contract_storage`] and return the previous value.
+fn replace_contract_storage<T: Trait>(
+	account: &AccountIdOf<T>,
+	trie_id: &TrieId,
+	storage_mode: StorageMode,
+	key: &[u8],
+	opt_new_value: Option<Vec<u8>>,
+) -> Result<Option<Vec<u8>>, ContractAbsentError> {
 	let mut new_info = match <ContractInfoOf<T>>::get(account) {
 		Some(ContractInfo::Alive(alive)) => alive,
 		None | Some(ContractInfo::Tombstone(_)) => return Err(ContractAbsentError),
 	};
 
-	let hashed_key = blake2_256(key);
+	let child_key = storage_mode.child_key(key);
 	let child_trie_info = &crate::child_trie_info(&trie_id);
 
 	// In order to correctly update the book keeping we need to fetch the previous
@@ -70,7 +210,7 @@ pub fn write_contract_storage<T: Trait>(
 	//
 	// That's not a show stopper in any case, since the performance cost is
 	// dominated by the trie traversal anyway.
-	let opt_prev_value = child::get_raw(&child_trie_info, &hashed_key);
+	let opt_prev_value = child::get_raw(&child_trie_info, &child_key);
 
 	// Update the total number of KV pairs and the number of empty pairs.
 	match (&opt_prev_value, &opt_new_value) {
@@ -116,11 +256,11 @@ pub fn write_contract_storage<T: Trait>(
 
 	// Finally, perform the change on the storage.
 	match opt_new_value {
-		Some(new_value) => child::put_raw(&child_trie_info, &hashed_key, &new_value[..]),
-		None => child::kill(&child_trie_info, &hashed_key),
+		Some(new_value) => child::put_raw(&child_trie_info, &child_key, &new_value[..]),
+		None => child::kill(&child_trie_info, &child_key),
 	}
 
-	Ok(())
+	Ok(opt_prev_value)
 }
 
 /// Returns the rent allowance set for the contract give by the account id.
@@ -158,17 +298,24 @@ pub fn code_hash<T: Trait>(account: &AccountIdOf<T>) -> Result<CodeHash<T>, Cont
 
 /// Creates a new contract descriptor in the storage with the given code hash at the given address.
 ///
+/// The keys of the contract's storage are mapped according to `storage_mode`.
+///
 /// Returns `Err` if there is already a contract (or a tombstone) exists at the given address.
 pub fn place_contract<T: Trait>(
 	account: &AccountIdOf<T>,
 	trie_id: TrieId,
 	ch: CodeHash<T>,
+	storage_mode: StorageMode,
 ) -> Result<(), &'static str> {
 	<ContractInfoOf<T>>::mutate(account, |maybe_contract_info| {
 		if maybe_contract_info.is_some() {
 			return Err("Alive contract or tombstone already exists");
 		}
 
+		if storage_mode != StorageMode::default() {
+			<StorageModeOf>::insert(&trie_id, storage_mode);
+		}
+
 		*maybe_contract_info = Some(
 			AliveContractInfo::<T> {
 				code_hash: ch,
@@ -192,5 +339,6 @@ pub fn place_contract<T: Trait>(
 /// This function doesn't affect the account.
 pub fn destroy_contract<T: Trait>(address: &AccountIdOf<T>, trie_id: &TrieId) {
 	<ContractInfoOf<T>>::remove(address);
+	<StorageModeOf>::remove(trie_id);
 	child::kill_storage(&crate::child_trie_info(&trie_id));
 }
@@ -17,23 +17,29 @@
 use crate::{
 	BalanceOf, ContractAddressFor, ContractInfo, ContractInfoOf, GenesisConfig, Module,
 	RawAliveContractInfo, RawEvent, Trait, TrieId, Schedule, TrieIdGenerator, gas::Gas,
-	Error, Config, RuntimeReturnCode,
+	Error, Config, RuntimeReturnCode, LinearRentCurve, RentCurve, AliveContractInfo, WeightInfo,
+	CodeMigrationCursor, ScheduleChange, PrepareError, StorageMode, StorageModeOf, CallFlags,
+	wasm::migrate_code,
 };
 use assert_matches::assert_matches;
+use pallet_contracts_primitives::ContractAccessError;
 use hex_literal::*;
-use codec::Encode;
+use codec::{Encode, Decode};
 use sp_runtime::{
 	Perbill,
 	traits::{BlakeTwo256, Hash, IdentityLookup, Convert},
 	testing::{Header, H256},
 };
 use frame_support::{
-	assert_ok, assert_err_ignore_postinfo, impl_outer_dispatch, impl_outer_event,
+	assert_ok, assert_noop, assert_err, assert_err_ignore_postinfo, impl_outer_dispatch, impl_outer_event,
 	impl_outer_origin, parameter_types, StorageMap, StorageValue,
-	traits::{Currency, Get, ReservableCurrency},
-	weights::{Weight, PostDispatchInfo},
+	traits::{Currency, Get, OnInitialize, OnRuntimeUpgrade, ReservableCurrency},
+	weights::{Weight, PostDispatchInfo, RuntimeDbWeight},
 	dispatch::DispatchErrorWithPostInfo,
 };
+use sp_io::hashing::blake2_256;
+use sp_core::{Pair, sr25519, ed25519, testing::KeyStore, traits::KeystoreExt};
+use sp_trie::{generate_trie_proof, Layout, MemoryDB, TrieDBMut, TrieMut};
 use std::cell::RefCell;
 use frame_system::{self as system, EventRecord, Phase};
 
@@ -68,21 +74,23 @@ impl_outer_dispatch! {
 pub mod test_utils {
 	use super::{Test, Balances};
 	use crate::{ContractInfoOf, TrieIdGenerator, CodeHash};
-	use crate::storage::{write_contract_storage, read_contract_storage};
+	use crate::storage::{write_contract_storage, read_contract_storage, storage_mode};
 	use crate::exec::StorageKey;
 	use frame_support::{StorageMap, traits::Currency};
 
 	pub fn set_storage(addr: &u64, key: &StorageKey, value: Option<Vec<u8>>) {
 		let contract_info = <ContractInfoOf::<Test>>::get(&addr).unwrap().get_alive().unwrap();
-		write_contract_storage::<Test>(&1, &contract_info.trie_id, key, value).unwrap();
+		let trie_id = &contract_info.trie_id;
+		write_contract_storage::<Test>(&1, trie_id, storage_mode(trie_id), key, value).unwrap();
 	}
 	pub fn get_storage(addr: &u64, key: &StorageKey) -> Option<Vec<u8>> {
 		let contract_info = <ContractInfoOf::<Test>>::get(&addr).unwrap().get_alive().unwrap();
-		read_contract_storage(&contract_info.trie_id, key)
+		read_contract_storage(&contract_info.trie_id, storage_mode(&contract_info.trie_id), key)
 	}
 	pub fn place_contract(address: &u64, code_hash: CodeHash<Test>) {
 		let trie_id = <Test as crate::Trait>::TrieIdGenerator::trie_id(address);
-		crate::storage::place_contract::<Test>(&address, trie_id, code_hash).unwrap()
+		crate::storage::place_contract::<Test>(&address, trie_id, code_hash, Default::default())
+			.unwrap()
 	}
 	pub fn set_balance(who: &u64, amount: u64) {
 		let imbalance = Balances::deposit_creating(who, amount);
@@ -101,6 +109,9 @@ pub mod test_utils {
 
 thread_local! {
 	static EXISTENTIAL_DEPOSIT: RefCell<u64> = RefCell::new(0);
+	static STRIP_PRISTINE_CODE: RefCell<bool> = RefCell::new(false);
+	static RENT_PER_ITEM: RefCell<Option<u64>> = RefCell::new(None);
+	static DB_WEIGHT: RefCell<RuntimeDbWeight> = RefCell::new(RuntimeDbWeight { read: 0, write: 0 });
 }
 
 pub struct ExistentialDeposit;
@@ -108,6 +119,77 @@ impl Get<u64> for ExistentialDeposit {
 	fn get() -> u64 { EXISTENTIAL_DEPOSIT.with(|v| *v.borrow()) }
 }
 
+pub struct DbWeight;
+impl Get<RuntimeDbWeight> for DbWeight {
+	fn get() -> RuntimeDbWeight { DB_WEIGHT.with(|v| *v.borrow()) }
+}
+
+pub struct StripPristineCode;
+impl Get<bool> for StripPristineCode {
+	fn get() -> bool { STRIP_PRISTINE_CODE.with(|v| *v.borrow()) }
+}
+
+/// Charges a flat fee per storage item if `ExtBuilder::rent_per_item` is set and behaves
+/// like `LinearRentCurve` otherwise.
+pub struct TestRentCurve;
+impl RentCurve<Test> for TestRentCurve {
+	fn fee_per_block(
+		account: &u64,
+		contract: &AliveContractInfo<Test>,
+		free_balance: &u64,
+		current_block_number: u64,
+	) -> u64 {
+		match RENT_PER_ITEM.with(|v| *v.borrow()) {
+			Some(fee) => u64::from(contract.total_pair_count) * fee,
+			None => <LinearRentCurve as RentCurve<Test>>::fee_per_block(
+				account, contract, free_balance, current_block_number,
+			),
+		}
+	}
+
+	fn effective_storage_size(
+		account: &u64,
+		contract: &AliveContractInfo<Test>,
+		free_balance: &u64,
+		current_block_number: u64,
+	) -> u64 {
+		match RENT_PER_ITEM.with(|v| *v.borrow()) {
+			Some(_) => u64::from(contract.storage_size),
+			None => <LinearRentCurve as RentCurve<Test>>::effective_storage_size(
+				account, contract, free_balance, current_block_number,
+			),
+		}
+	}
+
+	fn free_storage(
+		account: &u64,
+		contract: &AliveContractInfo<Test>,
+		free_balance: &u64,
+		current_block_number: u64,
+	) -> u64 {
+		match RENT_PER_ITEM.with(|v| *v.borrow()) {
+			Some(_) => 0,
+			None => <LinearRentCurve as RentCurve<Test>>::free_storage(
+				account, contract, free_balance, current_block_number,
+			),
+		}
+	}
+
+	fn exemption_deposit(
+		account: &u64,
+		contract: &AliveContractInfo<Test>,
+		free_balance: &u64,
+		current_block_number: u64,
+	) -> u64 {
+		match RENT_PER_ITEM.with(|v| *v.borrow()) {
+			Some(_) => u64::max_value(),
+			None => <LinearRentCurve as RentCurve<Test>>::exemption_deposit(
+				account, contract, free_balance, current_block_number,
+			),
+		}
+	}
+}
+
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub struct Test;
 parameter_types! {
@@ -130,7 +212,7 @@ impl frame_system::Trait for Test {
 	type Event = MetaEvent;
 	type BlockHashCount = BlockHashCount;
 	type MaximumBlockWeight = MaximumBlockWeight;
-	type DbWeight = ();
+	type DbWeight = DbWeight;
 	type BlockExecutionWeight = ();
 	type ExtrinsicBaseWeight = ();
 	type MaximumExtrinsicWeight = MaximumBlockWeight;
@@ -170,6 +252,7 @@ parameter_types! {
 	pub const SurchargeReward: u64 = 150;
 	pub const MaxDepth: u32 = 100;
 	pub const MaxValueSize: u32 = 16_384;
+	pub const MaxReinstrumentWeight: Weight = 100_000_000_000;
 }
 
 parameter_types! {
@@ -190,6 +273,7 @@ impl Trait for Test {
 	type Event = MetaEvent;
 	type TrieIdGenerator = DummyTrieIdGenerator;
 	type RentPayment = ();
+	type RentCurve = TestRentCurve;
 	type SignedClaimHandicap = SignedClaimHandicap;
 	type TombstoneDeposit = TombstoneDeposit;
 	type StorageSizeOffset = StorageSizeOffset;
@@ -198,7 +282,10 @@ impl Trait for Test {
 	type SurchargeReward = SurchargeReward;
 	type MaxDepth = MaxDepth;
 	type MaxValueSize = MaxValueSize;
+	type MaxReinstrumentWeight = MaxReinstrumentWeight;
+	type StripPristineCode = StripPristineCode;
 	type WeightPrice = Self;
+	type WeightInfo = ();
 }
 
 type Balances = pallet_balances::Module<Test>;
@@ -238,11 +325,17 @@ const GAS_LIMIT: Gas = 10_000_000_000;
 
 pub struct ExtBuilder {
 	existential_deposit: u64,
+	strip_pristine_code: bool,
+	rent_per_item: Option<u64>,
+	db_weight: RuntimeDbWeight,
 }
 impl Default for ExtBuilder {
 	fn default() -> Self {
 		Self {
 			existential_deposit: 1,
+			strip_pristine_code: false,
+			rent_per_item: None,
+			db_weight: RuntimeDbWeight { read: 0, write: 0 },
 		}
 	}
 }
@@ -251,8 +344,23 @@ impl ExtBuilder {
 		self.existential_deposit = existential_deposit;
 		self
 	}
+	pub fn strip_pristine_code(mut self, strip_pristine_code: bool) -> Self {
+		self.strip_pristine_code = strip_pristine_code;
+		self
+	}
+	pub fn rent_per_item(mut self, rent_per_item: u64) -> Self {
+		self.rent_per_item = Some(rent_per_item);
+		self
+	}
+	pub fn db_weight(mut self, db_weight: RuntimeDbWeight) -> Self {
+		self.db_weight = db_weight;
+		self
+	}
 	pub fn set_associated_consts(&self) {
 		EXISTENTIAL_DEPOSIT.with(|v| *v.borrow_mut() = self.existential_deposit);
+		STRIP_PRISTINE_CODE.with(|v| *v.borrow_mut() = self.strip_pristine_code);
+		RENT_PER_ITEM.with(|v| *v.borrow_mut() = self.rent_per_item);
+		DB_WEIGHT.with(|v| *v.borrow_mut() = self.db_weight);
 	}
 	pub fn build(self) -> sp_io::TestExternalities {
 		self.set_associated_consts();
@@ -267,6 +375,7 @@ impl ExtBuilder {
 			},
 		}.assimilate_storage(&mut t).unwrap();
 		let mut ext = sp_io::TestExternalities::new(t);
+		ext.register_extension(KeystoreExt(KeyStore::new()));
 		ext.execute_with(|| System::set_block_number(1));
 		ext
 	}
@@ -288,6 +397,23 @@ where
 	Ok((wasm_binary, code_hash))
 }
 
+/// Returns the number of bytes the custom sections of `wasm` occupy. They are always stripped
+/// from the instrumented code before it is stored.
+fn custom_sections_len(wasm: &[u8]) -> u32 {
+	use parity_wasm::elements::{self, Section};
+
+	let module = elements::deserialize_buffer::<elements::Module>(wasm).unwrap();
+	module
+		.sections()
+		.iter()
+		.filter(|section| match section {
+			Section::Custom(_) | Section::Name(_) | Section::Reloc(_) => true,
+			_ => false,
+		})
+		.map(|section| elements::serialize(section.clone()).unwrap().len() as u32)
+		.sum()
+}
+
 // Perform a call to a plain account.
 // The actual transfer fails because we can only call contracts.
 // Then we check that only the base costs are returned as actual costs.
@@ -302,7 +428,7 @@ fn calling_plain_account_fails() {
 				DispatchErrorWithPostInfo {
 					error: Error::<Test>::NotCallable.into(),
 					post_info: PostDispatchInfo {
-						actual_weight: Some(67500000),
+						actual_weight: Some(67500000 + <() as WeightInfo>::call()),
 						pays_fee: Default::default(),
 					},
 				}
@@ -389,6 +515,7 @@ fn account_removal_does_not_remove_storage() {
 #[test]
 fn instantiate_and_call_and_deposit_event() {
 	let (wasm, code_hash) = compile_module::<Test>("return_from_start_fn").unwrap();
+	let bytes_saved = custom_sections_len(&wasm);
 
 	ExtBuilder::default()
 		.existential_deposit(100)
@@ -421,7 +548,7 @@ fn instantiate_and_call_and_deposit_event() {
 				},
 				EventRecord {
 					phase: Phase::Initialization,
-					event: MetaEvent::contracts(RawEvent::CodeStored(code_hash.into())),
+					event: MetaEvent::contracts(RawEvent::CodeStored(code_hash.into(), bytes_saved)),
 					topics: vec![],
 				},
 				EventRecord {
@@ -513,6 +640,7 @@ fn test_set_rent_code_and_hash() {
 	assert_eq!(&encoded[..], &hex!("00000300000000000000C8")[..]);
 
 	let (wasm, code_hash) = compile_module::<Test>("set_rent").unwrap();
+	let bytes_saved = custom_sections_len(&wasm);
 
 	ExtBuilder::default()
 		.existential_deposit(50)
@@ -536,7 +664,7 @@ fn test_set_rent_code_and_hash() {
 				},
 				EventRecord {
 					phase: Phase::Initialization,
-					event: MetaEvent::contracts(RawEvent::CodeStored(code_hash.into())),
+					event: MetaEvent::contracts(RawEvent::CodeStored(code_hash.into(), bytes_saved)),
 					topics: vec![],
 				},
 			]);
@@ -737,6 +865,142 @@ fn deduct_blocks() {
 		});
 }
 
+#[test]
+fn rent_status_has_no_side_effects() {
+	let (wasm, code_hash) = compile_module::<Test>("set_rent").unwrap();
+
+	ExtBuilder::default()
+		.existential_deposit(50)
+		.build()
+		.execute_with(|| {
+			// Create
+			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+			assert_ok!(Contracts::instantiate(
+				Origin::signed(ALICE),
+				30_000,
+				GAS_LIMIT, code_hash.into(),
+				<Test as pallet_balances::Trait>::Balance::from(1_000u32).encode() // rent allowance
+			));
+
+			// Advance 4 blocks
+			initialize_block(5);
+
+			let rent = (8 + 4 - 3) // storage size = size_offset + deploy_set_storage - deposit_offset
+				* 4 // rent byte price
+				* 4; // blocks to rent
+			let fee_per_block = (8 + 4 - 2) * 4;
+			let status = Contracts::rent_status(BOB).unwrap();
+			assert_eq!(status.fee_per_block, fee_per_block);
+			assert_eq!(status.effective_storage_size, 8 + 4 - 2);
+			assert_eq!(status.free_storage, 2);
+			assert_eq!(status.rent_allowance, 1_000 - rent);
+			assert_eq!(status.rent_budget, 1_000 - rent);
+			assert_eq!(status.eviction_at, Some(5 + (1_000 - rent) / fee_per_block));
+			assert_eq!(status.exemption_deposit, (8 + 4) * 10_000 - (30_000 - rent));
+
+			// Nothing was charged
+			let bob_contract = ContractInfoOf::<Test>::get(BOB).unwrap().get_alive().unwrap();
+			assert_eq!(bob_contract.rent_allowance, 1_000);
+			assert_eq!(bob_contract.deduct_block, 1);
+			assert_eq!(Balances::free_balance(BOB), 30_000);
+
+			// The status matches what is actually charged
+			assert_ok!(Contracts::call(Origin::signed(ALICE), BOB, 0, GAS_LIMIT, call::null()));
+			assert_eq!(Contracts::rent_status(BOB).unwrap(), status);
+
+			assert_eq!(Contracts::rent_status(CHARLIE), Err(ContractAccessError::DoesntExist));
+		});
+}
+
+#[test]
+fn rent_status_uses_rent_curve() {
+	let (wasm, code_hash) = compile_module::<Test>("set_rent").unwrap();
+
+	ExtBuilder::default()
+		.existential_deposit(50)
+		.rent_per_item(7)
+		.build()
+		.execute_with(|| {
+			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+			assert_ok!(Contracts::instantiate(
+				Origin::signed(ALICE),
+				30_000,
+				GAS_LIMIT, code_hash.into(),
+				<Test as pallet_balances::Trait>::Balance::from(1_000u32).encode() // rent allowance
+			));
+
+			// Advance 4 blocks
+			initialize_block(5);
+
+			// The constructor stores a single item of 4 bytes.
+			let status = Contracts::rent_status(BOB).unwrap();
+			assert_eq!(status.fee_per_block, 7);
+			assert_eq!(status.effective_storage_size, 4);
+			assert_eq!(status.free_storage, 0);
+			assert_eq!(status.exemption_deposit, u64::max_value());
+			assert_eq!(status.rent_allowance, 1_000 - 4 * 7);
+			assert_eq!(status.eviction_at, Some(5 + (1_000 - 4 * 7) / 7));
+
+			// The status matches what is actually charged
+			assert_ok!(Contracts::call(Origin::signed(ALICE), BOB, 0, GAS_LIMIT, call::null()));
+			assert_eq!(Balances::free_balance(BOB), 30_000 - 4 * 7);
+			assert_eq!(Contracts::rent_status(BOB).unwrap(), status);
+		});
+}
+
+#[test]
+fn deposit_rent_works() {
+	let (wasm, code_hash) = compile_module::<Test>("set_rent").unwrap();
+
+	ExtBuilder::default()
+		.existential_deposit(50)
+		.build()
+		.execute_with(|| {
+			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+			let _ = Balances::deposit_creating(&CHARLIE, 1_000_000);
+			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+			assert_ok!(Contracts::instantiate(
+				Origin::signed(ALICE),
+				30_000,
+				GAS_LIMIT, code_hash.into(),
+				<Test as pallet_balances::Trait>::Balance::from(1_000u32).encode() // rent allowance
+			));
+
+			// Only funds are deposited
+			assert_ok!(Contracts::deposit_rent(Origin::signed(CHARLIE), BOB, 5_000, false));
+			let bob_contract = ContractInfoOf::<Test>::get(BOB).unwrap().get_alive().unwrap();
+			assert_eq!(bob_contract.rent_allowance, 1_000);
+			assert_eq!(Balances::free_balance(BOB), 35_000);
+			assert_eq!(Balances::free_balance(CHARLIE), 995_000);
+
+			// Advance 4 blocks
+			initialize_block(5);
+
+			// Outstanding rent is collected before the allowance is raised
+			assert_ok!(Contracts::deposit_rent(Origin::signed(CHARLIE), BOB, 5_000, true));
+			let rent = (8 + 4 - 3) // storage size = size_offset + deploy_set_storage - deposit_offset
+				* 4 // rent byte price
+				* 4; // blocks to rent
+			let bob_contract = ContractInfoOf::<Test>::get(BOB).unwrap().get_alive().unwrap();
+			assert_eq!(bob_contract.rent_allowance, 1_000 - rent + 5_000);
+			assert_eq!(bob_contract.deduct_block, 5);
+			assert_eq!(Balances::free_balance(BOB), 40_000 - rent);
+			assert_eq!(Balances::free_balance(CHARLIE), 990_000);
+			assert_eq!(
+				System::events().last().unwrap().event,
+				MetaEvent::contracts(RawEvent::RentDeposited(CHARLIE, BOB, 5_000, 1_000 - rent + 5_000)),
+			);
+
+			// Plain accounts can't receive rent deposits
+			assert_noop!(
+				Contracts::deposit_rent(Origin::signed(CHARLIE), DJANGO, 5_000, true),
+				Error::<Test>::NotCallable,
+			);
+		});
+}
+
 #[test]
 fn call_contract_removals() {
 	removals(|| {
@@ -1077,6 +1341,8 @@ fn restoration_success() {
 fn restoration(test_different_storage: bool, test_restore_to_with_dirty_storage: bool) {
 	let (set_rent_wasm, set_rent_code_hash) = compile_module::<Test>("set_rent").unwrap();
 	let (restoration_wasm, restoration_code_hash) = compile_module::<Test>("restoration").unwrap();
+	let set_rent_bytes_saved = custom_sections_len(&set_rent_wasm);
+	let restoration_bytes_saved = custom_sections_len(&restoration_wasm);
 
 	ExtBuilder::default()
 		.existential_deposit(50)
@@ -1101,12 +1367,18 @@ fn restoration(test_different_storage: bool, test_restore_to_with_dirty_storage:
 				},
 				EventRecord {
 					phase: Phase::Initialization,
-					event: MetaEvent::contracts(RawEvent::CodeStored(restoration_code_hash.into())),
+					event: MetaEvent::contracts(RawEvent::CodeStored(
+						restoration_code_hash.into(),
+						restoration_bytes_saved,
+					)),
 					topics: vec![],
 				},
 				EventRecord {
 					phase: Phase::Initialization,
-					event: MetaEvent::contracts(RawEvent::CodeStored(set_rent_code_hash.into())),
+					event: MetaEvent::contracts(RawEvent::CodeStored(
+						set_rent_code_hash.into(),
+						set_rent_bytes_saved,
+					)),
 					topics: vec![],
 				},
 			]);
@@ -1281,6 +1553,271 @@ fn restoration(test_different_storage: bool, test_restore_to_with_dirty_storage:
 		});
 }
 
+#[test]
+fn tombstones_of_old_layout_are_migrated_on_runtime_upgrade() {
+	use crate::{ContractInfoV0, Releases, StorageVersion};
+
+	let code_hash = H256::repeat_byte(1);
+	let tombstone =
+		crate::TombstoneContractInfo::<Test>::new(&[2; 32], code_hash, StorageMode::Hashed);
+	let alive = RawAliveContractInfo {
+		trie_id: vec![1; 32],
+		storage_size: 0,
+		empty_pair_count: 0,
+		total_pair_count: 0,
+		code_hash,
+		rent_allowance: 0,
+		deduct_block: 0,
+		last_write: None,
+	};
+
+	ExtBuilder::default().build().execute_with(|| {
+		// Before the storage root was kept a tombstone only consisted of its hash.
+		StorageVersion::put(Releases::V2_0_0);
+		frame_support::storage::unhashed::put_raw(
+			&ContractInfoOf::<Test>::hashed_key_for(BOB),
+			&ContractInfoV0::<Test>::Tombstone(tombstone.0).encode(),
+		);
+		frame_support::storage::unhashed::put_raw(
+			&ContractInfoOf::<Test>::hashed_key_for(CHARLIE),
+			&ContractInfoV0::<Test>::Alive(alive.clone()).encode(),
+		);
+
+		Contracts::on_runtime_upgrade();
+		let migrated = ContractInfoOf::<Test>::get(BOB).unwrap().get_tombstone().unwrap();
+		assert_eq!(migrated, tombstone);
+		assert!(migrated.storage_root().is_empty());
+		assert_eq!(migrated.storage_mode(), StorageMode::Hashed);
+		assert_eq!(ContractInfoOf::<Test>::get(CHARLIE).unwrap().get_alive(), Some(alive));
+		assert_eq!(StorageVersion::get(), Releases::V3_0_0);
+
+		// The migration only runs once.
+		Contracts::on_runtime_upgrade();
+		assert_eq!(ContractInfoOf::<Test>::get(BOB).unwrap().get_tombstone(), Some(migrated));
+	});
+}
+
+#[test]
+fn restore_contract_from_storage_items() {
+	let (wasm, code_hash) = compile_module::<Test>("set_rent").unwrap();
+
+	ExtBuilder::default()
+		.existential_deposit(50)
+		.build()
+		.execute_with(|| {
+			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+
+			// The rent allowance is set to 0 so that `BOB` is evicted on the next touch.
+			assert_ok!(Contracts::instantiate(
+				Origin::signed(ALICE),
+				30_000,
+				GAS_LIMIT,
+				code_hash.into(),
+				<Test as pallet_balances::Trait>::Balance::from(0u32).encode()
+			));
+			initialize_block(5);
+			assert_err_ignore_postinfo!(
+				Contracts::call(Origin::signed(ALICE), BOB, 0, GAS_LIMIT, call::null()),
+				Error::<Test>::NotCallable
+			);
+			assert!(ContractInfoOf::<Test>::get(BOB).unwrap().get_tombstone().is_some());
+
+			// The constructor stores `[0x28, 0, 0, 0]` under the key `[0x28, 0, .., 0]`.
+			let mut key = [0u8; 32];
+			key[0] = 0x28;
+			let value = vec![0x28, 0, 0, 0];
+
+			// Storage that doesn't match the tombstone is rejected without side effects.
+			let account_counter = super::AccountCounter::get();
+			assert_err!(
+				Contracts::restore_contract(
+					Origin::signed(CHARLIE), BOB, code_hash.into(), 50, vec![(key, vec![1, 2, 3])],
+				),
+				Error::<Test>::InvalidTombstone,
+			);
+			assert!(ContractInfoOf::<Test>::get(BOB).unwrap().get_tombstone().is_some());
+			assert_eq!(super::AccountCounter::get(), account_counter);
+
+			// Only tombstones can be restored.
+			assert_err!(
+				Contracts::restore_contract(
+					Origin::signed(CHARLIE), DJANGO, code_hash.into(), 50, vec![(key, value.clone())],
+				),
+				Error::<Test>::InvalidDestinationContract,
+			);
+
+			assert_ok!(Contracts::restore_contract(
+				Origin::signed(CHARLIE), BOB, code_hash.into(), 50, vec![(key, value.clone())],
+			));
+			let bob_contract = ContractInfoOf::<Test>::get(BOB).unwrap().get_alive().unwrap();
+			assert_eq!(bob_contract.rent_allowance, 50);
+			assert_eq!(bob_contract.storage_size, 4);
+			assert_eq!(bob_contract.total_pair_count, 1);
+			assert_eq!(bob_contract.deduct_block, 5);
+			assert_eq!(Contracts::get_storage(BOB, key), Ok(Some(value)));
+			assert_eq!(
+				System::events().last().unwrap().event,
+				MetaEvent::contracts(RawEvent::Restored(CHARLIE, BOB, code_hash.into(), 50)),
+			);
+		});
+}
+
+#[test]
+fn restore_contract_with_proof_restores_subset_of_storage() {
+	let (wasm, code_hash) = compile_module::<Test>("set_rent").unwrap();
+
+	ExtBuilder::default()
+		.existential_deposit(50)
+		.build()
+		.execute_with(|| {
+			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+
+			// The rent allowance is set to 0 so that `BOB` is evicted on the next touch.
+			assert_ok!(Contracts::instantiate(
+				Origin::signed(ALICE),
+				30_000,
+				GAS_LIMIT,
+				code_hash.into(),
+				<Test as pallet_balances::Trait>::Balance::from(0u32).encode()
+			));
+			assert_ok!(Contracts::call(
+				Origin::signed(ALICE),
+				BOB, 0, GAS_LIMIT,
+				call::set_storage_4_byte())
+			);
+			initialize_block(5);
+			assert_err_ignore_postinfo!(
+				Contracts::call(Origin::signed(ALICE), BOB, 0, GAS_LIMIT, call::null()),
+				Error::<Test>::NotCallable
+			);
+			let tombstone = ContractInfoOf::<Test>::get(BOB).unwrap().get_tombstone().unwrap();
+
+			// The constructor stores `[0x28, 0, 0, 0]` under the key `[0x28, 0, .., 0]` and
+			// the call stores the same value under the key `[0, .., 0]`.
+			let mut constructor_key = [0u8; 32];
+			constructor_key[0] = 0x28;
+			let call_key = [0u8; 32];
+			let value = vec![0x28, 0, 0, 0];
+
+			// Rebuild the storage of `BOB` off-chain and prove only one of its items.
+			let mut db = MemoryDB::<BlakeTwo256>::default();
+			let mut root = Default::default();
+			{
+				let mut trie = TrieDBMut::<Layout<BlakeTwo256>>::new(&mut db, &mut root);
+				trie.insert(&blake2_256(&constructor_key), &value).unwrap();
+				trie.insert(&blake2_256(&call_key), &value).unwrap();
+			}
+			assert_eq!(tombstone.storage_root(), root.as_ref());
+			let proof = generate_trie_proof::<Layout<BlakeTwo256>, _, _, _>(
+				&db,
+				root,
+				&[blake2_256(&constructor_key)],
+			).unwrap();
+
+			// Only root can restore a subset of the storage.
+			assert_err!(
+				Contracts::restore_contract_with_proof(
+					Origin::signed(CHARLIE), BOB, code_hash.into(), 50,
+					vec![(constructor_key, value.clone())], proof.clone(),
+				),
+				sp_runtime::DispatchError::BadOrigin,
+			);
+
+			// Items that are not covered by the proof are rejected.
+			assert_err!(
+				Contracts::restore_contract_with_proof(
+					Origin::root(), BOB, code_hash.into(), 50,
+					vec![(constructor_key, vec![1, 2, 3, 4])], proof.clone(),
+				),
+				Error::<Test>::InvalidTombstone,
+			);
+			assert!(ContractInfoOf::<Test>::get(BOB).unwrap().get_tombstone().is_some());
+
+			assert_ok!(Contracts::restore_contract_with_proof(
+				Origin::root(), BOB, code_hash.into(), 50,
+				vec![(constructor_key, value.clone())], proof,
+			));
+			let bob_contract = ContractInfoOf::<Test>::get(BOB).unwrap().get_alive().unwrap();
+			assert_eq!(bob_contract.rent_allowance, 50);
+			assert_eq!(bob_contract.storage_size, 4);
+			assert_eq!(bob_contract.total_pair_count, 1);
+			assert_eq!(Contracts::get_storage(BOB, constructor_key), Ok(Some(value)));
+			assert_eq!(Contracts::get_storage(BOB, call_key), Ok(None));
+		});
+}
+
+#[test]
+fn unhashed_contract_can_be_restored() {
+	let (wasm, code_hash) = compile_module::<Test>("set_rent").unwrap();
+
+	// The constructor stores `[0x28, 0, 0, 0]` under the key `[0x28, 0, .., 0]`.
+	let mut key = [0u8; 32];
+	key[0] = 0x28;
+	let value = vec![0x28, 0, 0, 0];
+
+	let evict_unhashed_bob = || {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm.clone()));
+
+		// The rent allowance is set to 0 so that `BOB` is evicted on the next touch.
+		assert_ok!(Contracts::instantiate_with_storage_mode(
+			Origin::signed(ALICE),
+			30_000,
+			GAS_LIMIT,
+			code_hash.into(),
+			<Test as pallet_balances::Trait>::Balance::from(0u32).encode(),
+			StorageMode::Unhashed,
+		));
+		initialize_block(5);
+		assert_err_ignore_postinfo!(
+			Contracts::call(Origin::signed(ALICE), BOB, 0, GAS_LIMIT, call::null()),
+			Error::<Test>::NotCallable
+		);
+		let tombstone = ContractInfoOf::<Test>::get(BOB).unwrap().get_tombstone().unwrap();
+		assert_eq!(tombstone.storage_mode(), StorageMode::Unhashed);
+		tombstone
+	};
+	let assert_restored = || {
+		let trie_id = ContractInfoOf::<Test>::get(BOB).unwrap().get_alive().unwrap().trie_id;
+		assert_eq!(StorageModeOf::get(&trie_id), StorageMode::Unhashed);
+		assert_eq!(
+			crate::storage::read_contract_storage(&trie_id, StorageMode::Unhashed, &key),
+			Some(value.clone()),
+		);
+		assert_eq!(Contracts::get_storage(BOB, key), Ok(Some(value.clone())));
+	};
+
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		evict_unhashed_bob();
+		assert_ok!(Contracts::restore_contract(
+			Origin::signed(CHARLIE), BOB, code_hash.into(), 50, vec![(key, value.clone())],
+		));
+		assert_restored();
+	});
+
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let tombstone = evict_unhashed_bob();
+
+		// The storage root was computed over the keys as they are.
+		let mut db = MemoryDB::<BlakeTwo256>::default();
+		let mut root = Default::default();
+		{
+			let mut trie = TrieDBMut::<Layout<BlakeTwo256>>::new(&mut db, &mut root);
+			trie.insert(&key, &value).unwrap();
+		}
+		assert_eq!(tombstone.storage_root(), root.as_ref());
+		let proof = generate_trie_proof::<Layout<BlakeTwo256>, _, _, _>(&db, root, &[key])
+			.unwrap();
+
+		assert_ok!(Contracts::restore_contract_with_proof(
+			Origin::root(), BOB, code_hash.into(), 50, vec![(key, value.clone())], proof,
+		));
+		assert_restored();
+	});
+}
+
 #[test]
 fn storage_max_value_limit() {
 	let (wasm, code_hash) = compile_module::<Test>("storage_size").unwrap();
@@ -1616,31 +2153,93 @@ fn crypto_hashes() {
 }
 
 #[test]
-fn transfer_return_code() {
-	let (wasm, code_hash) = compile_module::<Test>("transfer_return_code").unwrap();
-	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
-		let subsistence = Config::<Test>::subsistence_threshold_uncached();
-		let _ = Balances::deposit_creating(&ALICE, 10 * subsistence);
-		assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+fn signature_verification() {
+	let (wasm, code_hash) = compile_module::<Test>("signature_verify").unwrap();
 
-		assert_ok!(
-			Contracts::instantiate(
+	ExtBuilder::default()
+		.existential_deposit(50)
+		.build()
+		.execute_with(|| {
+			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+			assert_ok!(Contracts::instantiate(
 				Origin::signed(ALICE),
-				subsistence,
+				100_000,
 				GAS_LIMIT,
 				code_hash.into(),
 				vec![],
-			),
-		);
+			));
 
-		// Contract has only the minimal balance so any transfer will return BelowSubsistence.
-		let result = Contracts::bare_call(
-			ALICE,
-			BOB,
-			0,
-			GAS_LIMIT,
-			vec![],
-		).0.unwrap();
+			// Calls the contract which verifies the signature with the chosen scheme.
+			let verify = |scheme: u8, signature: &[u8], pub_key: &[u8], message: &[u8]| {
+				let input = [&[scheme][..], signature, pub_key, message].concat();
+				let result = <Module<Test>>::bare_call(
+					ALICE,
+					BOB,
+					0,
+					GAS_LIMIT,
+					input,
+				).0.unwrap();
+				assert!(result.is_success());
+				result.data
+			};
+			let message = b"Hello world";
+			let success = (RuntimeReturnCode::Success as u32).encode();
+
+			let sr25519_pair = sr25519::Pair::from_seed(&[1; 32]);
+			let signature = sr25519_pair.sign(message);
+			let pub_key = sr25519_pair.public();
+			assert_eq!(verify(0, signature.as_ref(), pub_key.as_ref(), message), success);
+			assert_eq!(
+				verify(0, signature.as_ref(), pub_key.as_ref(), b"Hello moon"),
+				(RuntimeReturnCode::Sr25519VerifyFailed as u32).encode(),
+			);
+
+			let ed25519_pair = ed25519::Pair::from_seed(&[2; 32]);
+			let signature = ed25519_pair.sign(message);
+			let pub_key = ed25519_pair.public();
+			assert_eq!(verify(1, signature.as_ref(), pub_key.as_ref(), message), success);
+			assert_eq!(
+				verify(1, signature.as_ref(), pub_key.as_ref(), b"Hello moon"),
+				(RuntimeReturnCode::Ed25519VerifyFailed as u32).encode(),
+			);
+
+			// A signature is only valid for the scheme it was created with.
+			let signature = sr25519_pair.sign(message);
+			let pub_key = sr25519_pair.public();
+			assert_eq!(
+				verify(1, signature.as_ref(), pub_key.as_ref(), message),
+				(RuntimeReturnCode::Ed25519VerifyFailed as u32).encode(),
+			);
+		})
+}
+
+#[test]
+fn transfer_return_code() {
+	let (wasm, code_hash) = compile_module::<Test>("transfer_return_code").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let subsistence = Config::<Test>::subsistence_threshold_uncached();
+		let _ = Balances::deposit_creating(&ALICE, 10 * subsistence);
+		assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+
+		assert_ok!(
+			Contracts::instantiate(
+				Origin::signed(ALICE),
+				subsistence,
+				GAS_LIMIT,
+				code_hash.into(),
+				vec![],
+			),
+		);
+
+		// Contract has only the minimal balance so any transfer will return BelowSubsistence.
+		let result = Contracts::bare_call(
+			ALICE,
+			BOB,
+			0,
+			GAS_LIMIT,
+			vec![],
+		).0.unwrap();
 		assert_return_code!(result, RuntimeReturnCode::BelowSubsistenceThreshold);
 
 		// Contract has enough total balance in order to not go below the subsistence
@@ -1748,6 +2347,141 @@ fn call_return_code() {
 	});
 }
 
+#[test]
+fn reentrance_is_denied_unless_allowed() {
+	let (wasm, code_hash) = compile_module::<Test>("reentrance").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let subsistence = Config::<Test>::subsistence_threshold_uncached();
+		let _ = Balances::deposit_creating(&ALICE, 10 * subsistence);
+		let _ = Balances::deposit_creating(&CHARLIE, 10 * subsistence);
+		assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+
+		// Both BOB and DJANGO run the same code.
+		assert_ok!(Contracts::instantiate(
+			Origin::signed(ALICE),
+			subsistence,
+			GAS_LIMIT,
+			code_hash.into(),
+			vec![],
+		));
+		assert_ok!(Contracts::instantiate(
+			Origin::signed(CHARLIE),
+			subsistence,
+			GAS_LIMIT,
+			code_hash.into(),
+			vec![],
+		));
+
+		// BOB calls DJANGO with `flags` which in turn tries to call back into BOB.
+		let call = |flags: u32| {
+			let input = (DJANGO, flags, BOB, 0u32).encode();
+			Contracts::bare_call(ALICE, BOB, 0, GAS_LIMIT, input).0.unwrap()
+		};
+
+		// Reentrance is denied by default.
+		assert_return_code!(call(0), RuntimeReturnCode::ReentranceDenied);
+
+		// BOB can allow DJANGO to call back.
+		let result = call(CallFlags::ALLOW_REENTRY.bits());
+		assert!(result.is_success());
+		assert!(result.data.is_empty());
+
+		// A contract that calls itself is denied unless it allows reentry.
+		let input = (BOB, 0u32).encode();
+		let result = Contracts::bare_call(ALICE, BOB, 0, GAS_LIMIT, input).0.unwrap();
+		assert_return_code!(result, RuntimeReturnCode::ReentranceDenied);
+
+		let input = (BOB, CallFlags::ALLOW_REENTRY.bits()).encode();
+		let result = Contracts::bare_call(ALICE, BOB, 0, GAS_LIMIT, input).0.unwrap();
+		assert!(result.is_success());
+		assert!(result.data.is_empty());
+	});
+}
+
+#[test]
+fn read_only_calls_deny_state_changes() {
+	let (caller_code, caller_hash) = compile_module::<Test>("reentrance").unwrap();
+	let (callee_code, callee_hash) = compile_module::<Test>("transfer_return_code").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let subsistence = Config::<Test>::subsistence_threshold_uncached();
+		let _ = Balances::deposit_creating(&ALICE, 10 * subsistence);
+		let _ = Balances::deposit_creating(&CHARLIE, 10 * subsistence);
+		assert_ok!(Contracts::put_code(Origin::signed(ALICE), caller_code));
+		assert_ok!(Contracts::put_code(Origin::signed(ALICE), callee_code));
+
+		// BOB forwards calls to DJANGO which transfers some balance.
+		assert_ok!(Contracts::instantiate(
+			Origin::signed(ALICE),
+			subsistence,
+			GAS_LIMIT,
+			caller_hash.into(),
+			vec![],
+		));
+		assert_ok!(Contracts::instantiate(
+			Origin::signed(CHARLIE),
+			5 * subsistence,
+			GAS_LIMIT,
+			callee_hash.into(),
+			vec![],
+		));
+
+		// DJANGO is allowed to transfer when called normally.
+		let input = (DJANGO, 0u32).encode();
+		let result = Contracts::bare_call(ALICE, BOB, 0, GAS_LIMIT, input).0.unwrap();
+		assert_return_code!(result, RuntimeReturnCode::Success);
+
+		// BOB calls DJANGO read-only.
+		let input = (DJANGO, CallFlags::READ_ONLY.bits()).encode();
+		let result = Contracts::bare_call(ALICE, BOB, 0, GAS_LIMIT, input).0.unwrap();
+		assert_return_code!(result, RuntimeReturnCode::StateChangeDenied);
+
+		// DJANGO is called read-only directly.
+		let result = Contracts::bare_call_read_only(ALICE, DJANGO, GAS_LIMIT, vec![]).0.unwrap();
+		assert_return_code!(result, RuntimeReturnCode::StateChangeDenied);
+
+		// Read-only applies to the whole call tree even if BOB doesn't ask for it.
+		let input = (DJANGO, 0u32).encode();
+		let result = Contracts::bare_call_read_only(ALICE, BOB, GAS_LIMIT, input).0.unwrap();
+		assert_return_code!(result, RuntimeReturnCode::StateChangeDenied);
+		// Only the first call transferred balance to the zero account.
+		assert_eq!(Balances::free_balance(&0), 100);
+	});
+}
+
+#[test]
+fn read_only_calls_collect_no_rent() {
+	let (wasm, code_hash) = compile_module::<Test>("set_rent").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+		assert_ok!(Contracts::instantiate(
+			Origin::signed(ALICE),
+			100,
+			GAS_LIMIT,
+			code_hash.into(),
+			<Test as pallet_balances::Trait>::Balance::from(1_000u32).encode(),
+		));
+		let info = ContractInfoOf::<Test>::get(BOB).unwrap().get_alive().unwrap();
+		let balance = Balances::free_balance(BOB);
+
+		// The contract can't pay its rent anymore.
+		initialize_block(10);
+
+		// A read-only call neither collects rent nor evicts the contract.
+		assert_ok!(Contracts::bare_call_read_only(ALICE, BOB, GAS_LIMIT, call::null()).0);
+		assert_eq!(ContractInfoOf::<Test>::get(BOB).unwrap().get_alive(), Some(info));
+		assert_eq!(Balances::free_balance(BOB), balance);
+		assert!(System::events().is_empty());
+
+		// A regular call does.
+		assert_err_ignore_postinfo!(
+			Contracts::call(Origin::signed(ALICE), BOB, 0, GAS_LIMIT, call::null()),
+			Error::<Test>::NotCallable,
+		);
+		assert!(ContractInfoOf::<Test>::get(BOB).unwrap().get_tombstone().is_some());
+	});
+}
+
 #[test]
 fn instantiate_return_code() {
 	let (caller_code, caller_hash) = compile_module::<Test>("instantiate_return_code").unwrap();
@@ -1827,3 +2561,1016 @@ fn instantiate_return_code() {
 
 	});
 }
+
+/// Bumps the version of the current schedule without changing any costs.
+fn bump_schedule_version() {
+	let schedule = Contracts::current_schedule();
+	assert_ok!(Contracts::update_schedule(Origin::root(), Schedule {
+		version: schedule.version + 1,
+		.. schedule
+	}));
+}
+
+#[test]
+fn reinstrumentation_is_charged_to_the_caller() {
+	let (wasm, code_hash) = compile_module::<Test>("return_with_data").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let subsistence = super::Config::<Test>::subsistence_threshold_uncached();
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		let code_len = wasm.len() as u32;
+		assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+		assert_ok!(Contracts::instantiate(
+			Origin::signed(ALICE),
+			subsistence,
+			GAS_LIMIT,
+			code_hash.into(),
+			vec![0; 4],
+		));
+
+		let (result, gas_up_to_date) = Contracts::bare_call(ALICE, BOB, 0, GAS_LIMIT, vec![0; 4]);
+		assert_ok!(result);
+
+		bump_schedule_version();
+		let (result, gas_outdated) = Contracts::bare_call(ALICE, BOB, 0, GAS_LIMIT, vec![0; 4]);
+		assert_ok!(result);
+		assert_eq!(
+			gas_outdated,
+			gas_up_to_date + <() as WeightInfo>::reinstrument(crate::kilobytes(code_len)),
+		);
+
+		// The code was written back to the cache and is not charged again.
+		let (result, gas_cached) = Contracts::bare_call(ALICE, BOB, 0, GAS_LIMIT, vec![0; 4]);
+		assert_ok!(result);
+		assert_eq!(gas_cached, gas_up_to_date);
+
+		// Running out of gas while re-instrumenting fails the call.
+		bump_schedule_version();
+		assert_err_ignore_postinfo!(
+			Contracts::call(
+				Origin::signed(ALICE),
+				BOB,
+				0,
+				gas_up_to_date + <() as WeightInfo>::reinstrument(crate::kilobytes(code_len)) - 1,
+				vec![0; 4],
+			),
+			Error::<Test>::OutOfGas,
+		);
+	});
+}
+
+#[test]
+fn read_only_calls_do_not_update_the_code_cache() {
+	let (wasm, code_hash) = compile_module::<Test>("return_with_data").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let subsistence = super::Config::<Test>::subsistence_threshold_uncached();
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		let code_len = wasm.len() as u32;
+		assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+		assert_ok!(Contracts::instantiate(
+			Origin::signed(ALICE),
+			subsistence,
+			GAS_LIMIT,
+			code_hash.into(),
+			vec![0; 4],
+		));
+
+		let (result, gas_up_to_date) =
+			Contracts::bare_call_read_only(ALICE, BOB, GAS_LIMIT, vec![0; 4]);
+		assert_ok!(result);
+
+		// The outdated code is re-instrumented in memory and charged on every read-only call.
+		bump_schedule_version();
+		let cached_code = crate::CodeStorage::<Test>::get(&code_hash).encode();
+		for _ in 0..2 {
+			let (result, gas_outdated) =
+				Contracts::bare_call_read_only(ALICE, BOB, GAS_LIMIT, vec![0; 4]);
+			assert_ok!(result);
+			assert_eq!(
+				gas_outdated,
+				gas_up_to_date + <() as WeightInfo>::reinstrument(crate::kilobytes(code_len)),
+			);
+			assert_eq!(crate::CodeStorage::<Test>::get(&code_hash).encode(), cached_code);
+		}
+
+		// A regular call writes the re-instrumented code back to the cache.
+		assert_ok!(Contracts::bare_call(ALICE, BOB, 0, GAS_LIMIT, vec![0; 4]).0);
+		assert_ne!(crate::CodeStorage::<Test>::get(&code_hash).encode(), cached_code);
+		let (result, gas_cached) =
+			Contracts::bare_call_read_only(ALICE, BOB, GAS_LIMIT, vec![0; 4]);
+		assert_ok!(result);
+		assert_eq!(gas_cached, gas_up_to_date);
+	});
+}
+
+#[test]
+fn reinstrumentation_of_large_code_is_charged_in_full() {
+	// Padding the code to a size which is not a multiple of a kilobyte makes sure that a
+	// started kilobyte is charged. Importing `seal_println` makes re-instrumentation
+	// fail once it is disabled by the schedule.
+	let (wasm, _) = compile_module::<Test>("return_with_data").unwrap();
+	let wasm = with_metadata(&wasm, &[0; 2000]);
+	let code_hash = <Test as frame_system::Trait>::Hashing::hash(&wasm);
+	let failing_wasm = with_metadata(&wat::parse_str(r#"
+		(module
+			(import "seal0" "seal_println" (func $seal_println (param i32 i32)))
+			(import "env" "memory" (memory 1 1))
+			(func (export "call"))
+			(func (export "deploy"))
+		)
+	"#).unwrap(), &[0; 2000]);
+	let db_weight = RuntimeDbWeight { read: 1_000, write: 10_000 };
+	ExtBuilder::default()
+		.existential_deposit(50)
+		.db_weight(db_weight)
+		.build()
+		.execute_with(|| {
+			let subsistence = super::Config::<Test>::subsistence_threshold_uncached();
+			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+			let code_len = wasm.len() as u32;
+			let failing_code_len = failing_wasm.len() as u32;
+			assert!(code_len > 1024 && code_len % 1024 != 0);
+			assert!(failing_code_len > 1024 && failing_code_len % 1024 != 0);
+			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+			assert_ok!(Contracts::put_code(Origin::signed(ALICE), failing_wasm));
+			assert_ok!(Contracts::instantiate(
+				Origin::signed(ALICE),
+				subsistence,
+				GAS_LIMIT,
+				code_hash.into(),
+				vec![0; 4],
+			));
+
+			// Re-instrumentation on execution.
+			let (result, gas_up_to_date) = Contracts::bare_call(ALICE, BOB, 0, GAS_LIMIT, vec![0; 4]);
+			assert_ok!(result);
+			bump_schedule_version();
+			let (result, gas_outdated) = Contracts::bare_call(ALICE, BOB, 0, GAS_LIMIT, vec![0; 4]);
+			assert_ok!(result);
+			assert_eq!(
+				gas_outdated,
+				gas_up_to_date + <() as WeightInfo>::reinstrument(crate::kilobytes(code_len)),
+			);
+
+			// Re-instrumentation in the background charges code that fails to re-instrument
+			// the same as code that succeeds.
+			let schedule = Contracts::current_schedule();
+			assert_ok!(Contracts::update_schedule(Origin::root(), Schedule {
+				version: schedule.version + 1,
+				enable_println: false,
+				.. schedule
+			}));
+			System::reset_events();
+			assert_eq!(
+				migrate_code::<Test>(Weight::max_value()),
+				db_weight.reads_writes(2, 1)
+					+ <() as WeightInfo>::reinstrument(crate::kilobytes(code_len))
+					+ <() as WeightInfo>::reinstrument(crate::kilobytes(failing_code_len)),
+			);
+			assert_eq!(System::events(), vec![
+				EventRecord {
+					phase: Phase::Initialization,
+					event: MetaEvent::contracts(RawEvent::CodeReinstrumented(code_hash.into())),
+					topics: vec![],
+				},
+			]);
+		});
+}
+
+#[test]
+fn reinstrument_code_only_acts_on_outdated_code() {
+	let (wasm, code_hash) = compile_module::<Test>("return_with_data").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		assert_noop!(
+			Contracts::reinstrument_code(Origin::signed(ALICE), code_hash.into()),
+			Error::<Test>::CodeNotFound,
+		);
+		assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+
+		System::reset_events();
+		assert_ok!(Contracts::reinstrument_code(Origin::signed(ALICE), code_hash.into()));
+		assert!(System::events().is_empty());
+
+		bump_schedule_version();
+		System::reset_events();
+		assert_ok!(Contracts::reinstrument_code(Origin::signed(ALICE), code_hash.into()));
+		assert_eq!(System::events(), vec![
+			EventRecord {
+				phase: Phase::Initialization,
+				event: MetaEvent::contracts(RawEvent::CodeReinstrumented(code_hash.into())),
+				topics: vec![],
+			},
+		]);
+	});
+}
+
+#[test]
+fn outdated_code_is_reinstrumented_in_background() {
+	let (wasm, code_hash) = compile_module::<Test>("return_with_data").unwrap();
+	let (other_wasm, other_code_hash) = compile_module::<Test>("run_out_of_gas").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+		assert_ok!(Contracts::put_code(Origin::signed(ALICE), other_wasm));
+
+		// Nothing to do without a schedule update.
+		assert_eq!(CodeMigrationCursor::get(), None);
+		bump_schedule_version();
+		assert!(CodeMigrationCursor::get().is_some());
+		System::reset_events();
+
+		// The limit only allows for migrating a single code of maximum size per call.
+		let max_code_kb = crate::kilobytes(Contracts::current_schedule().max_code_size);
+		let step_limit = <() as WeightInfo>::reinstrument(max_code_kb);
+		migrate_code::<Test>(step_limit);
+		assert_eq!(System::events().len(), 1);
+		assert!(CodeMigrationCursor::get().is_some());
+		migrate_code::<Test>(step_limit);
+		assert_eq!(System::events().len(), 2);
+
+		// All code was visited which finishes the migration with the next step.
+		migrate_code::<Test>(step_limit);
+		assert_eq!(CodeMigrationCursor::get(), None);
+
+		let mut migrated: Vec<_> = System::events().into_iter().map(|record| record.event).collect();
+		let mut expected = vec![
+			MetaEvent::contracts(RawEvent::CodeReinstrumented(code_hash.into())),
+			MetaEvent::contracts(RawEvent::CodeReinstrumented(other_code_hash.into())),
+		];
+		migrated.sort_by_key(|event| event.encode());
+		expected.sort_by_key(|event| event.encode());
+		assert_eq!(migrated, expected);
+
+		// The migrated code is not charged or re-instrumented again.
+		System::reset_events();
+		assert_ok!(Contracts::reinstrument_code(Origin::signed(ALICE), code_hash.into()));
+		assert_ok!(Contracts::reinstrument_code(Origin::signed(ALICE), other_code_hash.into()));
+		assert!(System::events().is_empty());
+	});
+}
+
+#[test]
+fn background_migration_progresses_with_a_limit_below_a_single_step() {
+	let (wasm, code_hash) = compile_module::<Test>("return_with_data").unwrap();
+	let (other_wasm, other_code_hash) = compile_module::<Test>("run_out_of_gas").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+		assert_ok!(Contracts::put_code(Origin::signed(ALICE), other_wasm));
+		bump_schedule_version();
+		System::reset_events();
+
+		// Every call migrates a single code even though the limit doesn't allow for any.
+		let code_len = crate::PristineCode::<Test>::get(&code_hash).unwrap().len() as u32;
+		let other_code_len = crate::PristineCode::<Test>::get(&other_code_hash).unwrap().len() as u32;
+		let first = migrate_code::<Test>(0);
+		assert_eq!(System::events().len(), 1);
+		let second = migrate_code::<Test>(0);
+		assert_eq!(System::events().len(), 2);
+		let mut charged = vec![first, second];
+		let mut expected = vec![
+			<() as WeightInfo>::reinstrument(crate::kilobytes(code_len)),
+			<() as WeightInfo>::reinstrument(crate::kilobytes(other_code_len)),
+		];
+		charged.sort();
+		expected.sort();
+		assert_eq!(charged, expected);
+
+		migrate_code::<Test>(0);
+		assert_eq!(CodeMigrationCursor::get(), None);
+		assert_eq!(System::events().len(), 2);
+	});
+}
+
+#[test]
+fn update_schedule_enforces_ceilings_and_floors() {
+	ExtBuilder::default().build().execute_with(|| {
+		let schedule = Contracts::current_schedule();
+		assert!(schedule.respects_weight_floors());
+		let version = schedule.version + 1;
+
+		// Limits above their ceilings are rejected even when forced.
+		let limits = vec![
+			Schedule { max_code_size: super::MAX_CODE_SIZE_CEILING + 1, .. schedule.clone() },
+			Schedule {
+				max_storage_key_len: super::MAX_STORAGE_KEY_LEN_CEILING + 1,
+				.. schedule.clone()
+			},
+			Schedule { max_subject_len: super::MAX_SUBJECT_LEN_CEILING + 1, .. schedule.clone() },
+			Schedule { max_event_topics: super::MAX_EVENT_TOPICS_CEILING + 1, .. schedule.clone() },
+			Schedule { max_table_size: super::MAX_TABLE_SIZE_CEILING + 1, .. schedule.clone() },
+			Schedule {
+				max_stack_height: super::MAX_STACK_HEIGHT_CEILING + 1,
+				.. schedule.clone()
+			},
+		];
+		for limit in limits {
+			assert_noop!(
+				Contracts::update_schedule_at(
+					Origin::root(),
+					Schedule { version, .. limit },
+					0,
+					true,
+				),
+				Error::<Test>::InvalidScheduleLimit,
+			);
+		}
+
+		// A cost of zero would make the operation free.
+		let mut free_instruction = schedule.clone();
+		free_instruction.instruction_weights.call = 0;
+		let mut free_host_fn = schedule.clone();
+		free_host_fn.host_fn_weights.set_storage = 0;
+		let mut free_bytes = schedule.clone();
+		free_bytes.host_fn_weights.hash_blake2_256_per_byte = 0;
+		let costs = vec![
+			free_instruction,
+			free_host_fn,
+			free_bytes,
+			Schedule { sandbox_data_read_cost: 0, .. schedule.clone() },
+			Schedule { call_base_cost: 0, .. schedule.clone() },
+		];
+		for cost in costs {
+			assert!(!cost.respects_weight_floors());
+			assert_noop!(
+				Contracts::update_schedule(Origin::root(), Schedule { version, .. cost }),
+				Error::<Test>::InvalidScheduleWeight,
+			);
+		}
+
+		// The floors and ceilings themselves are accepted.
+		let mut at_bounds = Schedule {
+			version,
+			max_code_size: super::MAX_CODE_SIZE_CEILING,
+			max_storage_key_len: super::MAX_STORAGE_KEY_LEN_CEILING,
+			sandbox_data_read_cost: super::MIN_PER_ITEM_WEIGHT,
+			call_base_cost: super::MIN_BASE_WEIGHT,
+			.. schedule
+		};
+		at_bounds.instruction_weights.call = super::MIN_INSTRUCTION_WEIGHT;
+		assert_ok!(Contracts::update_schedule(Origin::root(), at_bounds));
+	});
+}
+
+#[test]
+fn update_schedule_validates_the_schedule() {
+	ExtBuilder::default().build().execute_with(|| {
+		let schedule = Contracts::current_schedule();
+		assert_noop!(
+			Contracts::update_schedule(Origin::root(), schedule.clone()),
+			Error::<Test>::InvalidScheduleVersion,
+		);
+		assert_noop!(
+			Contracts::update_schedule(Origin::root(), Schedule {
+				version: schedule.version + 1,
+				max_memory_pages: 0,
+				.. schedule.clone()
+			}),
+			Error::<Test>::InvalidScheduleLimit,
+		);
+
+		// Lowering a limit is only possible when forced.
+		let lowered = Schedule {
+			version: schedule.version + 1,
+			max_code_size: schedule.max_code_size - 1,
+			.. schedule.clone()
+		};
+		assert_noop!(
+			Contracts::update_schedule(Origin::root(), lowered.clone()),
+			Error::<Test>::ScheduleLimitDecreased,
+		);
+		assert_noop!(
+			Contracts::update_schedule_at(Origin::root(), lowered.clone(), 0, false),
+			Error::<Test>::ScheduleLimitDecreased,
+		);
+		System::reset_events();
+		assert_ok!(Contracts::update_schedule_at(Origin::root(), lowered.clone(), 0, true));
+		assert_eq!(Contracts::current_schedule(), lowered);
+		assert_eq!(System::events(), vec![
+			EventRecord {
+				phase: Phase::Initialization,
+				event: MetaEvent::contracts(RawEvent::ScheduleUpdated(lowered.version, vec![
+					ScheduleChange {
+						field: b"max_code_size".to_vec(),
+						old: schedule.max_code_size.into(),
+						new: lowered.max_code_size.into(),
+					},
+				])),
+				topics: vec![],
+			},
+		]);
+	});
+}
+
+#[test]
+fn scheduled_schedule_update_is_activated_at_block() {
+	ExtBuilder::default().build().execute_with(|| {
+		let schedule = Contracts::current_schedule();
+		let new_schedule = Schedule {
+			version: schedule.version + 1,
+			enable_println: true,
+			.. schedule.clone()
+		};
+		let now = System::block_number();
+		assert_ok!(
+			Contracts::update_schedule_at(Origin::root(), new_schedule.clone(), now + 2, false)
+		);
+
+		// Immediate updates are rejected while another one is pending.
+		assert_noop!(
+			Contracts::update_schedule(Origin::root(), new_schedule.clone()),
+			Error::<Test>::ScheduleUpdatePending,
+		);
+
+		initialize_block(now + 1);
+		Contracts::on_initialize(now + 1);
+		assert_eq!(Contracts::current_schedule(), schedule);
+		assert!(System::events().is_empty());
+
+		initialize_block(now + 2);
+		Contracts::on_initialize(now + 2);
+		assert_eq!(Contracts::current_schedule(), new_schedule);
+		assert_eq!(System::events(), vec![
+			EventRecord {
+				phase: Phase::Initialization,
+				event: MetaEvent::contracts(RawEvent::ScheduleUpdated(new_schedule.version, vec![
+					ScheduleChange {
+						field: b"enable_println".to_vec(),
+						old: 0,
+						new: 1,
+					},
+				])),
+				topics: vec![],
+			},
+		]);
+
+		// The pending update was consumed.
+		assert_ok!(Contracts::update_schedule(Origin::root(), Schedule {
+			version: new_schedule.version + 1,
+			.. new_schedule
+		}));
+	});
+}
+
+#[test]
+fn schedule_of_old_layout_is_migrated_on_runtime_upgrade() {
+	let old_schedule = |version| super::ScheduleV0 {
+		version,
+		put_code_per_byte_cost: 1,
+		grow_mem_cost: 1,
+		regular_op_cost: 1,
+		return_data_per_byte_cost: 1,
+		event_data_per_byte_cost: 1,
+		event_per_topic_cost: 1,
+		event_base_cost: 1,
+		call_base_cost: 1,
+		instantiate_base_cost: 1,
+		dispatch_base_cost: 1,
+		sandbox_data_read_cost: 1,
+		sandbox_data_write_cost: 1,
+		transfer_cost: 1,
+		instantiate_cost: 1,
+		max_event_topics: 8,
+		max_stack_height: 128 * 1024,
+		max_memory_pages: 32,
+		max_table_size: 32 * 1024,
+		enable_println: true,
+		max_subject_len: 64,
+		max_code_size: 1024 * 1024,
+	};
+	let migrated = |version| Schedule {
+		version,
+		max_event_topics: 8,
+		max_stack_height: 128 * 1024,
+		max_memory_pages: 32,
+		max_table_size: 32 * 1024,
+		enable_println: true,
+		max_subject_len: 64,
+		max_code_size: 1024 * 1024,
+		.. Default::default()
+	};
+
+	ExtBuilder::default().build().execute_with(|| {
+		frame_support::storage::unhashed::put_raw(
+			&super::CurrentSchedule::hashed_key(),
+			&old_schedule(5).encode(),
+		);
+		frame_support::storage::unhashed::put_raw(
+			&super::PendingSchedule::<Test>::hashed_key(),
+			&(10u64, old_schedule(7)).encode(),
+		);
+
+		// The limits are kept and the costs are reset to the defaults.
+		Contracts::on_runtime_upgrade();
+		assert_eq!(Contracts::current_schedule(), migrated(6));
+		assert_eq!(super::PendingSchedule::<Test>::get(), Some((10, migrated(8))));
+		assert!(CodeMigrationCursor::get().is_some());
+
+		// Schedules of the current layout are kept.
+		Contracts::on_runtime_upgrade();
+		assert_eq!(Contracts::current_schedule(), migrated(6));
+		assert_eq!(super::PendingSchedule::<Test>::get(), Some((10, migrated(8))));
+
+		// A pending schedule that can't be decoded is dropped.
+		frame_support::storage::unhashed::put_raw(
+			&super::PendingSchedule::<Test>::hashed_key(),
+			&(10u64, 5u32).encode(),
+		);
+		Contracts::on_runtime_upgrade();
+		assert!(!super::PendingSchedule::<Test>::exists());
+	});
+}
+
+#[test]
+fn put_code_reports_why_code_was_rejected() {
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		let put_wat = |wat: &str| {
+			Contracts::put_code(Origin::signed(ALICE), wat::parse_str(wat).unwrap())
+		};
+
+		assert_noop!(
+			Contracts::put_code(Origin::signed(ALICE), vec![1, 2, 3]),
+			Error::<Test>::CodeDecodingFailed,
+		);
+		assert_noop!(
+			put_wat(r#"(module (func (export "call")))"#),
+			Error::<Test>::MissingExport,
+		);
+		assert_noop!(
+			put_wat(r#"
+			(module
+				(import "seal0" "seal_unknown" (func))
+				(func (export "call"))
+				(func (export "deploy"))
+			)"#),
+			Error::<Test>::UnknownImport,
+		);
+		assert_noop!(
+			put_wat(r#"
+			(module
+				(import "env" "memory" (memory 1 1000))
+				(func (export "call"))
+				(func (export "deploy"))
+			)"#),
+			Error::<Test>::MemoryLimitExceeded,
+		);
+		assert_noop!(
+			put_wat(r#"
+			(module
+				(func (export "call") (drop (f32.const 1)))
+				(func (export "deploy"))
+			)"#),
+			Error::<Test>::FloatingPointUsed,
+		);
+	});
+}
+
+#[test]
+fn validate_code_does_not_store_anything() {
+	let (wasm, code_hash) = compile_module::<Test>("return_with_data").unwrap();
+	ExtBuilder::default().build().execute_with(|| {
+		let validated = Contracts::validate_code(wasm.clone()).unwrap();
+		assert!(validated.instrumented_size as usize > wasm.len());
+		assert_eq!(validated.initial_memory_pages, 1);
+		assert_eq!(validated.maximum_memory_pages, 1);
+		assert_eq!(
+			validated.imported_functions,
+			vec![
+				(b"seal0".to_vec(), b"seal_input".to_vec()),
+				(b"seal0".to_vec(), b"seal_return".to_vec()),
+			],
+		);
+		assert!(!crate::PristineCode::<Test>::contains_key(&code_hash));
+		assert!(!crate::CodeStorage::<Test>::contains_key(&code_hash));
+
+		let unknown_import = wat::parse_str(r#"
+			(module
+				(import "seal0" "seal_unknown" (func))
+				(func (export "call"))
+				(func (export "deploy"))
+			)"#
+		).unwrap();
+		assert_eq!(
+			Contracts::validate_code(unknown_import),
+			Err(PrepareError::UnknownImport(b"seal_unknown".to_vec())),
+		);
+
+		let max_code_size = Contracts::current_schedule().max_code_size as usize;
+		assert_eq!(
+			Contracts::validate_code(vec![0; max_code_size + 1]),
+			Err(PrepareError::CodeTooLarge),
+		);
+	});
+}
+
+/// Appends a `contract-metadata` custom section with the given payload to `wasm`.
+fn with_metadata(wasm: &[u8], payload: &[u8]) -> Vec<u8> {
+	use parity_wasm::elements::{self, CustomSection, Section};
+
+	let mut module = elements::deserialize_buffer::<elements::Module>(wasm).unwrap();
+	module.sections_mut().push(Section::Custom(
+		CustomSection::new("contract-metadata".into(), payload.to_vec())
+	));
+	elements::serialize(module).unwrap()
+}
+
+#[test]
+fn contract_metadata_is_stored_separately() {
+	let (wasm, code_hash) = compile_module::<Test>("return_with_data").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+
+		assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+		assert_eq!(Contracts::code_metadata(&code_hash), None);
+
+		let wasm = with_metadata(&wasm_with_extra_export(), b"{\"name\":\"flipper\"}");
+		let code_hash = <Test as frame_system::Trait>::Hashing::hash(&wasm);
+		assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+		assert_eq!(
+			Contracts::code_metadata(&code_hash),
+			Some(b"{\"name\":\"flipper\"}".to_vec()),
+		);
+
+		let duplicate = with_metadata(&with_metadata(&wasm_with_extra_export(), b"a"), b"b");
+		assert_noop!(
+			Contracts::put_code(Origin::signed(ALICE), duplicate),
+			Error::<Test>::DuplicateMetadata,
+		);
+	});
+}
+
+fn wasm_with_extra_export() -> Vec<u8> {
+	wat::parse_str(r#"
+		(module
+			(func (export "call"))
+			(func (export "deploy"))
+			(func (export "__data_end"))
+		)"#
+	).unwrap()
+}
+
+#[test]
+fn code_with_extra_exports_can_be_instantiated() {
+	let wasm = wasm_with_extra_export();
+	let code_hash = <Test as frame_system::Trait>::Hashing::hash(&wasm);
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+
+		assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+		assert_ok!(Contracts::instantiate(
+			Origin::signed(ALICE),
+			100,
+			GAS_LIMIT,
+			code_hash.into(),
+			vec![],
+		));
+	});
+}
+
+#[test]
+fn custom_sections_are_stripped_before_storing() {
+	let (wasm, code_hash) = compile_module::<Test>("return_with_data").unwrap();
+	let normalized = crate::wasm::prepare::normalize(&wasm).unwrap();
+	let bytes_saved = custom_sections_len(&wasm);
+	assert!(bytes_saved > 0);
+
+	// By default only the instrumented code is stripped.
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm.clone()));
+		assert_eq!(crate::crate::PristineCode::<Test>::get(&code_hash), Some(wasm.clone()));
+		assert_eq!(
+			System::events().pop().unwrap().event,
+			MetaEvent::contracts(RawEvent::CodeStored(code_hash, bytes_saved)),
+		);
+	});
+
+	// The pristine code is stripped as well and stored under the hash of the stripped code
+	// which differs from the hash of the uploaded code.
+	let pristine_saved = (wasm.len() - normalized.len()) as u32;
+	assert!(pristine_saved > 0);
+	ExtBuilder::default()
+		.existential_deposit(50)
+		.strip_pristine_code(true)
+		.build()
+		.execute_with(|| {
+			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+			let normalized_hash = <Test as frame_system::Trait>::Hashing::hash(&normalized);
+			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm.clone()));
+			assert!(!crate::PristineCode::<Test>::contains_key(&code_hash));
+			assert_eq!(
+				crate::PristineCode::<Test>::get(&normalized_hash),
+				Some(normalized.clone()),
+			);
+			assert_eq!(
+				System::events().pop().unwrap().event,
+				MetaEvent::contracts(
+					RawEvent::CodeStored(normalized_hash, pristine_saved + bytes_saved)
+				),
+			);
+			assert_ok!(Contracts::instantiate(
+				Origin::signed(ALICE),
+				100,
+				GAS_LIMIT,
+				normalized_hash.into(),
+				vec![0; 4],
+			));
+		});
+}
+
+#[test]
+fn take_storage_keeps_bookkeeping_correct() {
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		test_utils::place_contract(&BOB, Default::default());
+		let trie_id = ContractInfoOf::<Test>::get(BOB).unwrap().get_alive().unwrap().trie_id;
+		let bookkeeping = || {
+			let info = ContractInfoOf::<Test>::get(BOB).unwrap().get_alive().unwrap();
+			(info.storage_size, info.total_pair_count, info.empty_pair_count)
+		};
+		let size = |key: &[u8]| {
+			crate::storage::contract_storage_size(&trie_id, StorageMode::Hashed, key)
+		};
+
+		assert_ok!(crate::storage::write_contract_storage::<Test>(
+			&BOB, &trie_id, StorageMode::Hashed, b"key", Some(vec![1, 2, 3]),
+		));
+		assert_ok!(crate::storage::write_contract_storage::<Test>(
+			&BOB, &trie_id, StorageMode::Hashed, b"empty", Some(vec![]),
+		));
+		assert_eq!(bookkeeping(), (3, 2, 1));
+		assert_eq!(size(b"key"), Some(3));
+		assert_eq!(size(b"empty"), Some(0));
+		assert_eq!(size(b"absent"), None);
+
+		assert_eq!(
+			crate::storage::take_contract_storage::<Test>(
+				&BOB, &trie_id, StorageMode::Hashed, b"key",
+			),
+			Ok(Some(vec![1, 2, 3])),
+		);
+		assert_eq!(bookkeeping(), (0, 1, 1));
+		assert_eq!(
+			crate::storage::take_contract_storage::<Test>(
+				&BOB, &trie_id, StorageMode::Hashed, b"empty",
+			),
+			Ok(Some(vec![])),
+		);
+		assert_eq!(bookkeeping(), (0, 0, 0));
+		assert_eq!(
+			crate::storage::take_contract_storage::<Test>(
+				&BOB, &trie_id, StorageMode::Hashed, b"key",
+			),
+			Ok(None),
+		);
+		assert_eq!(bookkeeping(), (0, 0, 0));
+		assert_eq!(size(b"key"), None);
+	});
+}
+
+#[test]
+fn write_storage_returns_size_of_previous_value() {
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		test_utils::place_contract(&BOB, Default::default());
+		let trie_id = ContractInfoOf::<Test>::get(BOB).unwrap().get_alive().unwrap().trie_id;
+		let write = |key: &[u8], value: Option<Vec<u8>>| {
+			crate::storage::write_contract_storage::<Test>(
+				&BOB, &trie_id, StorageMode::Hashed, key, value,
+			)
+		};
+		let size = |key: &[u8]| {
+			crate::storage::contract_storage_size(&trie_id, StorageMode::Hashed, key)
+		};
+
+		assert_eq!(write(b"key", Some(vec![1, 2, 3])), Ok(None));
+		assert_eq!(write(b"key", Some(vec![4; 5])), Ok(Some(3)));
+		assert_eq!(write(b"empty", Some(vec![])), Ok(None));
+		assert_eq!(write(b"empty", Some(vec![1])), Ok(Some(0)));
+		assert_eq!(write(b"key", None), Ok(Some(5)));
+		assert_eq!(write(b"key", None), Ok(None));
+		assert_eq!(size(b"key"), None);
+		assert_eq!(size(b"empty"), Some(1));
+	});
+}
+
+#[test]
+fn storage_of_unhashed_contract_can_be_iterated() {
+	let (wasm, code_hash) = compile_module::<Test>("storage_next_key").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+		assert_ok!(Contracts::instantiate_with_storage_mode(
+			Origin::signed(ALICE),
+			100_000,
+			GAS_LIMIT,
+			code_hash.into(),
+			vec![],
+			StorageMode::Unhashed,
+		));
+		let trie_id = ContractInfoOf::<Test>::get(BOB).unwrap().get_alive().unwrap().trie_id;
+		assert_eq!(StorageModeOf::get(&trie_id), StorageMode::Unhashed);
+		for key in [&b"a1"[..], b"a2", b"a3", b"b1"].iter() {
+			assert_ok!(crate::storage::write_contract_storage::<Test>(
+				&BOB, &trie_id, StorageMode::Unhashed, key, Some(vec![1]),
+			));
+		}
+
+		// The contract finds the first key with the prefix it was called with.
+		let result = Contracts::bare_call(ALICE, BOB, 0, GAS_LIMIT, b"b".to_vec()).0.unwrap();
+		assert_eq!(result.data, b"b1".to_vec());
+		let result = Contracts::bare_call(ALICE, BOB, 0, GAS_LIMIT, b"c".to_vec()).0.unwrap();
+		assert!(result.data.is_empty());
+
+		// The original keys can be listed page by page.
+		assert_eq!(
+			Contracts::storage_keys(BOB, b"a".to_vec(), None, 2),
+			Ok(vec![b"a1".to_vec(), b"a2".to_vec()]),
+		);
+		assert_eq!(
+			Contracts::storage_keys(BOB, b"a".to_vec(), Some(b"a2".to_vec()), 2),
+			Ok(vec![b"a3".to_vec()]),
+		);
+		assert_eq!(
+			Contracts::storage_keys(BOB, vec![], Some(b"a".to_vec()), 10),
+			Ok(vec![b"a1".to_vec(), b"a2".to_vec(), b"a3".to_vec(), b"b1".to_vec()]),
+		);
+		assert_eq!(
+			Contracts::storage_keys(DJANGO, vec![], None, 10),
+			Err(ContractAccessError::DoesntExist),
+		);
+
+		// A single call returns a bounded number of keys.
+		for i in 0 .. crate::MAX_STORAGE_KEYS_PER_PAGE {
+			assert_ok!(crate::storage::write_contract_storage::<Test>(
+				&BOB, &trie_id, StorageMode::Unhashed, &i.to_be_bytes(), Some(vec![1]),
+			));
+		}
+		let page = Contracts::storage_keys(BOB, vec![], None, u32::max_value()).unwrap();
+		assert_eq!(page.len(), crate::MAX_STORAGE_KEYS_PER_PAGE as usize);
+		let rest = Contracts::storage_keys(BOB, vec![], page.last().cloned(), u32::max_value());
+		assert_eq!(rest.unwrap().len(), 4);
+
+		// The storage mode is removed together with the contract.
+		crate::storage::destroy_contract::<Test>(&BOB, &trie_id);
+		assert!(!StorageModeOf::contains_key(&trie_id));
+	});
+}
+
+#[test]
+fn storage_of_hashed_contract_is_not_iterable() {
+	let (wasm, code_hash) = compile_module::<Test>("storage_next_key").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+		assert_ok!(Contracts::instantiate(
+			Origin::signed(ALICE),
+			100_000,
+			GAS_LIMIT,
+			code_hash.into(),
+			vec![],
+		));
+		let trie_id = ContractInfoOf::<Test>::get(BOB).unwrap().get_alive().unwrap().trie_id;
+		assert!(!StorageModeOf::contains_key(&trie_id));
+		assert_ok!(crate::storage::write_contract_storage::<Test>(
+			&BOB, &trie_id, StorageMode::Hashed, b"a1", Some(vec![1]),
+		));
+
+		assert_err_ignore_postinfo!(
+			Contracts::call(Origin::signed(ALICE), BOB, 0, GAS_LIMIT, b"a".to_vec()),
+			Error::<Test>::StorageNotIterable,
+		);
+
+		// Only the hashes of the keys are known.
+		assert_eq!(
+			Contracts::storage_keys(BOB, vec![], None, 10),
+			Ok(vec![blake2_256(b"a1").to_vec()]),
+		);
+	});
+}
+
+#[test]
+fn storage_can_be_exported_and_imported() {
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		test_utils::place_contract(&BOB, Default::default());
+		test_utils::place_contract(&CHARLIE, Default::default());
+		let trie_id = ContractInfoOf::<Test>::get(BOB).unwrap().get_alive().unwrap().trie_id;
+		assert_ok!(crate::storage::write_contract_storage::<Test>(
+			&BOB, &trie_id, StorageMode::Hashed, b"key", Some(vec![1, 2, 3]),
+		));
+		assert_ok!(crate::storage::write_contract_storage::<Test>(
+			&BOB, &trie_id, StorageMode::Hashed, b"empty", Some(vec![]),
+		));
+
+		let exported = Contracts::export_storage(BOB).unwrap();
+		assert_eq!(exported.storage_mode, StorageMode::Hashed);
+		assert_eq!(exported.items.len(), 2);
+		assert!(exported.items.contains(&(blake2_256(b"key").to_vec(), vec![1, 2, 3])));
+		assert!(exported.items.contains(&(blake2_256(b"empty").to_vec(), vec![])));
+		assert_eq!(Contracts::export_storage(DJANGO), Err(ContractAccessError::DoesntExist));
+
+		// Only root is allowed to import storage and the root has to match.
+		assert_noop!(
+			Contracts::import_storage(
+				Origin::signed(ALICE),
+				CHARLIE,
+				exported.storage_mode,
+				exported.items.clone(),
+				exported.child_root.clone(),
+			),
+			sp_runtime::DispatchError::BadOrigin,
+		);
+		assert_err!(
+			Contracts::import_storage(
+				Origin::root(), CHARLIE, exported.storage_mode, exported.items.clone(), vec![0; 32],
+			),
+			Error::<Test>::InvalidStorageRoot,
+		);
+		assert!(Contracts::export_storage(CHARLIE).unwrap().items.is_empty());
+		assert_err!(
+			Contracts::import_storage(
+				Origin::root(),
+				DJANGO,
+				exported.storage_mode,
+				exported.items.clone(),
+				exported.child_root.clone(),
+			),
+			Error::<Test>::NotCallable,
+		);
+
+		assert_ok!(Contracts::import_storage(
+			Origin::root(),
+			CHARLIE,
+			exported.storage_mode,
+			exported.items.clone(),
+			exported.child_root.clone(),
+		));
+		assert_eq!(Contracts::export_storage(CHARLIE), Ok(exported.clone()));
+		let info = ContractInfoOf::<Test>::get(CHARLIE).unwrap().get_alive().unwrap();
+		assert_eq!((info.storage_size, info.total_pair_count, info.empty_pair_count), (3, 2, 1));
+		assert_eq!(
+			crate::storage::read_contract_storage(&info.trie_id, StorageMode::Hashed, b"key"),
+			Some(vec![1, 2, 3]),
+		);
+
+		// Storage can only be imported once.
+		assert_err!(
+			Contracts::import_storage(
+				Origin::root(),
+				CHARLIE,
+				exported.storage_mode,
+				exported.items.clone(),
+				exported.child_root.clone(),
+			),
+			Error::<Test>::StorageNotEmpty,
+		);
+	});
+}
+
+#[test]
+fn unhashed_storage_can_be_exported_and_imported() {
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let bob_trie_id = <Test as Trait>::TrieIdGenerator::trie_id(&BOB);
+		assert_ok!(crate::storage::place_contract::<Test>(
+			&BOB, bob_trie_id.clone(), Default::default(), StorageMode::Unhashed,
+		));
+		test_utils::place_contract(&CHARLIE, Default::default());
+		for key in [&b"a1"[..], b"a2", b"b1"].iter() {
+			assert_ok!(crate::storage::write_contract_storage::<Test>(
+				&BOB, &bob_trie_id, StorageMode::Unhashed, key, Some(vec![1]),
+			));
+		}
+
+		let exported = Contracts::export_storage(BOB).unwrap();
+		assert_eq!(exported.storage_mode, StorageMode::Unhashed);
+		assert!(exported.items.contains(&(b"a1".to_vec(), vec![1])));
+
+		// A failed import leaves the storage mode untouched.
+		let charlie_trie_id =
+			ContractInfoOf::<Test>::get(CHARLIE).unwrap().get_alive().unwrap().trie_id;
+		assert_err!(
+			Contracts::import_storage(
+				Origin::root(), CHARLIE, exported.storage_mode, exported.items.clone(), vec![0; 32],
+			),
+			Error::<Test>::InvalidStorageRoot,
+		);
+		assert!(!StorageModeOf::contains_key(&charlie_trie_id));
+
+		assert_ok!(Contracts::import_storage(
+			Origin::root(),
+			CHARLIE,
+			exported.storage_mode,
+			exported.items.clone(),
+			exported.child_root.clone(),
+		));
+		assert_eq!(StorageModeOf::get(&charlie_trie_id), StorageMode::Unhashed);
+		assert_eq!(Contracts::export_storage(CHARLIE), Ok(exported));
+		assert_eq!(
+			crate::storage::read_contract_storage(&charlie_trie_id, StorageMode::Unhashed, b"a2"),
+			Some(vec![1]),
+		);
+		assert_eq!(
+			Contracts::storage_keys(CHARLIE, b"a".to_vec(), None, 10),
+			Ok(vec![b"a1".to_vec(), b"a2".to_vec()]),
+		);
+	});
+}
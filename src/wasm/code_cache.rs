@@ -25,51 +25,201 @@
 //! - When we update the schedule we want it to have strictly greater version than the current saved one:
 //! this guarantees that every instrumented contract code in cache cannot have the version equal to the current one.
 //! Thus, before executing a contract it should be reinstrument with new schedule.
+//! - Re-instrumentation is charged to the caller. In order to avoid surprising costs after a
+//! schedule update outdated code is also migrated in the background (see [`migrate`]) and
+//! anyone can re-instrument a specific code upfront by calling `reinstrument_code`.
 
+use crate::gas::{Gas, GasMeter, Token};
 use crate::wasm::{prepare, runtime::Env, PrefabWasmModule};
-use crate::{CodeHash, CodeStorage, PristineCode, Schedule, Trait};
+use crate::{
+	CodeHash, CodeMetadata, CodeMigrationCursor, CodeStorage, Error, Module, PristineCode, RawEvent,
+	Schedule, Trait, WeightInfo, kilobytes,
+};
+use codec::Decode;
 use sp_std::prelude::*;
-use sp_runtime::traits::Hash;
-use frame_support::StorageMap;
+use sp_runtime::{traits::Hash, DispatchError};
+use frame_support::{
+	storage::{StoragePrefixedMap, StorageValue},
+	traits::Get,
+	weights::Weight,
+	StorageMap,
+};
 
 /// Put code in the storage. The hash of code is used as a key and is returned
-/// as a result of this function.
+/// as a result of this function together with the number of bytes that were saved by
+/// stripping custom sections.
 ///
-/// This function instruments the given code and caches it in the storage.
+/// This function instruments the given code and caches it in the storage. The payload of
+/// the `contract-metadata` custom section is stored in `CodeMetadata` if present.
+///
+/// Custom sections are always stripped from the instrumented code. If
+/// `T::StripPristineCode` is set they are also stripped from the pristine code in which
+/// case the code hash is the hash of the stripped code. It then differs from the hash of
+/// `original_code` if the code contains custom sections.
 pub fn save<T: Trait>(
 	original_code: Vec<u8>,
 	schedule: &Schedule,
-) -> Result<CodeHash<T>, &'static str> {
-	let prefab_module = prepare::prepare_contract::<Env>(&original_code, schedule)?;
-	let code_hash = T::Hashing::hash(&original_code);
+) -> Result<(CodeHash<T>, u32), DispatchError> {
+	let prepared = prepare::prepare_for_storage::<Env>(
+		original_code,
+		schedule,
+		T::StripPristineCode::get(),
+	).map_err(Error::<T>::from)?;
+	let code_hash = T::Hashing::hash(&prepared.pristine_code);
 
-	<CodeStorage<T>>::insert(code_hash, prefab_module);
-	<PristineCode<T>>::insert(code_hash, original_code);
+	<CodeStorage<T>>::insert(code_hash, prepared.prefab_module);
+	if let Some(metadata) = prepared.metadata {
+		<CodeMetadata<T>>::insert(code_hash, metadata);
+	}
+	<PristineCode<T>>::insert(code_hash, prepared.pristine_code);
 
-	Ok(code_hash)
+	Ok((code_hash, prepared.bytes_saved))
 }
 
 /// Load code with the given code hash.
 ///
 /// If the module was instrumented with a lower version of schedule than
 /// the current one given as an argument, then this function will perform
-/// re-instrumentation and update the cache in the storage.
+/// re-instrumentation and update the cache in the storage. The re-instrumentation
+/// is charged to the `gas_meter` proportionally to the size of the pristine code.
+///
+/// If `read_only` is set the code is re-instrumented in memory only and the cache is
+/// left untouched. The re-instrumentation is still charged in this case.
 pub fn load<T: Trait>(
 	code_hash: &CodeHash<T>,
 	schedule: &Schedule,
-) -> Result<PrefabWasmModule, &'static str> {
-	let mut prefab_module =
-		<CodeStorage<T>>::get(code_hash).ok_or_else(|| "code is not found")?;
+	gas_meter: &mut GasMeter<T>,
+	read_only: bool,
+) -> Result<PrefabWasmModule, DispatchError> {
+	let prefab_module = <CodeStorage<T>>::get(code_hash).ok_or_else(|| Error::<T>::CodeNotFound)?;
 
 	if prefab_module.schedule_version < schedule.version {
 		// The current schedule version is greater than the version of the one cached
 		// in the storage.
 		//
 		// We need to re-instrument the code with the latest schedule here.
-		let original_code =
-			<PristineCode<T>>::get(code_hash).ok_or_else(|| "pristine code is not found")?;
-		prefab_module = prepare::prepare_contract::<Env>(&original_code, schedule)?;
-		<CodeStorage<T>>::insert(&code_hash, &prefab_module);
+		let code_len = <PristineCode<T>>::decode_len(code_hash)
+			.ok_or_else(|| Error::<T>::CodeNotFound)?;
+		if gas_meter.charge(&(), InstrumentToken(code_len as u32)).is_out_of_gas() {
+			Err(Error::<T>::OutOfGas)?
+		}
+		return if read_only {
+			instrument::<T>(code_hash, schedule)
+		} else {
+			reinstrument::<T>(code_hash, schedule)
+		};
+	}
+	Ok(prefab_module)
+}
+
+/// Re-instrument the code with the given code hash if it was instrumented with a lower
+/// version of schedule than the one given as an argument.
+///
+/// Returns the size of the pristine code if it was re-instrumented and `None` if the
+/// cached code is already up to date.
+pub fn reinstrument_outdated<T: Trait>(
+	code_hash: &CodeHash<T>,
+	schedule: &Schedule,
+) -> Result<Option<u32>, DispatchError> {
+	let prefab_module = <CodeStorage<T>>::get(code_hash).ok_or_else(|| Error::<T>::CodeNotFound)?;
+	if prefab_module.schedule_version >= schedule.version {
+		return Ok(None);
+	}
+	let code_len = <PristineCode<T>>::decode_len(code_hash)
+		.ok_or_else(|| Error::<T>::CodeNotFound)?;
+	reinstrument::<T>(code_hash, schedule)?;
+	Ok(Some(code_len as u32))
+}
+
+/// Re-instrument outdated code in the background after a schedule update.
+///
+/// Walks `CodeStorage` starting after the position stored in `CodeMigrationCursor` and
+/// re-instruments every outdated module until either all code is migrated or processing
+/// the next module could exceed `weight_limit`. Returns the weight that was consumed.
+///
+/// At least one module is processed per call. Otherwise the migration would never finish
+/// if `weight_limit` is lower than the worst case of a single step. The consumed weight can
+/// exceed `weight_limit` by one step in this case.
+pub fn migrate<T: Trait>(weight_limit: Weight) -> Weight {
+	let db_weight = T::DbWeight::get();
+	let mut cursor = match <CodeMigrationCursor>::get() {
+		Some(cursor) => cursor,
+		None => return db_weight.reads(1),
+	};
+	let schedule = <Module<T>>::current_schedule();
+	let prefix = <CodeStorage<T>>::final_prefix();
+	// Every step is accounted with the worst case so that the limit can not be exceeded.
+	let max_step_weight = T::WeightInfo::reinstrument(kilobytes(schedule.max_code_size));
+	let mut weight_used = db_weight.reads_writes(2, 1);
+	let mut done = false;
+	let mut first_step = true;
+
+	while first_step || weight_used.saturating_add(max_step_weight) <= weight_limit {
+		first_step = false;
+		let key = match sp_io::storage::next_key(&cursor).filter(|key| key.starts_with(&prefix)) {
+			Some(key) => key,
+			None => {
+				done = true;
+				break;
+			}
+		};
+		if let Ok(code_hash) = CodeHash::<T>::decode(&mut &key[prefix.len()..]) {
+			// Code that can not be re-instrumented is skipped: The error is reported
+			// to whoever executes it next. It is still charged as `prepare` did run.
+			let reinstrumented_len = match reinstrument_outdated::<T>(&code_hash, &schedule) {
+				Ok(Some(code_len)) => {
+					<Module<T>>::deposit_event(RawEvent::CodeReinstrumented(code_hash));
+					Some(code_len)
+				},
+				Ok(None) => None,
+				Err(_) => <PristineCode<T>>::decode_len(&code_hash).map(|len| len as u32),
+			};
+			weight_used = weight_used.saturating_add(match reinstrumented_len {
+				Some(code_len) => T::WeightInfo::reinstrument(kilobytes(code_len)),
+				None => db_weight.reads(2),
+			});
+		}
+		cursor = key;
 	}
+
+	if done {
+		<CodeMigrationCursor>::kill();
+	} else {
+		<CodeMigrationCursor>::put(cursor);
+	}
+	weight_used
+}
+
+/// Instrument the pristine code with the given code hash and update the cache.
+fn reinstrument<T: Trait>(
+	code_hash: &CodeHash<T>,
+	schedule: &Schedule,
+) -> Result<PrefabWasmModule, DispatchError> {
+	let prefab_module = instrument::<T>(code_hash, schedule)?;
+	<CodeStorage<T>>::insert(&code_hash, &prefab_module);
+	Ok(prefab_module)
+}
+
+/// Instrument the pristine code with the given code hash without updating the cache.
+fn instrument<T: Trait>(
+	code_hash: &CodeHash<T>,
+	schedule: &Schedule,
+) -> Result<PrefabWasmModule, DispatchError> {
+	let original_code = <PristineCode<T>>::get(code_hash).ok_or_else(|| Error::<T>::CodeNotFound)?;
+	let prefab_module = prepare::prepare_contract::<Env>(&original_code, schedule)
+		.map_err(Error::<T>::from)?;
 	Ok(prefab_module)
 }
+
+/// Token for charging the re-instrumentation of code with the given pristine size in bytes.
+#[cfg_attr(test, derive(Debug, PartialEq, Eq))]
+#[derive(Clone, Copy)]
+struct InstrumentToken(u32);
+
+impl<T: Trait> Token<T> for InstrumentToken {
+	type Metadata = ();
+
+	fn calculate_amount(&self, _metadata: &()) -> Gas {
+		T::WeightInfo::reinstrument(kilobytes(self.0))
+	}
+}
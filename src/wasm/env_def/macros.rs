@@ -45,20 +45,35 @@ macro_rules! gen_signature {
 #[macro_export]
 macro_rules! gen_signature_dispatch {
 	(
+		$needle_module:ident,
 		$needle_name:ident,
 		$needle_sig:ident ;
-		$name:ident
-		( $ctx:ident $( , $names:ident : $params:ty )* ) $( -> $returns:ty )* , $($rest:tt)* ) => {
-		if stringify!($name).as_bytes() == $needle_name {
+		[ $module:ident ] $name:ident
+		( $ctx:ident $( , $names:ident : $params:ty )* ) $( -> $returns:ty )* => $body:tt ,
+		$($rest:tt)*
+	) => {
+		if stringify!($module).as_bytes() == $needle_module &&
+			stringify!($name).as_bytes() == $needle_name
+		{
 			let signature = gen_signature!( ( $( $params ),* ) $( -> $returns )* );
 			if $needle_sig == &signature {
 				return true;
 			}
 		} else {
-			gen_signature_dispatch!($needle_name, $needle_sig ; $($rest)*);
+			gen_signature_dispatch!($needle_module, $needle_name, $needle_sig ; $($rest)*);
 		}
 	};
-	( $needle_name:ident, $needle_sig:ident ; ) => {
+	(
+		$needle_module:ident,
+		$needle_name:ident,
+		$needle_sig:ident ;
+		$name:ident $($rest:tt)*
+	) => {
+		gen_signature_dispatch!(
+			$needle_module, $needle_name, $needle_sig ; [seal0] $name $($rest)*
+		);
+	};
+	( $needle_module:ident, $needle_name:ident, $needle_sig:ident ; ) => {
 	};
 }
 
@@ -145,10 +160,11 @@ macro_rules! register_func {
 	( $reg_cb:ident, < E: $seal_ty:tt > ; ) => {};
 
 	( $reg_cb:ident, < E: $seal_ty:tt > ;
-		$name:ident ( $ctx:ident $( , $names:ident : $params:ty )* )
-		$( -> $returns:ty )* => $body:tt $($rest:tt)*
+		[ $module:ident ] $name:ident ( $ctx:ident $( , $names:ident : $params:ty )* )
+		$( -> $returns:ty )* => $body:tt , $($rest:tt)*
 	) => {
 		$reg_cb(
+			stringify!($module).as_bytes(),
 			stringify!($name).as_bytes(),
 			{
 				define_func!(
@@ -159,33 +175,42 @@ macro_rules! register_func {
 		);
 		register_func!( $reg_cb, < E: $seal_ty > ; $($rest)* );
 	};
+
+	( $reg_cb:ident, < E: $seal_ty:tt > ; $name:ident $($rest:tt)* ) => {
+		register_func!( $reg_cb, < E: $seal_ty > ; [seal0] $name $($rest)* );
+	};
 }
 
 /// Define a function set that can be imported by executing wasm code.
 ///
+/// A function is imported from the `seal0` module unless its name is prefixed with another
+/// module, e.g. `[seal1] seal_get_storage(...)`. New versions of a function are added this
+/// way without changing the behaviour of the existing ones.
+///
 /// **NB**: Be advised that all functions defined by this macro
 /// will panic if called with unexpected arguments.
 ///
 /// It's up to the user of this macro to check signatures of wasm code to be executed
 /// and reject the code if any imported function has a mismatched signature.
 macro_rules! define_env {
-	( $init_name:ident , < E: $seal_ty:tt > ,
-		$( $name:ident ( $ctx:ident $( , $names:ident : $params:ty )* )
-			$( -> $returns:ty )* => $body:tt , )*
-	) => {
+	( $init_name:ident , < E: $seal_ty:tt > , $( $rest:tt )* ) => {
 		pub struct $init_name;
 
 		impl $crate::wasm::env_def::ImportSatisfyCheck for $init_name {
-			fn can_satisfy(name: &[u8], func_type: &parity_wasm::elements::FunctionType) -> bool {
-				gen_signature_dispatch!( name, func_type ; $( $name ( $ctx $(, $names : $params )* ) $( -> $returns )* , )* );
+			fn can_satisfy(
+				module: &[u8],
+				name: &[u8],
+				func_type: &parity_wasm::elements::FunctionType,
+			) -> bool {
+				gen_signature_dispatch!( module, name, func_type ; $( $rest )* );
 
 				return false;
 			}
 		}
 
 		impl<E: Ext> $crate::wasm::env_def::FunctionImplProvider<E> for $init_name {
-			fn impls<F: FnMut(&[u8], $crate::wasm::env_def::HostFunc<E>)>(f: &mut F) {
-				register_func!(f, < E: $seal_ty > ; $( $name ( $ctx $( , $names : $params )* ) $( -> $returns)* => $body )* );
+			fn impls<F: FnMut(&[u8], &[u8], $crate::wasm::env_def::HostFunc<E>)>(f: &mut F) {
+				register_func!(f, < E: $seal_ty > ; $( $rest )* );
 			}
 		}
 	};
@@ -317,7 +342,27 @@ mod tests {
 			},
 		);
 
-		assert!(Env::can_satisfy(b"seal_gas", &FunctionType::new(vec![ValueType::I32], None)));
-		assert!(!Env::can_satisfy(b"not_exists", &FunctionType::new(vec![], None)));
+		assert!(
+			Env::can_satisfy(b"seal0", b"seal_gas", &FunctionType::new(vec![ValueType::I32], None))
+		);
+		assert!(!Env::can_satisfy(b"seal0", b"not_exists", &FunctionType::new(vec![], None)));
+	}
+
+	#[test]
+	fn macro_define_env_with_versions() {
+		use crate::wasm::env_def::ImportSatisfyCheck;
+
+		define_env!(Env, <E: Ext>,
+			seal_gas( _ctx, _amount: u32 ) => { Ok(()) },
+			[seal1] seal_gas( _ctx, _amount: u32, _extra: u32 ) => { Ok(()) },
+		);
+
+		let v0 = FunctionType::new(vec![ValueType::I32], None);
+		let v1 = FunctionType::new(vec![ValueType::I32, ValueType::I32], None);
+		assert!(Env::can_satisfy(b"seal0", b"seal_gas", &v0));
+		assert!(Env::can_satisfy(b"seal1", b"seal_gas", &v1));
+		assert!(!Env::can_satisfy(b"seal0", b"seal_gas", &v1));
+		assert!(!Env::can_satisfy(b"seal1", b"seal_gas", &v0));
+		assert!(!Env::can_satisfy(b"seal2", b"seal_gas", &v1));
 	}
 }
@@ -23,8 +23,11 @@ use crate::exec::{Ext, ExecResult};
 use crate::gas::GasMeter;
 
 use sp_std::prelude::*;
+use sp_runtime::{DispatchError, RuntimeDebug};
 use codec::{Encode, Decode};
 use sp_sandbox;
+#[cfg(feature = "std")]
+use serde::{Serialize, Deserialize};
 
 #[macro_use]
 pub mod env_def;
@@ -36,8 +39,11 @@ pub mod runtime_escrow;
 use self::runtime::{to_execution_result, Runtime};
 use self::code_cache::load as load_code;
 
-pub use self::code_cache::save as save_code;
+pub use self::code_cache::{
+	save as save_code, reinstrument_outdated as reinstrument_code, migrate as migrate_code,
+};
 pub use self::runtime::ReturnCode;
+pub use self::prepare::PrepareError;
 
 /// A prepared wasm module ready for execution.
 #[derive(Clone, Encode, Decode)]
@@ -58,6 +64,38 @@ pub struct PrefabWasmModule {
 	code: Vec<u8>,
 }
 
+/// Details about a code that passed the checks of `put_code`.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug)]
+pub struct ValidatedCode {
+	/// Size of the code in bytes after it was instrumented.
+	pub instrumented_size: u32,
+	/// Number of memory pages the imported memory initially requests.
+	pub initial_memory_pages: u32,
+	/// Maximum number of memory pages the imported memory can grow to.
+	pub maximum_memory_pages: u32,
+	/// The host functions imported by the code as `(module, name)` pairs.
+	pub imported_functions: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+/// Runs the checks and the instrumentation that `put_code` performs on `original_code`
+/// against the given `schedule` without storing anything.
+pub fn validate_code(
+	original_code: &[u8],
+	schedule: &Schedule,
+) -> Result<ValidatedCode, PrepareError> {
+	if original_code.len() as u32 > schedule.max_code_size {
+		return Err(PrepareError::CodeTooLarge);
+	}
+	let prefab_module = prepare::prepare_contract::<runtime::Env>(original_code, schedule)?;
+	Ok(ValidatedCode {
+		instrumented_size: prefab_module.code.len() as u32,
+		initial_memory_pages: prefab_module.initial,
+		maximum_memory_pages: prefab_module.maximum,
+		imported_functions: prepare::imported_functions(original_code)?,
+	})
+}
+
 /// Wasm executable loaded by `WasmLoader` and executed by `WasmVm`.
 pub struct WasmExecutable {
 	pub entrypoint_name: &'static str,
@@ -78,15 +116,24 @@ impl<'a> WasmLoader<'a> {
 impl<'a, T: Trait> crate::exec::Loader<T> for WasmLoader<'a> {
 	type Executable = WasmExecutable;
 
-	fn load_init(&self, code_hash: &CodeHash<T>) -> Result<WasmExecutable, &'static str> {
-		let prefab_module = load_code::<T>(code_hash, self.schedule)?;
+	fn load_init(
+		&self,
+		code_hash: &CodeHash<T>,
+		gas_meter: &mut GasMeter<T>,
+	) -> Result<WasmExecutable, DispatchError> {
+		let prefab_module = load_code::<T>(code_hash, self.schedule, gas_meter, false)?;
 		Ok(WasmExecutable {
 			entrypoint_name: "deploy",
 			prefab_module,
 		})
 	}
-	fn load_main(&self, code_hash: &CodeHash<T>) -> Result<WasmExecutable, &'static str> {
-		let prefab_module = load_code::<T>(code_hash, self.schedule)?;
+	fn load_main(
+		&self,
+		code_hash: &CodeHash<T>,
+		gas_meter: &mut GasMeter<T>,
+		read_only: bool,
+	) -> Result<WasmExecutable, DispatchError> {
+		let prefab_module = load_code::<T>(code_hash, self.schedule, gas_meter, read_only)?;
 		Ok(WasmExecutable {
 			entrypoint_name: "call",
 			prefab_module,
@@ -129,8 +176,8 @@ impl<'a, T: Trait> crate::exec::Vm<T> for WasmVm<'a> {
 
 		let mut imports = sp_sandbox::EnvironmentDefinitionBuilder::new();
 		imports.add_memory(self::prepare::IMPORT_MODULE_MEMORY, "memory", memory.clone());
-		runtime::Env::impls(&mut |name, func_ptr| {
-			imports.add_host_func(self::prepare::IMPORT_MODULE_FN, name, func_ptr);
+		runtime::Env::impls(&mut |module, name, func_ptr| {
+			imports.add_host_func(module, name, func_ptr);
 		});
 
 		let mut runtime = Runtime::new(
@@ -198,9 +245,12 @@ mod tests {
 		gas_left: u64,
 	}
 
+	/// A call to this account fails as if the callee trapped.
+	const TRAPPING_CALLEE: u64 = 42;
+
 	#[derive(Default)]
 	pub struct MockExt {
-		storage: HashMap<StorageKey, Vec<u8>>,
+		storage: HashMap<Vec<u8>, Vec<u8>>,
 		rent_allowance: u64,
 		instantiates: Vec<InstantiateEntry>,
 		terminations: Vec<TerminationEntry>,
@@ -209,16 +259,53 @@ mod tests {
 		// (topics, data)
 		events: Vec<(Vec<H256>, Vec<u8>)>,
 		next_account_id: u64,
+		// Whether storage writes, events and the rent allowance are denied.
+		read_only: bool,
+	}
+
+	impl MockExt {
+		fn ensure_writable(&self) -> Result<(), DispatchError> {
+			if self.read_only {
+				return Err(Error::<Test>::StateChangeDenied.into());
+			}
+			Ok(())
+		}
 	}
 
 	impl Ext for MockExt {
 		type T = Test;
 
-		fn get_storage(&self, key: &StorageKey) -> Option<Vec<u8>> {
+		fn get_storage(&self, key: &[u8]) -> Option<Vec<u8>> {
 			self.storage.get(key).cloned()
 		}
-		fn set_storage(&mut self, key: StorageKey, value: Option<Vec<u8>>) {
-			*self.storage.entry(key).or_insert(Vec::new()) = value.unwrap_or(Vec::new());
+		fn set_storage(
+			&mut self,
+			key: &[u8],
+			value: Option<Vec<u8>>,
+		) -> Result<Option<u32>, DispatchError> {
+			self.ensure_writable()?;
+			let prev_value = match value {
+				Some(value) => self.storage.insert(key.to_vec(), value),
+				None => self.storage.remove(key),
+			};
+			Ok(prev_value.map(|value| value.len() as u32))
+		}
+		fn get_storage_size(&self, key: &[u8]) -> Option<u32> {
+			self.storage.get(key).map(|value| value.len() as u32)
+		}
+		fn take_storage(&mut self, key: &[u8]) -> Result<Option<Vec<u8>>, DispatchError> {
+			self.ensure_writable()?;
+			Ok(self.storage.remove(key))
+		}
+		fn next_storage_key(
+			&self,
+			prefix: &[u8],
+			after: Option<&[u8]>,
+		) -> Result<Option<Vec<u8>>, DispatchError> {
+			Ok(self.storage.keys()
+				.filter(|key| key.starts_with(prefix) && after.map_or(true, |after| &key[..] > after))
+				.min()
+				.cloned())
 		}
 		fn instantiate(
 			&mut self,
@@ -264,16 +351,23 @@ mod tests {
 			value: u64,
 			gas_meter: &mut GasMeter<Test>,
 			data: Vec<u8>,
+			_allows_reentry: bool,
+			_read_only: bool,
 		) -> ExecResult {
 			self.transfers.push(TransferEntry {
 				to: *to,
 				value,
-				data: data,
+				data: data.clone(),
 				gas_left: gas_meter.gas_left(),
 			});
-			// Assume for now that it was just a plain transfer.
-			// TODO: Add tests for different call outcomes.
-			Ok(ExecReturnValue { flags: ReturnFlags::empty(), data: Vec::new() })
+			// The callee at `TRAPPING_CALLEE` traps. Every other callee returns its input.
+			if *to == TRAPPING_CALLEE {
+				return Err(ExecError {
+					error: Error::<Test>::ContractTrapped.into(),
+					origin: ErrorOrigin::Callee,
+				});
+			}
+			Ok(ExecReturnValue { flags: ReturnFlags::empty(), data })
 		}
 		fn terminate(
 			&mut self,
@@ -292,7 +386,7 @@ mod tests {
 			code_hash: H256,
 			rent_allowance: u64,
 			delta: Vec<StorageKey>,
-		) -> Result<(), &'static str> {
+		) -> Result<(), DispatchError> {
 			self.restores.push(RestoreEntry {
 				dest,
 				code_hash,
@@ -307,6 +401,25 @@ mod tests {
 		fn address(&self) -> &u64 {
 			&69
 		}
+		fn origin(&self) -> &u64 {
+			&42
+		}
+		fn caller_is_origin(&self) -> bool {
+			self.caller() == self.origin()
+		}
+		fn is_contract(&self, address: &u64) -> bool {
+			*address == 69
+		}
+		fn code_hash(&self, address: &u64) -> Option<H256> {
+			if self.is_contract(address) {
+				Some(H256::repeat_byte(0x11))
+			} else {
+				None
+			}
+		}
+		fn own_code_hash(&self) -> H256 {
+			H256::repeat_byte(0x11)
+		}
 		fn balance(&self) -> u64 {
 			228
 		}
@@ -330,12 +443,16 @@ mod tests {
 			H256::from_slice(subject)
 		}
 
-		fn deposit_event(&mut self, topics: Vec<H256>, data: Vec<u8>) {
-			self.events.push((topics, data))
+		fn deposit_event(&mut self, topics: Vec<H256>, data: Vec<u8>) -> Result<(), DispatchError> {
+			self.ensure_writable()?;
+			self.events.push((topics, data));
+			Ok(())
 		}
 
-		fn set_rent_allowance(&mut self, rent_allowance: u64) {
+		fn set_rent_allowance(&mut self, rent_allowance: u64) -> Result<(), DispatchError> {
+			self.ensure_writable()?;
 			self.rent_allowance = rent_allowance;
+			Ok(())
 		}
 
 		fn rent_allowance(&self) -> u64 {
@@ -354,12 +471,29 @@ mod tests {
 	impl Ext for &mut MockExt {
 		type T = <MockExt as Ext>::T;
 
-		fn get_storage(&self, key: &[u8; 32]) -> Option<Vec<u8>> {
+		fn get_storage(&self, key: &[u8]) -> Option<Vec<u8>> {
 			(**self).get_storage(key)
 		}
-		fn set_storage(&mut self, key: [u8; 32], value: Option<Vec<u8>>) {
+		fn set_storage(
+			&mut self,
+			key: &[u8],
+			value: Option<Vec<u8>>,
+		) -> Result<Option<u32>, DispatchError> {
 			(**self).set_storage(key, value)
 		}
+		fn get_storage_size(&self, key: &[u8]) -> Option<u32> {
+			(**self).get_storage_size(key)
+		}
+		fn take_storage(&mut self, key: &[u8]) -> Result<Option<Vec<u8>>, DispatchError> {
+			(**self).take_storage(key)
+		}
+		fn next_storage_key(
+			&self,
+			prefix: &[u8],
+			after: Option<&[u8]>,
+		) -> Result<Option<Vec<u8>>, DispatchError> {
+			(**self).next_storage_key(prefix, after)
+		}
 		fn instantiate(
 			&mut self,
 			code: &CodeHash<Test>,
@@ -390,8 +524,10 @@ mod tests {
 			value: u64,
 			gas_meter: &mut GasMeter<Test>,
 			input_data: Vec<u8>,
+			allows_reentry: bool,
+			read_only: bool,
 		) -> ExecResult {
-			(**self).call(to, value, gas_meter, input_data)
+			(**self).call(to, value, gas_meter, input_data, allows_reentry, read_only)
 		}
 		fn restore_to(
 			&mut self,
@@ -399,7 +535,7 @@ mod tests {
 			code_hash: H256,
 			rent_allowance: u64,
 			delta: Vec<StorageKey>,
-		) -> Result<(), &'static str> {
+		) -> Result<(), DispatchError> {
 			(**self).restore_to(
 				dest,
 				code_hash,
@@ -413,6 +549,21 @@ mod tests {
 		fn address(&self) -> &u64 {
 			(**self).address()
 		}
+		fn origin(&self) -> &u64 {
+			(**self).origin()
+		}
+		fn caller_is_origin(&self) -> bool {
+			(**self).caller_is_origin()
+		}
+		fn is_contract(&self, address: &u64) -> bool {
+			(**self).is_contract(address)
+		}
+		fn code_hash(&self, address: &u64) -> Option<H256> {
+			(**self).code_hash(address)
+		}
+		fn own_code_hash(&self) -> H256 {
+			(**self).own_code_hash()
+		}
 		fn balance(&self) -> u64 {
 			(**self).balance()
 		}
@@ -431,10 +582,10 @@ mod tests {
 		fn random(&self, subject: &[u8]) -> H256 {
 			(**self).random(subject)
 		}
-		fn deposit_event(&mut self, topics: Vec<H256>, data: Vec<u8>) {
+		fn deposit_event(&mut self, topics: Vec<H256>, data: Vec<u8>) -> Result<(), DispatchError> {
 			(**self).deposit_event(topics, data)
 		}
-		fn set_rent_allowance(&mut self, rent_allowance: u64) {
+		fn set_rent_allowance(&mut self, rent_allowance: u64) -> Result<(), DispatchError> {
 			(**self).set_rent_allowance(rent_allowance)
 		}
 		fn rent_allowance(&self) -> u64 {
@@ -593,198 +744,1278 @@ mod tests {
 		);
 	}
 
-	const CODE_INSTANTIATE: &str = r#"
+	/// Calls the callee with the flags passed as the first byte of the input. The input
+	/// is first cloned and then forwarded to the callee.
+	const CODE_CALL_WITH_FLAGS: &str = r#"
 (module
-	;; seal_instantiate(
-	;;     code_ptr: u32,
-	;;     code_len: u32,
-	;;     gas: u64,
-	;;     value_ptr: u32,
-	;;     value_len: u32,
-	;;     input_data_ptr: u32,
-	;;     input_data_len: u32,
-	;;     input_data_len: u32,
-	;;     address_ptr: u32,
-	;;     address_len_ptr: u32,
-	;;     output_ptr: u32,
-	;;     output_len_ptr: u32
-	;; ) -> u32
-	(import "seal0" "seal_instantiate" (func $seal_instantiate (param i32 i32 i64 i32 i32 i32 i32 i32 i32 i32 i32) (result i32)))
+	;; seal_call(
+	;;    flags: u32,
+	;;    callee_ptr: u32,
+	;;    callee_len: u32,
+	;;    gas: u64,
+	;;    value_ptr: u32,
+	;;    value_len: u32,
+	;;    input_data_ptr: u32,
+	;;    input_data_len: u32,
+	;;    output_ptr: u32,
+	;;    output_len_ptr: u32
+	;;) -> u32
+	(import "seal1" "seal_call" (func $seal_call (param i32 i32 i32 i64 i32 i32 i32 i32 i32 i32) (result i32)))
 	(import "env" "memory" (memory 1 1))
-	(func (export "call")
+
+	(func $call (param $flags i32)
 		(drop
-			(call $seal_instantiate
-				(i32.const 16)   ;; Pointer to `code_hash`
-				(i32.const 32)   ;; Length of `code_hash`
-				(i64.const 0)    ;; How much gas to devote for the execution. 0 = all.
-				(i32.const 4)    ;; Pointer to the buffer with value to transfer
-				(i32.const 8)    ;; Length of the buffer with value to transfer
-				(i32.const 12)   ;; Pointer to input data buffer address
-				(i32.const 4)    ;; Length of input data buffer
-				(i32.const 4294967295) ;; u32 max value is the sentinel value: do not copy address
-				(i32.const 0) ;; Length is ignored in this case
+			(call $seal_call
+				(get_local $flags)
+				(i32.const 4)  ;; Pointer to "callee" address.
+				(i32.const 8)  ;; Length of "callee" address.
+				(i64.const 0)  ;; How much gas to devote for the execution. 0 = all.
+				(i32.const 12) ;; Pointer to the buffer with value to transfer
+				(i32.const 8)  ;; Length of the buffer with value to transfer.
+				(i32.const 0)  ;; Input is passed by flags
+				(i32.const 0)  ;; Input is passed by flags
 				(i32.const 4294967295) ;; u32 max value is the sentinel value: do not copy output
 				(i32.const 0) ;; Length is ignored in this case
 			)
 		)
 	)
+
+	(func (export "call")
+		;; CLONE_INPUT
+		(call $call (i32.const 2))
+
+		;; FORWARD_INPUT | TAIL_CALL
+		(call $call (i32.const 5))
+
+		;; The tail call does not return.
+		(unreachable)
+	)
 	(func (export "deploy"))
 
+	;; Destination AccountId to transfer the funds.
+	;; Represented by u64 (8 bytes long) in little endian.
+	(data (i32.const 4) "\09\00\00\00\00\00\00\00")
 	;; Amount of value to transfer.
 	;; Represented by u64 (8 bytes long) in little endian.
-	(data (i32.const 4) "\03\00\00\00\00\00\00\00")
-	;; Input data to pass to the contract being instantiated.
-	(data (i32.const 12) "\01\02\03\04")
-	;; Hash of code.
-	(data (i32.const 16)
-		"\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11"
-		"\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11"
+	(data (i32.const 12) "\06\00\00\00\00\00\00\00")
+)
+"#;
+
+	#[test]
+	fn contract_call_forward_and_clone_input() {
+		let mut mock_ext = MockExt::default();
+		let result = execute(
+			CODE_CALL_WITH_FLAGS,
+			vec![1, 2, 3, 4],
+			&mut mock_ext,
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+
+		// The output of the tail call is returned.
+		assert_eq!(result, ExecReturnValue { flags: ReturnFlags::empty(), data: vec![1, 2, 3, 4] });
+		assert_eq!(mock_ext.transfers.len(), 2);
+		assert!(mock_ext.transfers.iter().all(|t| t.data == vec![1, 2, 3, 4]));
+	}
+
+	/// Calls the callee with `CLONE_INPUT` and afterwards returns its own input.
+	const CODE_CALL_CLONE_INPUT: &str = r#"
+(module
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal1" "seal_call" (func $seal_call (param i32 i32 i32 i64 i32 i32 i32 i32 i32 i32) (result i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; [0, 8) the callee
+	(data (i32.const 0) "\09\00\00\00\00\00\00\00")
+
+	;; [8, 16) the value to transfer
+	(data (i32.const 8) "\06\00\00\00\00\00\00\00")
+
+	;; [16, 20) size of the input buffer
+	(data (i32.const 16) "\10")
+
+	;; [20, 36) the input buffer
+
+	(func (export "call")
+		(drop
+			(call $seal_call
+				(i32.const 2)  ;; CLONE_INPUT
+				(i32.const 0)  ;; Pointer to "callee" address.
+				(i32.const 8)  ;; Length of "callee" address.
+				(i64.const 0)  ;; How much gas to devote for the execution. 0 = all.
+				(i32.const 8)  ;; Pointer to the buffer with value to transfer
+				(i32.const 8)  ;; Length of the buffer with value to transfer.
+				(i32.const 0)  ;; Input is passed by flags
+				(i32.const 0)  ;; Input is passed by flags
+				(i32.const 4294967295) ;; u32 max value is the sentinel value: do not copy output
+				(i32.const 0) ;; Length is ignored in this case
+			)
+		)
+
+		;; The input is still available after it was cloned.
+		(call $seal_input (i32.const 20) (i32.const 16))
+		(call $seal_return (i32.const 0) (i32.const 20) (i32.load (i32.const 16)))
 	)
+	(func (export "deploy"))
 )
 "#;
 
 	#[test]
-	fn contract_instantiate() {
+	fn contract_call_clone_input() {
 		let mut mock_ext = MockExt::default();
-		let _ = execute(
-			CODE_INSTANTIATE,
-			vec![],
+		let result = execute(
+			CODE_CALL_CLONE_INPUT,
+			vec![1, 2, 3, 4],
 			&mut mock_ext,
 			&mut GasMeter::new(GAS_LIMIT),
 		).unwrap();
 
-		assert_eq!(
-			&mock_ext.instantiates,
-			&[InstantiateEntry {
-				code_hash: [0x11; 32].into(),
-				endowment: 3,
-				data: vec![1, 2, 3, 4],
-				gas_left: 9971500000,
-			}]
+		assert_eq!(result, ExecReturnValue { flags: ReturnFlags::empty(), data: vec![1, 2, 3, 4] });
+		assert_eq!(mock_ext.transfers.len(), 1);
+		assert_eq!(mock_ext.transfers[0].data, vec![1, 2, 3, 4]);
+	}
+
+	/// Calls the callee with `FORWARD_INPUT` and then tries to clone the forwarded input.
+	const CODE_CALL_FORWARD_INPUT_TWICE: &str = r#"
+(module
+	(import "seal1" "seal_call" (func $seal_call (param i32 i32 i32 i64 i32 i32 i32 i32 i32 i32) (result i32)))
+	(import "env" "memory" (memory 1 1))
+
+	(func $call (param $flags i32)
+		(drop
+			(call $seal_call
+				(get_local $flags)
+				(i32.const 0)  ;; Pointer to "callee" address.
+				(i32.const 8)  ;; Length of "callee" address.
+				(i64.const 0)  ;; How much gas to devote for the execution. 0 = all.
+				(i32.const 8)  ;; Pointer to the buffer with value to transfer
+				(i32.const 8)  ;; Length of the buffer with value to transfer.
+				(i32.const 0)  ;; Input is passed by flags
+				(i32.const 0)  ;; Input is passed by flags
+				(i32.const 4294967295) ;; u32 max value is the sentinel value: do not copy output
+				(i32.const 0) ;; Length is ignored in this case
+			)
+		)
+	)
+
+	(func (export "call")
+		;; FORWARD_INPUT
+		(call $call (i32.const 1))
+
+		;; CLONE_INPUT traps because the input is gone.
+		(call $call (i32.const 2))
+	)
+	(func (export "deploy"))
+
+	(data (i32.const 0) "\09\00\00\00\00\00\00\00")
+	(data (i32.const 8) "\06\00\00\00\00\00\00\00")
+)
+"#;
+
+	/// Reads its input and then tries to forward it to the callee.
+	const CODE_CALL_FORWARD_INPUT_AFTER_READ: &str = r#"
+(module
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal1" "seal_call" (func $seal_call (param i32 i32 i32 i64 i32 i32 i32 i32 i32 i32) (result i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; [0, 8) the callee
+	(data (i32.const 0) "\09\00\00\00\00\00\00\00")
+
+	;; [8, 16) the value to transfer
+	(data (i32.const 8) "\06\00\00\00\00\00\00\00")
+
+	;; [16, 20) size of the input buffer
+	(data (i32.const 16) "\10")
+
+	;; [20, 36) the input buffer
+
+	(func (export "call")
+		(call $seal_input (i32.const 20) (i32.const 16))
+		(drop
+			(call $seal_call
+				(i32.const 1)  ;; FORWARD_INPUT
+				(i32.const 0)  ;; Pointer to "callee" address.
+				(i32.const 8)  ;; Length of "callee" address.
+				(i64.const 0)  ;; How much gas to devote for the execution. 0 = all.
+				(i32.const 8)  ;; Pointer to the buffer with value to transfer
+				(i32.const 8)  ;; Length of the buffer with value to transfer.
+				(i32.const 0)  ;; Input is passed by flags
+				(i32.const 0)  ;; Input is passed by flags
+				(i32.const 4294967295) ;; u32 max value is the sentinel value: do not copy output
+				(i32.const 0) ;; Length is ignored in this case
+			)
+		)
+	)
+	(func (export "deploy"))
+)
+"#;
+
+	#[test]
+	fn contract_call_forwarded_input_traps() {
+		let input_forwarded = Err(ExecError {
+			error: Error::<Test>::InputForwarded.into(),
+			origin: ErrorOrigin::Caller,
+		});
+
+		let mut mock_ext = MockExt::default();
+		let result = execute(
+			CODE_CALL_FORWARD_INPUT_TWICE,
+			vec![1, 2, 3, 4],
+			&mut mock_ext,
+			&mut GasMeter::new(GAS_LIMIT),
+		);
+		assert_eq!(result, input_forwarded);
+		assert_eq!(mock_ext.transfers.len(), 1);
+		assert_eq!(mock_ext.transfers[0].data, vec![1, 2, 3, 4]);
+
+		let mut mock_ext = MockExt::default();
+		let result = execute(
+			CODE_CALL_FORWARD_INPUT_AFTER_READ,
+			vec![1, 2, 3, 4],
+			&mut mock_ext,
+			&mut GasMeter::new(GAS_LIMIT),
 		);
+		assert_eq!(result, input_forwarded);
+		assert!(mock_ext.transfers.is_empty());
+	}
+
+	/// Tail calls the callee passed as input and returns the return code if it resumes.
+	const CODE_TAIL_CALL: &str = r#"
+(module
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal1" "seal_call" (func $seal_call (param i32 i32 i32 i64 i32 i32 i32 i32 i32 i32) (result i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; [0, 8) the callee which is supplied as input
+
+	;; [8, 16) the value to transfer
+	(data (i32.const 8) "\06\00\00\00\00\00\00\00")
+
+	;; [16, 20) size of the input buffer, later the return code of the call
+	(data (i32.const 16) "\08")
+
+	;; [20, 24) the input of the callee
+	(data (i32.const 20) "\01\02\03\04")
+
+	(func (export "call")
+		(call $seal_input (i32.const 0) (i32.const 16))
+		(i32.store (i32.const 16)
+			(call $seal_call
+				(i32.const 4)  ;; TAIL_CALL
+				(i32.const 0)  ;; Pointer to "callee" address.
+				(i32.const 8)  ;; Length of "callee" address.
+				(i64.const 0)  ;; How much gas to devote for the execution. 0 = all.
+				(i32.const 8)  ;; Pointer to the buffer with value to transfer
+				(i32.const 8)  ;; Length of the buffer with value to transfer.
+				(i32.const 20) ;; Pointer to input data buffer address
+				(i32.const 4)  ;; Length of input data buffer
+				(i32.const 4294967295) ;; The output buffer is ignored for a tail call
+				(i32.const 0) ;; Length is ignored in this case
+			)
+		)
+
+		;; Only reached if the call failed.
+		(call $seal_return (i32.const 0) (i32.const 16) (i32.const 4))
+	)
+	(func (export "deploy"))
+)
+"#;
+
+	#[test]
+	fn tail_call_returns_output_of_callee() {
+		let mut mock_ext = MockExt::default();
+		let result = execute(
+			CODE_TAIL_CALL,
+			9u64.to_le_bytes().to_vec(),
+			&mut mock_ext,
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+
+		assert_eq!(result, ExecReturnValue { flags: ReturnFlags::empty(), data: vec![1, 2, 3, 4] });
+		assert_eq!(mock_ext.transfers.len(), 1);
+	}
+
+	#[test]
+	fn failed_tail_call_resumes_execution() {
+		let mut mock_ext = MockExt::default();
+		let result = execute(
+			CODE_TAIL_CALL,
+			TRAPPING_CALLEE.to_le_bytes().to_vec(),
+			&mut mock_ext,
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+
+		// The contract receives `ReturnCode::CalleeTrapped` and returns it.
+		assert_eq!(result, ExecReturnValue { flags: ReturnFlags::empty(), data: vec![1, 0, 0, 0] });
+		assert_eq!(mock_ext.transfers.len(), 1);
 	}
 
-	const CODE_TERMINATE: &str = r#"
-(module
-	;; seal_terminate(
-	;;     beneficiary_ptr: u32,
-	;;     beneficiary_len: u32,
-	;; )
-	(import "seal0" "seal_terminate" (func $seal_terminate (param i32 i32)))
-	(import "env" "memory" (memory 1 1))
+	/// Calls the callee with reserved bits set in the flags.
+	const CODE_CALL_INVALID_FLAGS: &str = r#"
+(module
+	(import "seal1" "seal_call" (func $seal_call (param i32 i32 i32 i64 i32 i32 i32 i32 i32 i32) (result i32)))
+	(import "env" "memory" (memory 1 1))
+
+	(func (export "call")
+		(drop
+			(call $seal_call
+				(i32.const 32) ;; First reserved bit
+				(i32.const 4)  ;; Pointer to "callee" address.
+				(i32.const 8)  ;; Length of "callee" address.
+				(i64.const 0)  ;; How much gas to devote for the execution. 0 = all.
+				(i32.const 12) ;; Pointer to the buffer with value to transfer
+				(i32.const 8)  ;; Length of the buffer with value to transfer.
+				(i32.const 0)  ;; Pointer to input data buffer address
+				(i32.const 0)  ;; Length of input data buffer
+				(i32.const 4294967295) ;; u32 max value is the sentinel value: do not copy output
+				(i32.const 0) ;; Length is ignored in this case
+			)
+		)
+	)
+	(func (export "deploy"))
+
+	(data (i32.const 4) "\09\00\00\00\00\00\00\00")
+	(data (i32.const 12) "\06\00\00\00\00\00\00\00")
+)
+"#;
+
+	const CODE_CALL_FORWARD_AND_CLONE_INPUT: &str = r#"
+(module
+	(import "seal1" "seal_call" (func $seal_call (param i32 i32 i32 i64 i32 i32 i32 i32 i32 i32) (result i32)))
+	(import "env" "memory" (memory 1 1))
+
+	(func (export "call")
+		(drop
+			(call $seal_call
+				(i32.const 3)  ;; FORWARD_INPUT | CLONE_INPUT
+				(i32.const 4)  ;; Pointer to "callee" address.
+				(i32.const 8)  ;; Length of "callee" address.
+				(i64.const 0)  ;; How much gas to devote for the execution. 0 = all.
+				(i32.const 12) ;; Pointer to the buffer with value to transfer
+				(i32.const 8)  ;; Length of the buffer with value to transfer.
+				(i32.const 0)  ;; Pointer to input data buffer address
+				(i32.const 0)  ;; Length of input data buffer
+				(i32.const 4294967295) ;; u32 max value is the sentinel value: do not copy output
+				(i32.const 0) ;; Length is ignored in this case
+			)
+		)
+	)
+	(func (export "deploy"))
+
+	(data (i32.const 4) "\09\00\00\00\00\00\00\00")
+	(data (i32.const 12) "\06\00\00\00\00\00\00\00")
+)
+"#;
+
+	#[test]
+	fn contract_call_invalid_flags() {
+		let mut mock_ext = MockExt::default();
+		let result = execute(
+			CODE_CALL_INVALID_FLAGS,
+			vec![],
+			&mut mock_ext,
+			&mut GasMeter::new(GAS_LIMIT),
+		);
+
+		assert_eq!(
+			result,
+			Err(ExecError {
+				error: Error::<Test>::InvalidCallFlags.into(),
+				origin: ErrorOrigin::Caller,
+			})
+		);
+		assert!(mock_ext.transfers.is_empty());
+
+		// Forwarding and cloning the input at the same time is contradictory.
+		let mut mock_ext = MockExt::default();
+		let result = execute(
+			CODE_CALL_FORWARD_AND_CLONE_INPUT,
+			vec![1, 2, 3, 4],
+			&mut mock_ext,
+			&mut GasMeter::new(GAS_LIMIT),
+		);
+
+		assert_eq!(
+			result,
+			Err(ExecError {
+				error: Error::<Test>::InvalidCallFlags.into(),
+				origin: ErrorOrigin::Caller,
+			})
+		);
+		assert!(mock_ext.transfers.is_empty());
+	}
+
+	const CODE_INSTANTIATE: &str = r#"
+(module
+	;; seal_instantiate(
+	;;     code_ptr: u32,
+	;;     code_len: u32,
+	;;     gas: u64,
+	;;     value_ptr: u32,
+	;;     value_len: u32,
+	;;     input_data_ptr: u32,
+	;;     input_data_len: u32,
+	;;     input_data_len: u32,
+	;;     address_ptr: u32,
+	;;     address_len_ptr: u32,
+	;;     output_ptr: u32,
+	;;     output_len_ptr: u32
+	;; ) -> u32
+	(import "seal0" "seal_instantiate" (func $seal_instantiate (param i32 i32 i64 i32 i32 i32 i32 i32 i32 i32 i32) (result i32)))
+	(import "env" "memory" (memory 1 1))
+	(func (export "call")
+		(drop
+			(call $seal_instantiate
+				(i32.const 16)   ;; Pointer to `code_hash`
+				(i32.const 32)   ;; Length of `code_hash`
+				(i64.const 0)    ;; How much gas to devote for the execution. 0 = all.
+				(i32.const 4)    ;; Pointer to the buffer with value to transfer
+				(i32.const 8)    ;; Length of the buffer with value to transfer
+				(i32.const 12)   ;; Pointer to input data buffer address
+				(i32.const 4)    ;; Length of input data buffer
+				(i32.const 4294967295) ;; u32 max value is the sentinel value: do not copy address
+				(i32.const 0) ;; Length is ignored in this case
+				(i32.const 4294967295) ;; u32 max value is the sentinel value: do not copy output
+				(i32.const 0) ;; Length is ignored in this case
+			)
+		)
+	)
+	(func (export "deploy"))
+
+	;; Amount of value to transfer.
+	;; Represented by u64 (8 bytes long) in little endian.
+	(data (i32.const 4) "\03\00\00\00\00\00\00\00")
+	;; Input data to pass to the contract being instantiated.
+	(data (i32.const 12) "\01\02\03\04")
+	;; Hash of code.
+	(data (i32.const 16)
+		"\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11"
+		"\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11"
+	)
+)
+"#;
+
+	#[test]
+	fn contract_instantiate() {
+		let mut mock_ext = MockExt::default();
+		let _ = execute(
+			CODE_INSTANTIATE,
+			vec![],
+			&mut mock_ext,
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+
+		assert_eq!(
+			&mock_ext.instantiates,
+			&[InstantiateEntry {
+				code_hash: [0x11; 32].into(),
+				endowment: 3,
+				data: vec![1, 2, 3, 4],
+				gas_left: 9971500000,
+			}]
+		);
+	}
+
+	const CODE_TERMINATE: &str = r#"
+(module
+	;; seal_terminate(
+	;;     beneficiary_ptr: u32,
+	;;     beneficiary_len: u32,
+	;; )
+	(import "seal0" "seal_terminate" (func $seal_terminate (param i32 i32)))
+	(import "env" "memory" (memory 1 1))
+	(func (export "call")
+		(call $seal_terminate
+			(i32.const 4)  ;; Pointer to "beneficiary" address.
+			(i32.const 8)  ;; Length of "beneficiary" address.
+		)
+	)
+	(func (export "deploy"))
+
+	;; Beneficiary AccountId to transfer the funds.
+	;; Represented by u64 (8 bytes long) in little endian.
+	(data (i32.const 4) "\09\00\00\00\00\00\00\00")
+)
+"#;
+
+	#[test]
+	fn contract_terminate() {
+		let mut mock_ext = MockExt::default();
+		execute(
+			CODE_TERMINATE,
+			vec![],
+			&mut mock_ext,
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+
+		assert_eq!(
+			&mock_ext.terminations,
+			&[TerminationEntry {
+				beneficiary: 0x09,
+				gas_left: 9994500000,
+			}]
+		);
+	}
+
+	const CODE_TRANSFER_LIMITED_GAS: &str = r#"
+(module
+	;; seal_call(
+	;;    callee_ptr: u32,
+	;;    callee_len: u32,
+	;;    gas: u64,
+	;;    value_ptr: u32,
+	;;    value_len: u32,
+	;;    input_data_ptr: u32,
+	;;    input_data_len: u32,
+	;;    output_ptr: u32,
+	;;    output_len_ptr: u32
+	;;) -> u32
+	(import "seal0" "seal_call" (func $seal_call (param i32 i32 i64 i32 i32 i32 i32 i32 i32) (result i32)))
+	(import "env" "memory" (memory 1 1))
+	(func (export "call")
+		(drop
+			(call $seal_call
+				(i32.const 4)  ;; Pointer to "callee" address.
+				(i32.const 8)  ;; Length of "callee" address.
+				(i64.const 228)  ;; How much gas to devote for the execution.
+				(i32.const 12)  ;; Pointer to the buffer with value to transfer
+				(i32.const 8)   ;; Length of the buffer with value to transfer.
+				(i32.const 20)   ;; Pointer to input data buffer address
+				(i32.const 4)   ;; Length of input data buffer
+				(i32.const 4294967295) ;; u32 max value is the sentinel value: do not copy output
+				(i32.const 0) ;; Length is ignored in this cas
+			)
+		)
+	)
+	(func (export "deploy"))
+
+	;; Destination AccountId to transfer the funds.
+	;; Represented by u64 (8 bytes long) in little endian.
+	(data (i32.const 4) "\09\00\00\00\00\00\00\00")
+	;; Amount of value to transfer.
+	;; Represented by u64 (8 bytes long) in little endian.
+	(data (i32.const 12) "\06\00\00\00\00\00\00\00")
+
+	(data (i32.const 20) "\01\02\03\04")
+)
+"#;
+
+	#[test]
+	fn contract_call_limited_gas() {
+		let mut mock_ext = MockExt::default();
+		let _ = execute(
+			&CODE_TRANSFER_LIMITED_GAS,
+			vec![],
+			&mut mock_ext,
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+
+		assert_eq!(
+			&mock_ext.transfers,
+			&[TransferEntry {
+				to: 9,
+				value: 6,
+				data: vec![1, 2, 3, 4],
+				gas_left: 228,
+			}]
+		);
+	}
+
+	const CODE_GET_STORAGE: &str = r#"
+(module
+	(import "seal0" "seal_get_storage" (func $seal_get_storage (param i32 i32 i32) (result i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; [0, 32) key for get storage
+	(data (i32.const 0)
+		"\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11"
+		"\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11"
+	)
+
+	;; [32, 36) buffer size = 128 bytes
+	(data (i32.const 32) "\80")
+
+	;; [36; inf) buffer where the result is copied
+
+	(func $assert (param i32)
+		(block $ok
+			(br_if $ok
+				(get_local 0)
+			)
+			(unreachable)
+		)
+	)
+
+	(func (export "call")
+		(local $buf_size i32)
+
+		;; Load a storage value into contract memory.
+		(call $assert
+			(i32.eq
+				(call $seal_get_storage
+					(i32.const 0)		;; The pointer to the storage key to fetch
+					(i32.const 36)		;; Pointer to the output buffer
+					(i32.const 32)		;; Pointer to the size of the buffer
+				)
+
+				;; Return value 0 means that the value is found and there were
+				;; no errors.
+				(i32.const 0)
+			)
+		)
+
+		;; Find out the size of the buffer
+		(set_local $buf_size
+			(i32.load (i32.const 32))
+		)
+
+		;; Return the contents of the buffer
+		(call $seal_return
+			(i32.const 0)
+			(i32.const 36)
+			(get_local $buf_size)
+		)
+
+		;; env:seal_return doesn't return, so this is effectively unreachable.
+		(unreachable)
+	)
+
+	(func (export "deploy"))
+)
+"#;
+
+	#[test]
+	fn get_storage_puts_data_into_buf() {
+		let mut mock_ext = MockExt::default();
+		mock_ext
+			.storage
+			.insert([0x11; 32].to_vec(), [0x22; 32].to_vec());
+
+		let output = execute(
+			CODE_GET_STORAGE,
+			vec![],
+			mock_ext,
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+
+		assert_eq!(output, ExecReturnValue { flags: ReturnFlags::empty(), data: [0x22; 32].to_vec() });
+	}
+
+	const CODE_STORAGE_VARIABLE_KEY: &str = r#"
+(module
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal1" "seal_set_storage" (func $seal_set_storage (param i32 i32 i32 i32)))
+	(import "seal1" "seal_get_storage" (func $seal_get_storage (param i32 i32 i32 i32) (result i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; [0, 4) length of the key which is supplied as input
+
+	;; [4, 8) value to store
+	(data (i32.const 4) "\22\22\22\22")
+
+	;; [8, 12) buffer size = 4 bytes
+	(data (i32.const 8) "\04")
+
+	;; [12, 16) buffer where the stored value is copied
+
+	;; [16, inf) the key
+
+	(func $assert (param i32)
+		(block $ok
+			(br_if $ok
+				(get_local 0)
+			)
+			(unreachable)
+		)
+	)
+
+	(func (export "call")
+		;; Use the whole input as key.
+		(i32.store (i32.const 0) (i32.const 256))
+		(call $seal_input (i32.const 16) (i32.const 0))
+
+		(call $seal_set_storage
+			(i32.const 16)			;; Pointer to the key
+			(i32.load (i32.const 0))	;; Length of the key
+			(i32.const 4)			;; Pointer to the value
+			(i32.const 4)			;; Length of the value
+		)
+
+		(call $assert
+			(i32.eq
+				(call $seal_get_storage
+					(i32.const 16)			;; Pointer to the key
+					(i32.load (i32.const 0))	;; Length of the key
+					(i32.const 12)			;; Pointer to the output buffer
+					(i32.const 8)			;; Pointer to the size of the buffer
+				)
+				(i32.const 0)
+			)
+		)
+
+		(call $seal_return (i32.const 0) (i32.const 12) (i32.const 4))
+	)
+
+	(func (export "deploy"))
+)
+"#;
+
+	#[test]
+	fn storage_with_variable_length_key() {
+		let mut mock_ext = MockExt::default();
+
+		let output = execute(
+			CODE_STORAGE_VARIABLE_KEY,
+			b"abc".to_vec(),
+			&mut mock_ext,
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+
+		assert_eq!(output, ExecReturnValue { flags: ReturnFlags::empty(), data: [0x22; 4].to_vec() });
+		assert_eq!(mock_ext.storage.get(&b"abc"[..]), Some(&[0x22; 4].to_vec()));
+	}
+
+	#[test]
+	fn storage_key_is_charged_per_byte() {
+		let gas_used = |key_len: usize| {
+			let mut gas_meter = GasMeter::new(GAS_LIMIT);
+			execute(
+				CODE_STORAGE_VARIABLE_KEY,
+				vec![0x11; key_len],
+				MockExt::default(),
+				&mut gas_meter,
+			).unwrap();
+			GAS_LIMIT - gas_meter.gas_left()
+		};
+
+		// The key is written by `seal_input` and read by `seal_set_storage` and
+		// `seal_get_storage`.
+		let schedule = crate::Schedule::default();
+		let per_byte = schedule.sandbox_data_write_cost +
+			2 * (schedule.sandbox_data_read_cost + schedule.host_fn_weights.storage_key_per_byte);
+		assert_eq!(gas_used(101) - gas_used(1), 100 * per_byte);
+	}
+
+	#[test]
+	fn storage_key_too_large_traps() {
+		let max_key_len = crate::Schedule::default().max_storage_key_len as usize;
+
+		let result = execute(
+			CODE_STORAGE_VARIABLE_KEY,
+			vec![0x11; max_key_len + 1],
+			MockExt::default(),
+			&mut GasMeter::new(GAS_LIMIT),
+		);
+
+		assert_eq!(
+			result,
+			Err(ExecError {
+				error: Error::<Test>::StorageKeyTooLarge.into(),
+				origin: ErrorOrigin::Caller,
+			})
+		);
+	}
+
+	const CODE_SET_STORAGE_RETURNS_PREVIOUS_SIZE: &str = r#"
+(module
+	(import "seal2" "seal_set_storage" (func $seal_set_storage (param i32 i32 i32 i32) (result i32)))
+	(import "seal2" "seal_clear_storage" (func $seal_clear_storage (param i32 i32) (result i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; [0, 3) the key
+	(data (i32.const 0) "abc")
+
+	;; [4, 12) the value
+	(data (i32.const 4) "\22\22\22\22\22\22\22\22")
+
+	;; [12, 28) the returned sizes
+
+	(func (export "call")
+		;; No previous value.
+		(i32.store (i32.const 12)
+			(call $seal_set_storage (i32.const 0) (i32.const 3) (i32.const 4) (i32.const 8))
+		)
+		;; Overwrites the value of 8 bytes.
+		(i32.store (i32.const 16)
+			(call $seal_set_storage (i32.const 0) (i32.const 3) (i32.const 4) (i32.const 2))
+		)
+		;; Clears the value of 2 bytes.
+		(i32.store (i32.const 20)
+			(call $seal_clear_storage (i32.const 0) (i32.const 3))
+		)
+		;; Nothing left to clear.
+		(i32.store (i32.const 24)
+			(call $seal_clear_storage (i32.const 0) (i32.const 3))
+		)
+		(call $seal_return (i32.const 0) (i32.const 12) (i32.const 16))
+	)
+
+	(func (export "deploy"))
+)
+"#;
+
+	#[test]
+	fn set_storage_returns_previous_size() {
+		let mut mock_ext = MockExt::default();
+
+		let output = execute(
+			CODE_SET_STORAGE_RETURNS_PREVIOUS_SIZE,
+			vec![],
+			&mut mock_ext,
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+
+		use self::runtime::SENTINEL;
+		assert_eq!(output.data, (SENTINEL, 8u32, 2u32, SENTINEL).encode());
+		assert!(mock_ext.storage.is_empty());
+	}
+
+	/// Changes state with all functions that return a `ReturnCode` and returns the codes.
+	const CODE_STATE_CHANGES_RETURN_CODES: &str = r#"
+(module
+	(import "seal3" "seal_set_storage" (func $seal_set_storage (param i32 i32 i32 i32) (result i32)))
+	(import "seal3" "seal_clear_storage" (func $seal_clear_storage (param i32 i32) (result i32)))
+	(import "seal1" "seal_deposit_event" (func $seal_deposit_event (param i32 i32 i32 i32) (result i32)))
+	(import "seal1" "seal_set_rent_allowance" (func $seal_set_rent_allowance (param i32 i32) (result i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; [0, 3) the key
+	(data (i32.const 0) "abc")
+
+	;; [4, 12) the value which is also used as event data and rent allowance
+	(data (i32.const 4) "\22\22\22\22\22\22\22\22")
+
+	;; [12, 28) the returned codes
+
+	(func (export "call")
+		(i32.store (i32.const 12)
+			(call $seal_set_storage (i32.const 0) (i32.const 3) (i32.const 4) (i32.const 8))
+		)
+		(i32.store (i32.const 16)
+			(call $seal_deposit_event (i32.const 0) (i32.const 0) (i32.const 4) (i32.const 8))
+		)
+		(i32.store (i32.const 20)
+			(call $seal_set_rent_allowance (i32.const 4) (i32.const 8))
+		)
+		(i32.store (i32.const 24)
+			(call $seal_clear_storage (i32.const 0) (i32.const 3))
+		)
+		(call $seal_return (i32.const 0) (i32.const 12) (i32.const 16))
+	)
+
+	(func (export "deploy"))
+)
+"#;
+
+	#[test]
+	fn state_changes_return_codes() {
+		let codes = |code: ReturnCode| [code as u32; 4].encode();
+
+		let mut mock_ext = MockExt::default();
+		let output = execute(
+			CODE_STATE_CHANGES_RETURN_CODES,
+			vec![],
+			&mut mock_ext,
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+		assert_eq!(output.data, codes(ReturnCode::Success));
+		assert_eq!(mock_ext.events, vec![(vec![], vec![0x22; 8])]);
+		assert_eq!(mock_ext.rent_allowance, 0x2222_2222_2222_2222);
+		assert!(mock_ext.storage.is_empty());
+
+		// In read-only mode every function returns a code instead of trapping.
+		let mut mock_ext = MockExt { read_only: true, ..Default::default() };
+		let output = execute(
+			CODE_STATE_CHANGES_RETURN_CODES,
+			vec![],
+			&mut mock_ext,
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+		assert_eq!(output.data, codes(ReturnCode::StateChangeDenied));
+		assert!(mock_ext.events.is_empty());
+		assert_eq!(mock_ext.rent_allowance, 0);
+	}
+
+	const CODE_CONTAINS_AND_TAKE_STORAGE: &str = r#"
+(module
+	(import "seal0" "seal_contains_storage" (func $seal_contains_storage (param i32 i32 i32) (result i32)))
+	(import "seal0" "seal_take_storage" (func $seal_take_storage (param i32 i32 i32 i32) (result i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; [0, 3) the key
+	(data (i32.const 0) "abc")
+
+	;; [4, 8) size of the value written by seal_contains_storage
+
+	;; [8, 12) buffer size = 32 bytes
+	(data (i32.const 8) "\20")
+
+	;; [12, 44) buffer where the taken value is copied
+
+	(func $assert (param i32)
+		(block $ok
+			(br_if $ok
+				(get_local 0)
+			)
+			(unreachable)
+		)
+	)
+
+	(func (export "call")
+		(call $assert
+			(i32.eq
+				(call $seal_contains_storage (i32.const 0) (i32.const 3) (i32.const 4))
+				(i32.const 0) ;; ReturnCode::Success
+			)
+		)
+		(call $assert
+			(i32.eq (i32.load (i32.const 4)) (i32.const 4))
+		)
+		(call $assert
+			(i32.eq
+				(call $seal_take_storage (i32.const 0) (i32.const 3) (i32.const 12) (i32.const 8))
+				(i32.const 0) ;; ReturnCode::Success
+			)
+		)
+		(call $assert
+			(i32.eq
+				(call $seal_contains_storage (i32.const 0) (i32.const 3) (i32.const 4))
+				(i32.const 3) ;; ReturnCode::KeyNotFound
+			)
+		)
+		(call $assert
+			(i32.eq
+				(call $seal_take_storage (i32.const 0) (i32.const 3) (i32.const 12) (i32.const 8))
+				(i32.const 3) ;; ReturnCode::KeyNotFound
+			)
+		)
+
+		;; Return the taken value.
+		(call $seal_return (i32.const 0) (i32.const 12) (i32.load (i32.const 8)))
+	)
+
+	(func (export "deploy"))
+)
+"#;
+
+	#[test]
+	fn contains_and_take_storage() {
+		let mut mock_ext = MockExt::default();
+		mock_ext.storage.insert(b"abc".to_vec(), [0x22; 4].to_vec());
+
+		let output = execute(
+			CODE_CONTAINS_AND_TAKE_STORAGE,
+			vec![],
+			&mut mock_ext,
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+
+		assert_eq!(output, ExecReturnValue { flags: ReturnFlags::empty(), data: [0x22; 4].to_vec() });
+		assert!(mock_ext.storage.is_empty());
+	}
+
+	const CODE_STORAGE_NEXT_KEY: &str = r#"
+(module
+	(import "seal0" "seal_storage_next_key" (func $seal_storage_next_key (param i32 i32 i32 i32 i32 i32) (result i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; [0, 1) the prefix
+	(data (i32.const 0) "a")
+
+	;; [4, 8) buffer size = 32 bytes
+	(data (i32.const 4) "\20")
+
+	;; [8, 40) buffer where the next key is copied
+
+	(func $assert (param i32)
+		(block $ok
+			(br_if $ok
+				(get_local 0)
+			)
+			(unreachable)
+		)
+	)
+
+	(func (export "call")
+		;; Start at the first key with the prefix.
+		(call $assert
+			(i32.eq
+				(call $seal_storage_next_key
+					(i32.const 0) (i32.const 1) ;; prefix
+					(i32.const -1) (i32.const 0) ;; SENTINEL: no key to continue after
+					(i32.const 8) (i32.const 4)
+				)
+				(i32.const 0) ;; ReturnCode::Success
+			)
+		)
+		(call $assert
+			(i32.eq (i32.load (i32.const 4)) (i32.const 2))
+		)
+
+		;; Continue after the key that was just returned.
+		(i32.store (i32.const 4) (i32.const 32))
+		(call $assert
+			(i32.eq
+				(call $seal_storage_next_key
+					(i32.const 0) (i32.const 1)
+					(i32.const 8) (i32.const 2)
+					(i32.const 8) (i32.const 4)
+				)
+				(i32.const 0) ;; ReturnCode::Success
+			)
+		)
+		(call $assert
+			(i32.eq
+				(call $seal_storage_next_key
+					(i32.const 0) (i32.const 1)
+					(i32.const 8) (i32.load (i32.const 4))
+					(i32.const 48) (i32.const 4)
+				)
+				(i32.const 3) ;; ReturnCode::KeyNotFound
+			)
+		)
+
+		;; Return the last key with the prefix.
+		(call $seal_return (i32.const 0) (i32.const 8) (i32.load (i32.const 4)))
+	)
+
+	(func (export "deploy"))
+)
+"#;
+
+	#[test]
+	fn storage_next_key() {
+		let mut mock_ext = MockExt::default();
+		mock_ext.storage.insert(b"a1".to_vec(), vec![1]);
+		mock_ext.storage.insert(b"a2".to_vec(), vec![2]);
+		mock_ext.storage.insert(b"b1".to_vec(), vec![3]);
+
+		let output = execute(
+			CODE_STORAGE_NEXT_KEY,
+			vec![],
+			&mut mock_ext,
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+
+		assert_eq!(output, ExecReturnValue { flags: ReturnFlags::empty(), data: b"a2".to_vec() });
+	}
+
+	/// calls `seal_caller` and compares the result with the constant 42.
+	const CODE_CALLER: &str = r#"
+(module
+	(import "seal0" "seal_caller" (func $seal_caller (param i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; size of our buffer is 32 bytes
+	(data (i32.const 32) "\20")
+
+	(func $assert (param i32)
+		(block $ok
+			(br_if $ok
+				(get_local 0)
+			)
+			(unreachable)
+		)
+	)
+
+	(func (export "call")
+		;; fill the buffer with the caller.
+		(call $seal_caller (i32.const 0) (i32.const 32))
+
+		;; assert len == 8
+		(call $assert
+			(i32.eq
+				(i32.load (i32.const 32))
+				(i32.const 8)
+			)
+		)
+
+		;; assert that contents of the buffer is equal to the i64 value of 42.
+		(call $assert
+			(i64.eq
+				(i64.load (i32.const 0))
+				(i64.const 42)
+			)
+		)
+	)
+
+	(func (export "deploy"))
+)
+"#;
+
+	#[test]
+	fn caller() {
+		let _ = execute(
+			CODE_CALLER,
+			vec![],
+			MockExt::default(),
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+	}
+
+	/// calls `seal_address` and compares the result with the constant 69.
+	const CODE_ADDRESS: &str = r#"
+(module
+	(import "seal0" "seal_address" (func $seal_address (param i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; size of our buffer is 32 bytes
+	(data (i32.const 32) "\20")
+
+	(func $assert (param i32)
+		(block $ok
+			(br_if $ok
+				(get_local 0)
+			)
+			(unreachable)
+		)
+	)
+
+	(func (export "call")
+		;; fill the buffer with the self address.
+		(call $seal_address (i32.const 0) (i32.const 32))
+
+		;; assert size == 8
+		(call $assert
+			(i32.eq
+				(i32.load (i32.const 32))
+				(i32.const 8)
+			)
+		)
+
+		;; assert that contents of the buffer is equal to the i64 value of 69.
+		(call $assert
+			(i64.eq
+				(i64.load (i32.const 0))
+				(i64.const 69)
+			)
+		)
+	)
+
+	(func (export "deploy"))
+)
+"#;
+
+	#[test]
+	fn address() {
+		let _ = execute(
+			CODE_ADDRESS,
+			vec![],
+			MockExt::default(),
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+	}
+
+	/// calls `seal_origin` and compares the result with the constant 42.
+	const CODE_ORIGIN: &str = r#"
+(module
+	(import "seal0" "seal_origin" (func $seal_origin (param i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; size of our buffer is 32 bytes
+	(data (i32.const 32) "\20")
+
+	(func $assert (param i32)
+		(block $ok
+			(br_if $ok
+				(get_local 0)
+			)
+			(unreachable)
+		)
+	)
+
 	(func (export "call")
-		(call $seal_terminate
-			(i32.const 4)  ;; Pointer to "beneficiary" address.
-			(i32.const 8)  ;; Length of "beneficiary" address.
+		;; fill the buffer with the origin.
+		(call $seal_origin (i32.const 0) (i32.const 32))
+
+		;; assert len == 8
+		(call $assert
+			(i32.eq
+				(i32.load (i32.const 32))
+				(i32.const 8)
+			)
+		)
+
+		;; assert that contents of the buffer is equal to the i64 value of 42.
+		(call $assert
+			(i64.eq
+				(i64.load (i32.const 0))
+				(i64.const 42)
+			)
 		)
 	)
-	(func (export "deploy"))
 
-	;; Beneficiary AccountId to transfer the funds.
-	;; Represented by u64 (8 bytes long) in little endian.
-	(data (i32.const 4) "\09\00\00\00\00\00\00\00")
+	(func (export "deploy"))
 )
 "#;
 
 	#[test]
-	fn contract_terminate() {
-		let mut mock_ext = MockExt::default();
-		execute(
-			CODE_TERMINATE,
+	fn origin() {
+		let _ = execute(
+			CODE_ORIGIN,
 			vec![],
-			&mut mock_ext,
+			MockExt::default(),
 			&mut GasMeter::new(GAS_LIMIT),
 		).unwrap();
-
-		assert_eq!(
-			&mock_ext.terminations,
-			&[TerminationEntry {
-				beneficiary: 0x09,
-				gas_left: 9994500000,
-			}]
-		);
 	}
 
-	const CODE_TRANSFER_LIMITED_GAS: &str = r#"
+	/// calls `seal_caller_is_origin` and asserts that the mocked caller is the origin.
+	const CODE_CALLER_IS_ORIGIN: &str = r#"
 (module
-	;; seal_call(
-	;;    callee_ptr: u32,
-	;;    callee_len: u32,
-	;;    gas: u64,
-	;;    value_ptr: u32,
-	;;    value_len: u32,
-	;;    input_data_ptr: u32,
-	;;    input_data_len: u32,
-	;;    output_ptr: u32,
-	;;    output_len_ptr: u32
-	;;) -> u32
-	(import "seal0" "seal_call" (func $seal_call (param i32 i32 i64 i32 i32 i32 i32 i32 i32) (result i32)))
+	(import "seal0" "seal_caller_is_origin" (func $seal_caller_is_origin (result i32)))
 	(import "env" "memory" (memory 1 1))
-	(func (export "call")
-		(drop
-			(call $seal_call
-				(i32.const 4)  ;; Pointer to "callee" address.
-				(i32.const 8)  ;; Length of "callee" address.
-				(i64.const 228)  ;; How much gas to devote for the execution.
-				(i32.const 12)  ;; Pointer to the buffer with value to transfer
-				(i32.const 8)   ;; Length of the buffer with value to transfer.
-				(i32.const 20)   ;; Pointer to input data buffer address
-				(i32.const 4)   ;; Length of input data buffer
-				(i32.const 4294967295) ;; u32 max value is the sentinel value: do not copy output
-				(i32.const 0) ;; Length is ignored in this cas
+
+	(func $assert (param i32)
+		(block $ok
+			(br_if $ok
+				(get_local 0)
 			)
+			(unreachable)
 		)
 	)
-	(func (export "deploy"))
 
-	;; Destination AccountId to transfer the funds.
-	;; Represented by u64 (8 bytes long) in little endian.
-	(data (i32.const 4) "\09\00\00\00\00\00\00\00")
-	;; Amount of value to transfer.
-	;; Represented by u64 (8 bytes long) in little endian.
-	(data (i32.const 12) "\06\00\00\00\00\00\00\00")
+	(func (export "call")
+		(call $assert
+			(i32.eq
+				(call $seal_caller_is_origin)
+				(i32.const 1)
+			)
+		)
+	)
 
-	(data (i32.const 20) "\01\02\03\04")
+	(func (export "deploy"))
 )
 "#;
 
 	#[test]
-	fn contract_call_limited_gas() {
-		let mut mock_ext = MockExt::default();
+	fn caller_is_origin() {
 		let _ = execute(
-			&CODE_TRANSFER_LIMITED_GAS,
+			CODE_CALLER_IS_ORIGIN,
 			vec![],
-			&mut mock_ext,
+			MockExt::default(),
 			&mut GasMeter::new(GAS_LIMIT),
 		).unwrap();
-
-		assert_eq!(
-			&mock_ext.transfers,
-			&[TransferEntry {
-				to: 9,
-				value: 6,
-				data: vec![1, 2, 3, 4],
-				gas_left: 228,
-			}]
-		);
 	}
 
-	const CODE_GET_STORAGE: &str = r#"
+	/// calls `seal_is_contract` for the contract itself (69) and for its caller (42).
+	const CODE_IS_CONTRACT: &str = r#"
 (module
-	(import "seal0" "seal_get_storage" (func $seal_get_storage (param i32 i32 i32) (result i32)))
-	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "seal0" "seal_is_contract" (func $seal_is_contract (param i32 i32) (result i32)))
 	(import "env" "memory" (memory 1 1))
 
-	;; [0, 32) key for get storage
-	(data (i32.const 0)
-		"\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11"
-		"\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11"
-	)
-
-	;; [32, 36) buffer size = 128 bytes
-	(data (i32.const 32) "\80")
+	;; [0, 8) the address of the contract
+	(data (i32.const 0) "\45\00\00\00\00\00\00\00")
 
-	;; [36; inf) buffer where the result is copied
+	;; [8, 16) the address of the caller
+	(data (i32.const 8) "\2a\00\00\00\00\00\00\00")
 
 	(func $assert (param i32)
 		(block $ok
@@ -796,37 +2027,21 @@ mod tests {
 	)
 
 	(func (export "call")
-		(local $buf_size i32)
-
-		;; Load a storage value into contract memory.
+		;; the contract itself is a contract.
 		(call $assert
 			(i32.eq
-				(call $seal_get_storage
-					(i32.const 0)		;; The pointer to the storage key to fetch
-					(i32.const 36)		;; Pointer to the output buffer
-					(i32.const 32)		;; Pointer to the size of the buffer
-				)
-
-				;; Return value 0 means that the value is found and there were
-				;; no errors.
-				(i32.const 0)
+				(call $seal_is_contract (i32.const 0) (i32.const 8))
+				(i32.const 1)
 			)
 		)
 
-		;; Find out the size of the buffer
-		(set_local $buf_size
-			(i32.load (i32.const 32))
-		)
-
-		;; Return the contents of the buffer
-		(call $seal_return
-			(i32.const 0)
-			(i32.const 36)
-			(get_local $buf_size)
+		;; the caller is a plain account.
+		(call $assert
+			(i32.eq
+				(call $seal_is_contract (i32.const 8) (i32.const 8))
+				(i32.const 0)
+			)
 		)
-
-		;; env:seal_return doesn't return, so this is effectively unreachable.
-		(unreachable)
 	)
 
 	(func (export "deploy"))
@@ -834,30 +2049,31 @@ mod tests {
 "#;
 
 	#[test]
-	fn get_storage_puts_data_into_buf() {
-		let mut mock_ext = MockExt::default();
-		mock_ext
-			.storage
-			.insert([0x11; 32], [0x22; 32].to_vec());
-
-		let output = execute(
-			CODE_GET_STORAGE,
+	fn is_contract() {
+		let _ = execute(
+			CODE_IS_CONTRACT,
 			vec![],
-			mock_ext,
+			MockExt::default(),
 			&mut GasMeter::new(GAS_LIMIT),
 		).unwrap();
-
-		assert_eq!(output, ExecReturnValue { flags: ReturnFlags::empty(), data: [0x22; 32].to_vec() });
 	}
 
-	/// calls `seal_caller` and compares the result with the constant 42.
-	const CODE_CALLER: &str = r#"
+	/// calls `seal_code_hash` for the contract itself (69) and for its caller (42).
+	const CODE_CODE_HASH: &str = r#"
 (module
-	(import "seal0" "seal_caller" (func $seal_caller (param i32 i32)))
+	(import "seal0" "seal_code_hash" (func $seal_code_hash (param i32 i32 i32 i32) (result i32)))
 	(import "env" "memory" (memory 1 1))
 
-	;; size of our buffer is 32 bytes
-	(data (i32.const 32) "\20")
+	;; [0, 8) the address of the contract
+	(data (i32.const 0) "\45\00\00\00\00\00\00\00")
+
+	;; [8, 16) the address of the caller
+	(data (i32.const 8) "\2a\00\00\00\00\00\00\00")
+
+	;; [16, 20) size of the output buffer
+	(data (i32.const 16) "\20")
+
+	;; [20, 52) output buffer
 
 	(func $assert (param i32)
 		(block $ok
@@ -869,22 +2085,35 @@ mod tests {
 	)
 
 	(func (export "call")
-		;; fill the buffer with the caller.
-		(call $seal_caller (i32.const 0) (i32.const 32))
+		;; the code hash of the contract is returned.
+		(call $assert
+			(i32.eq
+				(call $seal_code_hash (i32.const 0) (i32.const 8) (i32.const 20) (i32.const 16))
+				(i32.const 0) ;; ReturnCode::Success
+			)
+		)
 
-		;; assert len == 8
+		;; assert len == 32
 		(call $assert
 			(i32.eq
-				(i32.load (i32.const 32))
-				(i32.const 8)
+				(i32.load (i32.const 16))
+				(i32.const 32)
 			)
 		)
 
-		;; assert that contents of the buffer is equal to the i64 value of 42.
+		;; assert that the mocked code hash was written.
 		(call $assert
 			(i64.eq
-				(i64.load (i32.const 0))
-				(i64.const 42)
+				(i64.load (i32.const 20))
+				(i64.const 0x1111111111111111)
+			)
+		)
+
+		;; the caller has no code hash.
+		(call $assert
+			(i32.eq
+				(call $seal_code_hash (i32.const 8) (i32.const 8) (i32.const 20) (i32.const 16))
+				(i32.const 8) ;; ReturnCode::NotCallable
 			)
 		)
 	)
@@ -894,19 +2123,19 @@ mod tests {
 "#;
 
 	#[test]
-	fn caller() {
+	fn code_hash() {
 		let _ = execute(
-			CODE_CALLER,
+			CODE_CODE_HASH,
 			vec![],
 			MockExt::default(),
 			&mut GasMeter::new(GAS_LIMIT),
 		).unwrap();
 	}
 
-	/// calls `seal_address` and compares the result with the constant 69.
-	const CODE_ADDRESS: &str = r#"
+	/// calls `seal_own_code_hash` and compares the result with the mocked code hash.
+	const CODE_OWN_CODE_HASH: &str = r#"
 (module
-	(import "seal0" "seal_address" (func $seal_address (param i32 i32)))
+	(import "seal0" "seal_own_code_hash" (func $seal_own_code_hash (param i32 i32)))
 	(import "env" "memory" (memory 1 1))
 
 	;; size of our buffer is 32 bytes
@@ -922,22 +2151,22 @@ mod tests {
 	)
 
 	(func (export "call")
-		;; fill the buffer with the self address.
-		(call $seal_address (i32.const 0) (i32.const 32))
+		;; fill the buffer with the code hash.
+		(call $seal_own_code_hash (i32.const 0) (i32.const 32))
 
-		;; assert size == 8
+		;; assert len == 32
 		(call $assert
 			(i32.eq
 				(i32.load (i32.const 32))
-				(i32.const 8)
+				(i32.const 32)
 			)
 		)
 
-		;; assert that contents of the buffer is equal to the i64 value of 69.
+		;; assert that the buffer starts with the mocked code hash.
 		(call $assert
 			(i64.eq
 				(i64.load (i32.const 0))
-				(i64.const 69)
+				(i64.const 0x1111111111111111)
 			)
 		)
 	)
@@ -947,9 +2176,9 @@ mod tests {
 "#;
 
 	#[test]
-	fn address() {
+	fn own_code_hash() {
 		let _ = execute(
-			CODE_ADDRESS,
+			CODE_OWN_CODE_HASH,
 			vec![],
 			MockExt::default(),
 			&mut GasMeter::new(GAS_LIMIT),
@@ -1471,7 +2700,7 @@ mod tests {
 			vec![0x00, 0x01, 0x2a, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xe5, 0x14, 0x00])
 		]);
 
-		assert_eq!(gas_meter.gas_left(), 9967000000);
+		assert_eq!(gas_meter.gas_left(), 9969459000);
 	}
 
 	const CODE_DEPOSIT_EVENT_MAX_TOPICS: &str = r#"
@@ -1746,4 +2975,99 @@ mod tests {
 		);
 	}
 
+	const CODE_ECDSA_RECOVER: &str = r#"
+(module
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal0" "seal_ecdsa_recover" (func $seal_ecdsa_recover (param i32 i32 i32) (result i32)))
+	(import "seal0" "seal_ecdsa_to_eth_address" (func $seal_ecdsa_to_eth_address (param i32 i32) (result i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; [0, 97) input: the signature followed by the message hash
+
+	;; [100, 133) recovered public key
+
+	;; [133, 153) ethereum address
+
+	;; [200, 204) input buffer size = 97 bytes
+	(data (i32.const 200) "\61")
+
+	;; [204, 208) return code of seal_ecdsa_recover
+
+	(func $assert (param i32)
+		(block $ok
+			(br_if $ok
+				(get_local 0)
+			)
+			(unreachable)
+		)
+	)
+
+	(func (export "call")
+		(call $seal_input (i32.const 0) (i32.const 200))
+		(i32.store
+			(i32.const 204)
+			(call $seal_ecdsa_recover (i32.const 0) (i32.const 65) (i32.const 100))
+		)
+
+		;; Return the error code if the recovery failed.
+		(if (i32.load (i32.const 204))
+			(then
+				(call $seal_return (i32.const 0) (i32.const 204) (i32.const 4))
+			)
+		)
+
+		(call $assert
+			(i32.eqz
+				(call $seal_ecdsa_to_eth_address (i32.const 100) (i32.const 133))
+			)
+		)
+
+		;; Return the public key followed by the address.
+		(call $seal_return (i32.const 0) (i32.const 100) (i32.const 53))
+	)
+
+	(func (export "deploy"))
+)
+"#;
+
+	#[test]
+	fn ecdsa_recover() {
+		// Signature of the message hash `blake2_256("Hello world")`: `r`, `s` and recovery id.
+		let signature = [
+			&hex!("17f53289eac961e5adc858d3ca50dab056ddca7a1a906c0815a0369312d1aa49")[..],
+			&hex!("406f342468a373a06246e243009b6bdc492059374e363e0eef9678d08b03a7cd")[..],
+			&[1],
+		].concat();
+		let message_hash = hex!("a21cf4b3604cf4b2bc53e6f88f6a4d75ef5ff4ab415f3e99aea6b61c8249c4d0");
+		let public_key = hex!("036223158e3f303beea2a372b175f7eb1c7f23b34c1ca6c061d4f9501172460319");
+		let address = hex!("5f92315b077e7d1f14c3a8008eebb6ae4f618a0e");
+
+		let output = execute(
+			CODE_ECDSA_RECOVER,
+			[&signature[..], &message_hash[..]].concat(),
+			MockExt::default(),
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+		assert_eq!(output.data, [&public_key[..], &address[..]].concat());
+
+		let output = execute(
+			CODE_ECDSA_RECOVER,
+			[&[0; 65][..], &message_hash[..]].concat(),
+			MockExt::default(),
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+		assert_eq!(output.data, (ReturnCode::EcdsaRecoverFailed as u32).encode());
+	}
+
+	#[test]
+	fn ecdsa_to_eth_address() {
+		// The public key of the secret key `1` is the generator point of the curve.
+		let generator = hex!("0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798");
+		assert_eq!(
+			runtime::ecdsa_to_eth_address(&generator),
+			Ok(hex!("7e5f4552091a69125d5dfcb7b8c2659029395bdf")),
+		);
+		assert_eq!(runtime::ecdsa_to_eth_address(&[0; 33]), Err(()));
+	}
 }
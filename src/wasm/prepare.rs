@@ -20,22 +20,104 @@
 
 use crate::wasm::env_def::ImportSatisfyCheck;
 use crate::wasm::PrefabWasmModule;
-use crate::Schedule;
+use crate::{Error, Schedule, Trait};
 
+use codec::{Encode, Decode};
 use parity_wasm::elements::{self, Internal, External, MemoryType, Type, ValueType};
 use pwasm_utils;
 use pwasm_utils::rules;
-use sp_std::prelude::*;
-use sp_runtime::traits::{SaturatedConversion};
+use sp_std::{prelude::*, num::NonZeroU32};
+use sp_runtime::{traits::{SaturatedConversion}, RuntimeDebug};
 
-/// Currently, all imported functions must be located inside this module. We might support
-/// additional modules for versioning later.
+/// The module of the first version of every imported function.
+///
+/// New versions of a function are located in the modules `seal1`, `seal2` and so on.
 pub const IMPORT_MODULE_FN: &str = "seal0";
 
+/// The name of the optional custom section that contains the metadata of a contract.
+///
+/// Its payload is stored separately from the code and can be retrieved by code hash.
+pub const CONTRACT_METADATA_SECTION: &str = "contract-metadata";
+
 /// Imported memory must be located inside this module. The reason for that is that current
 /// compiler toolchains might not support specifying other modules than "env" for memory imports.
 pub const IMPORT_MODULE_MEMORY: &str = "env";
 
+/// The reason why [`prepare_contract`] rejected a wasm module.
+///
+/// Names of imports and exports are included where they help to locate the problem.
+#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug)]
+pub enum PrepareError {
+	/// The code exceeds `Schedule::max_code_size`.
+	CodeTooLarge,
+	/// The code can not be decoded as a wasm module.
+	Deserialization,
+	/// The module is not valid according to the wasm specification.
+	Validation,
+	/// The module declares an internal memory instead of importing it.
+	InternalMemory,
+	/// The memory is not imported as `env.memory` or it is imported multiple times.
+	InvalidMemoryImport,
+	/// The imported memory declares no maximum or an initial size above its maximum.
+	InvalidMemoryLimits,
+	/// The maximum of the imported memory exceeds `Schedule::max_memory_pages`.
+	MemoryLimitExceeded,
+	/// The module declares more than one table.
+	MultipleTables,
+	/// The initial size of the table exceeds `Schedule::max_table_size`.
+	TableLimitExceeded,
+	/// Floating point types or instructions are used.
+	FloatUsage,
+	/// The named entry point is not exported.
+	MissingExport(Vec<u8>),
+	/// The named entry point is not a function defined by the module or has the wrong signature.
+	InvalidEntryPoint(Vec<u8>),
+	/// The `contract-metadata` custom section is declared more than once.
+	DuplicateMetadata,
+	/// A table or global is imported.
+	ForbiddenImport(Vec<u8>),
+	/// A function is imported from another module than `seal0` or one of its later versions.
+	InvalidImportModule(Vec<u8>),
+	/// The named function is not provided to contracts or is imported with a wrong signature.
+	UnknownImport(Vec<u8>),
+	/// `seal_println` is imported but not enabled by the schedule.
+	PrintlnDisabled,
+	/// Injecting the gas metering failed.
+	GasInstrumentation,
+	/// Injecting the stack height metering failed.
+	StackHeightInstrumentation,
+	/// The instrumented module could not be serialized.
+	Serialization,
+}
+
+impl<T: Trait> From<PrepareError> for Error<T> {
+	fn from(error: PrepareError) -> Self {
+		use PrepareError::*;
+		match error {
+			CodeTooLarge => Error::<T>::CodeTooLarge,
+			Deserialization => Error::<T>::CodeDecodingFailed,
+			Validation => Error::<T>::InvalidModule,
+			InternalMemory => Error::<T>::InternalMemory,
+			InvalidMemoryImport => Error::<T>::InvalidMemoryImport,
+			InvalidMemoryLimits => Error::<T>::InvalidMemoryLimits,
+			MemoryLimitExceeded => Error::<T>::MemoryLimitExceeded,
+			MultipleTables => Error::<T>::MultipleTables,
+			TableLimitExceeded => Error::<T>::TableLimitExceeded,
+			FloatUsage => Error::<T>::FloatingPointUsed,
+			MissingExport(_) => Error::<T>::MissingExport,
+			InvalidEntryPoint(_) => Error::<T>::InvalidEntryPoint,
+			DuplicateMetadata => Error::<T>::DuplicateMetadata,
+			ForbiddenImport(_) => Error::<T>::ForbiddenImport,
+			InvalidImportModule(_) => Error::<T>::InvalidImportModule,
+			UnknownImport(_) => Error::<T>::UnknownImport,
+			PrintlnDisabled => Error::<T>::PrintlnDisabled,
+			GasInstrumentation => Error::<T>::GasInstrumentationFailed,
+			StackHeightInstrumentation => Error::<T>::StackHeightInstrumentationFailed,
+			Serialization => Error::<T>::SerializationFailed,
+		}
+	}
+}
+
 struct ContractModule<'a> {
 	/// A deserialized module. The module is valid (this is Guaranteed by `new` method).
 	module: elements::Module,
@@ -50,14 +132,14 @@ impl<'a> ContractModule<'a> {
 	fn new(
 		original_code: &[u8],
 		schedule: &'a Schedule,
-	) -> Result<Self, &'static str> {
+	) -> Result<Self, PrepareError> {
 		use wasmi_validation::{validate_module, PlainValidator};
 
 		let module =
-			elements::deserialize_buffer(original_code).map_err(|_| "Can't decode wasm code")?;
+			elements::deserialize_buffer(original_code).map_err(|_| PrepareError::Deserialization)?;
 
 		// Make sure that the module is valid.
-		validate_module::<PlainValidator>(&module).map_err(|_| "Module is not valid")?;
+		validate_module::<PlainValidator>(&module).map_err(|_| PrepareError::Validation)?;
 
 		// Return a `ContractModule` instance with
 		// __valid__ module.
@@ -72,42 +154,42 @@ impl<'a> ContractModule<'a> {
 	/// In this runtime we only allow wasm module to import memory from the environment.
 	/// Memory section contains declarations of internal linear memories, so if we find one
 	/// we reject such a module.
-	fn ensure_no_internal_memory(&self) -> Result<(), &'static str> {
+	fn ensure_no_internal_memory(&self) -> Result<(), PrepareError> {
 		if self.module
 			.memory_section()
 			.map_or(false, |ms| ms.entries().len() > 0)
 		{
-			return Err("module declares internal memory");
+			return Err(PrepareError::InternalMemory);
 		}
 		Ok(())
 	}
 
 	/// Ensures that tables declared in the module are not too big.
-	fn ensure_table_size_limit(&self, limit: u32) -> Result<(), &'static str> {
+	fn ensure_table_size_limit(&self, limit: u32) -> Result<(), PrepareError> {
 		if let Some(table_section) = self.module.table_section() {
 			// In Wasm MVP spec, there may be at most one table declared. Double check this
 			// explicitly just in case the Wasm version changes.
 			if table_section.entries().len() > 1 {
-				return Err("multiple tables declared");
+				return Err(PrepareError::MultipleTables);
 			}
 			if let Some(table_type) = table_section.entries().first() {
 				// Check the table's initial size as there is no instruction or environment function
 				// capable of growing the table.
 				if table_type.limits().initial() > limit {
-					return Err("table exceeds maximum size allowed")
+					return Err(PrepareError::TableLimitExceeded)
 				}
 			}
 		}
 		Ok(())
 	}
 
-	/// Ensures that no floating point types are in use.
-	fn ensure_no_floating_types(&self) -> Result<(), &'static str> {
+	/// Ensures that no floating point types or instructions are in use.
+	fn ensure_no_floating_types(&self) -> Result<(), PrepareError> {
 		if let Some(global_section) = self.module.global_section() {
 			for global in global_section.entries() {
 				match global.global_type().content_type() {
 					ValueType::F32 | ValueType::F64 =>
-						return Err("use of floating point type in globals is forbidden"),
+						return Err(PrepareError::FloatUsage),
 					_ => {}
 				}
 			}
@@ -118,7 +200,15 @@ impl<'a> ContractModule<'a> {
 				for local in func_body.locals() {
 					match local.value_type() {
 						ValueType::F32 | ValueType::F64 =>
-							return Err("use of floating point type in locals is forbidden"),
+							return Err(PrepareError::FloatUsage),
+						_ => {}
+					}
+				}
+				for instruction in func_body.code().elements() {
+					use rules::InstructionType::*;
+					match rules::InstructionType::op(instruction) {
+						FloatConst | FloatComparsion | Float | FloatConversion =>
+							return Err(PrepareError::FloatUsage),
 						_ => {}
 					}
 				}
@@ -133,7 +223,7 @@ impl<'a> ContractModule<'a> {
 						for value_type in func_type.params().iter().chain(return_type.iter()) {
 							match value_type {
 								ValueType::F32 | ValueType::F64 =>
-									return Err("use of floating point type in function types is forbidden"),
+									return Err(PrepareError::FloatUsage),
 								_ => {}
 							}
 						}
@@ -145,30 +235,24 @@ impl<'a> ContractModule<'a> {
 		Ok(())
 	}
 
-	fn inject_gas_metering(self) -> Result<Self, &'static str> {
-		let gas_rules =
-			rules::Set::new(
-				self.schedule.regular_op_cost.clone().saturated_into(),
-				Default::default(),
-			)
-			.with_grow_cost(self.schedule.grow_mem_cost.clone().saturated_into())
-			.with_forbidden_floats();
+	fn inject_gas_metering(self) -> Result<Self, PrepareError> {
+		let gas_rules = ScheduleRules { schedule: self.schedule };
 
 		let contract_module = pwasm_utils::inject_gas_counter(
 			self.module,
 			&gas_rules,
 			IMPORT_MODULE_FN
-		).map_err(|_| "gas instrumentation failed")?;
+		).map_err(|_| PrepareError::GasInstrumentation)?;
 		Ok(ContractModule {
 			module: contract_module,
 			schedule: self.schedule,
 		})
 	}
 
-	fn inject_stack_height_metering(self) -> Result<Self, &'static str> {
+	fn inject_stack_height_metering(self) -> Result<Self, PrepareError> {
 		let contract_module =
 			pwasm_utils::stack_height::inject_limiter(self.module, self.schedule.max_stack_height)
-				.map_err(|_| "stack height instrumentation failed")?;
+				.map_err(|_| PrepareError::StackHeightInstrumentation)?;
 		Ok(ContractModule {
 			module: contract_module,
 			schedule: self.schedule,
@@ -181,8 +265,8 @@ impl<'a> ContractModule<'a> {
 	/// - 'call'
 	/// - 'deploy'
 	///
-	/// Any other exports are not allowed.
-	fn scan_exports(&self) -> Result<(), &'static str> {
+	/// Any other exports are allowed but removed by [`Self::strip_extras`].
+	fn scan_exports(&self) -> Result<(), PrepareError> {
 		let mut deploy_found = false;
 		let mut call_found = false;
 
@@ -218,14 +302,14 @@ impl<'a> ContractModule<'a> {
 			match export.field() {
 				"call" => call_found = true,
 				"deploy" => deploy_found = true,
-				_ => return Err("unknown export: expecting only deploy and call functions"),
+				_ => continue,
 			}
 
 			// Then check the export kind. "call" and "deploy" are
 			// functions.
 			let fn_idx = match export.internal() {
 				Internal::Function(ref fn_idx) => *fn_idx,
-				_ => return Err("expected a function"),
+				_ => return Err(PrepareError::InvalidEntryPoint(export.field().as_bytes().to_vec())),
 			};
 
 			// convert index from function index space to declared index space.
@@ -233,30 +317,30 @@ impl<'a> ContractModule<'a> {
 				Some(fn_idx) => fn_idx,
 				None => {
 					// Underflow here means fn_idx points to imported function which we don't allow!
-					return Err("entry point points to an imported function");
+					return Err(PrepareError::InvalidEntryPoint(export.field().as_bytes().to_vec()));
 				}
 			};
 
 			// Then check the signature.
 			// Both "call" and "deploy" has a () -> () function type.
 			let func_ty_idx = func_entries.get(fn_idx as usize)
-				.ok_or_else(|| "export refers to non-existent function")?
+				.ok_or_else(|| PrepareError::Validation)?
 				.type_ref();
 			let Type::Function(ref func_ty) = types
 				.get(func_ty_idx as usize)
-				.ok_or_else(|| "function has a non-existent type")?;
+				.ok_or_else(|| PrepareError::Validation)?;
 			if !func_ty.params().is_empty() ||
 				!(func_ty.return_type().is_none() ||
 					func_ty.return_type() == Some(ValueType::I32)) {
-				return Err("entry point has wrong signature");
+				return Err(PrepareError::InvalidEntryPoint(export.field().as_bytes().to_vec()));
 			}
 		}
 
 		if !deploy_found {
-			return Err("deploy function isn't exported");
+			return Err(PrepareError::MissingExport(b"deploy".to_vec()));
 		}
 		if !call_found {
-			return Err("call function isn't exported");
+			return Err(PrepareError::MissingExport(b"call".to_vec()));
 		}
 
 		Ok(())
@@ -269,7 +353,7 @@ impl<'a> ContractModule<'a> {
 	/// - checks any imported function against defined host functions set, incl.
 	///   their signatures.
 	/// - if there is a memory import, returns it's descriptor
-	fn scan_imports<C: ImportSatisfyCheck>(&self) -> Result<Option<&MemoryType>, &'static str> {
+	fn scan_imports<C: ImportSatisfyCheck>(&self) -> Result<Option<&MemoryType>, PrepareError> {
 		let module = &self.module;
 
 		let types = module.type_section().map(|ts| ts.types()).unwrap_or(&[]);
@@ -282,23 +366,23 @@ impl<'a> ContractModule<'a> {
 
 		for import in import_entries {
 			let type_idx = match import.external() {
-				&External::Table(_) => return Err("Cannot import tables"),
-				&External::Global(_) => return Err("Cannot import globals"),
+				&External::Table(_) | &External::Global(_) =>
+					return Err(PrepareError::ForbiddenImport(import.field().as_bytes().to_vec())),
 				&External::Function(ref type_idx) => {
-					if import.module() != IMPORT_MODULE_FN {
-						return Err("Invalid module for imported function");
+					if !is_seal_module(import.module()) {
+						return Err(
+							PrepareError::InvalidImportModule(import.module().as_bytes().to_vec())
+						);
 					}
 					type_idx
 				},
 				&External::Memory(ref memory_type) => {
-					if import.module() != IMPORT_MODULE_MEMORY {
-						return Err("Invalid module for imported memory");
-					}
-					if import.field() != "memory" {
-						return Err("Memory import must have the field name 'memory'")
-					}
-					if imported_mem_type.is_some() {
-						return Err("Multiple memory imports defined")
+					// The memory must be imported once as `env.memory`.
+					if import.module() != IMPORT_MODULE_MEMORY ||
+						import.field() != "memory" ||
+						imported_mem_type.is_some()
+					{
+						return Err(PrepareError::InvalidMemoryImport);
 					}
 					imported_mem_type = Some(memory_type);
 					continue;
@@ -307,27 +391,87 @@ impl<'a> ContractModule<'a> {
 
 			let Type::Function(ref func_ty) = types
 				.get(*type_idx as usize)
-				.ok_or_else(|| "validation: import entry points to a non-existent type")?;
+				.ok_or_else(|| PrepareError::Validation)?;
 
 			// We disallow importing `seal_println` unless debug features are enabled,
 			// which should only be allowed on a dev chain
 			if !self.schedule.enable_println && import.field().as_bytes() == b"seal_println" {
-				return Err("module imports `seal_println` but debug features disabled");
+				return Err(PrepareError::PrintlnDisabled);
 			}
 
 			// We disallow importing `gas` function here since it is treated as implementation detail.
 			if import.field().as_bytes() == b"gas"
-				|| !C::can_satisfy(import.field().as_bytes(), func_ty)
+				|| !C::can_satisfy(import.module().as_bytes(), import.field().as_bytes(), func_ty)
 			{
-				return Err("module imports a non-existent function");
+				return Err(PrepareError::UnknownImport(import.field().as_bytes().to_vec()));
 			}
 		}
 		Ok(imported_mem_type)
 	}
 
-	fn into_wasm_code(self) -> Result<Vec<u8>, &'static str> {
+	/// Removes all exports except for the entry points and all custom sections.
+	///
+	/// Neither is needed for execution. The `contract-metadata` section is stored separately.
+	/// Returns the number of bytes the stripped custom sections occupied.
+	fn strip_extras(&mut self) -> u32 {
+		if let Some(export_section) = self.module.export_section_mut() {
+			export_section
+				.entries_mut()
+				.retain(|export| export.field() == "call" || export.field() == "deploy");
+		}
+		strip_custom_sections(&mut self.module)
+	}
+
+	fn into_wasm_code(self) -> Result<Vec<u8>, PrepareError> {
 		elements::serialize(self.module)
-			.map_err(|_| "error serializing instrumented module")
+			.map_err(|_| PrepareError::Serialization)
+	}
+}
+
+/// The rules used for the gas instrumentation. They price every instruction according to the
+/// instruction weights of the schedule.
+struct ScheduleRules<'a> {
+	schedule: &'a Schedule,
+}
+
+impl<'a> rules::Rules for ScheduleRules<'a> {
+	fn instruction_cost(&self, instruction: &elements::Instruction) -> Option<u32> {
+		use elements::Instruction;
+		use rules::InstructionType::*;
+
+		let weights = &self.schedule.instruction_weights;
+		let weight = match instruction {
+			Instruction::End => 0,
+			Instruction::Call(_) => weights.call,
+			Instruction::CallIndirect(_, _) => weights.call_indirect,
+			_ => match rules::InstructionType::op(instruction) {
+				Const => weights.constant,
+				Local => weights.local,
+				Global => weights.global,
+				Load => weights.load,
+				Store => weights.store,
+				ControlFlow => weights.branch,
+				IntegerComparsion => weights.integer_comparison,
+				Bit => weights.bit,
+				Add => weights.add,
+				Mul => weights.mul,
+				Div => weights.div,
+				Conversion => weights.conversion,
+				// Only float values can be reinterpreted which are forbidden anyways.
+				Reinterpretation => weights.conversion,
+				Unreachable => weights.unreachable,
+				Nop => weights.nop,
+				CurrentMemory => weights.current_memory,
+				GrowMemory => weights.grow_memory,
+				FloatConst | FloatComparsion | Float | FloatConversion => return None,
+			},
+		};
+		Some(weight)
+	}
+
+	fn memory_grow_cost(&self) -> Option<rules::MemoryGrowCost> {
+		NonZeroU32::new(self.schedule.grow_mem_cost.saturated_into())
+			.map(rules::MemoryGrowCost::Linear)
 	}
 }
 
@@ -345,8 +489,67 @@ impl<'a> ContractModule<'a> {
 pub fn prepare_contract<C: ImportSatisfyCheck>(
 	original_code: &[u8],
 	schedule: &Schedule,
-) -> Result<PrefabWasmModule, &'static str> {
-	let mut contract_module = ContractModule::new(original_code, schedule)?;
+) -> Result<PrefabWasmModule, PrepareError> {
+	let contract_module = ContractModule::new(original_code, schedule)?;
+	instrument::<C>(contract_module, schedule).map(|(prefab_module, _)| prefab_module)
+}
+
+/// Code that passed the checks of [`prepare_for_storage`] in the form it is stored.
+pub struct PreparedCode {
+	/// The checked and instrumented code.
+	pub prefab_module: PrefabWasmModule,
+	/// The code that is stored as pristine code.
+	pub pristine_code: Vec<u8>,
+	/// The payload of the `contract-metadata` custom section if present.
+	pub metadata: Option<Vec<u8>>,
+	/// The number of bytes that are not stored because custom sections were stripped from
+	/// the instrumented and optionally the pristine code.
+	pub bytes_saved: u32,
+}
+
+/// Does the same as [`prepare_contract`] and additionally extracts the metadata and strips the
+/// custom sections from the pristine code if `strip_pristine_code` is set.
+///
+/// The code is only deserialized once. The pristine code is only re-serialized if custom
+/// sections were stripped from it.
+pub fn prepare_for_storage<C: ImportSatisfyCheck>(
+	original_code: Vec<u8>,
+	schedule: &Schedule,
+	strip_pristine_code: bool,
+) -> Result<PreparedCode, PrepareError> {
+	let contract_module = ContractModule::new(&original_code, schedule)?;
+	let metadata = metadata_of(&contract_module.module)?;
+	let mut normalized = None;
+	if strip_pristine_code {
+		let mut module = contract_module.module.clone();
+		if strip_custom_sections(&mut module) > 0 {
+			normalized = Some(elements::serialize(module).map_err(|_| PrepareError::Serialization)?);
+		}
+	}
+	let (prefab_module, instrumented_saved) = instrument::<C>(contract_module, schedule)?;
+	let (pristine_code, pristine_saved) = match normalized {
+		Some(code) => {
+			let saved = original_code.len().saturating_sub(code.len()) as u32;
+			(code, saved)
+		},
+		None => (original_code, 0),
+	};
+	Ok(PreparedCode {
+		prefab_module,
+		pristine_code,
+		metadata,
+		bytes_saved: pristine_saved.saturating_add(instrumented_saved),
+	})
+}
+
+/// Checks and instruments an already deserialized module.
+///
+/// Returns the number of bytes that were saved by stripping custom sections along with
+/// the instrumented module.
+fn instrument<C: ImportSatisfyCheck>(
+	mut contract_module: ContractModule<'_>,
+	schedule: &Schedule,
+) -> Result<(PrefabWasmModule, u32), PrepareError> {
 	contract_module.scan_exports()?;
 	contract_module.ensure_no_internal_memory()?;
 	contract_module.ensure_table_size_limit(schedule.max_table_size)?;
@@ -362,19 +565,18 @@ pub fn prepare_contract<C: ImportSatisfyCheck>(
 		let limits = memory_type.limits();
 		match (limits.initial(), limits.maximum()) {
 			(initial, Some(maximum)) if initial > maximum => {
-				return Err(
-					"Requested initial number of pages should not exceed the requested maximum",
-				);
+				// Requested initial number of pages should not exceed the requested maximum.
+				return Err(PrepareError::InvalidMemoryLimits);
 			}
 			(_, Some(maximum)) if maximum > schedule.max_memory_pages => {
-				return Err("Maximum number of pages should not exceed the configured maximum.");
+				return Err(PrepareError::MemoryLimitExceeded);
 			}
 			(initial, Some(maximum)) => MemoryDefinition { initial, maximum },
 			(_, None) => {
 				// Maximum number of pages should be always declared.
 				// This isn't a hard requirement and can be treated as a maximum set
 				// to configured maximum.
-				return Err("Maximum number of pages should be always declared.");
+				return Err(PrepareError::InvalidMemoryLimits);
 			}
 		}
 	} else {
@@ -386,17 +588,99 @@ pub fn prepare_contract<C: ImportSatisfyCheck>(
 		}
 	};
 
+	let stripped = contract_module.strip_extras();
 	contract_module = contract_module
 		.inject_gas_metering()?
 		.inject_stack_height_metering()?;
 
-	Ok(PrefabWasmModule {
+	Ok((PrefabWasmModule {
 		schedule_version: schedule.version,
 		initial: memory_def.initial,
 		maximum: memory_def.maximum,
 		_reserved: None,
 		code: contract_module.into_wasm_code()?,
-	})
+	}, stripped))
+}
+
+/// Returns whether `module` is one of the versioned modules that provide host functions.
+fn is_seal_module(module: &str) -> bool {
+	module.starts_with("seal") &&
+		module.len() > 4 &&
+		module.bytes().skip(4).all(|b| b.is_ascii_digit())
+}
+
+/// Removes the custom sections, e.g. the name and producers sections, from `module`.
+///
+/// Returns the number of bytes the removed sections occupied in the serialized module.
+fn strip_custom_sections(module: &mut elements::Module) -> u32 {
+	let mut stripped = 0u32;
+	module.sections_mut().retain(|section| match section {
+		elements::Section::Custom(_) | elements::Section::Name(_) | elements::Section::Reloc(_) => {
+			let len = elements::serialize(section.clone()).map_or(0, |bytes| bytes.len() as u32);
+			stripped = stripped.saturating_add(len);
+			false
+		},
+		_ => true,
+	});
+	stripped
+}
+
+/// Returns `original_code` without its custom sections.
+///
+/// The code is returned unchanged if it doesn't contain any custom sections so that its
+/// hash stays the same.
+pub fn normalize(original_code: &[u8]) -> Result<Vec<u8>, PrepareError> {
+	let mut module =
+		elements::deserialize_buffer::<elements::Module>(original_code)
+			.map_err(|_| PrepareError::Deserialization)?;
+	if strip_custom_sections(&mut module) == 0 {
+		return Ok(original_code.to_vec());
+	}
+	elements::serialize(module).map_err(|_| PrepareError::Serialization)
+}
+
+/// Returns the payload of the `contract-metadata` custom section of `original_code`.
+///
+/// Returns `None` if there is no such section.
+pub fn contract_metadata(original_code: &[u8]) -> Result<Option<Vec<u8>>, PrepareError> {
+	let module =
+		elements::deserialize_buffer::<elements::Module>(original_code)
+			.map_err(|_| PrepareError::Deserialization)?;
+	metadata_of(&module)
+}
+
+/// Returns the payload of the `contract-metadata` custom section of `module`.
+fn metadata_of(module: &elements::Module) -> Result<Option<Vec<u8>>, PrepareError> {
+	let mut sections = module
+		.custom_sections()
+		.filter(|section| section.name() == CONTRACT_METADATA_SECTION);
+	let metadata = sections.next().map(|section| section.payload().to_vec());
+	if sections.next().is_some() {
+		return Err(PrepareError::DuplicateMetadata);
+	}
+	Ok(metadata)
+}
+
+/// Returns the functions that `original_code` imports from the host as `(module, name)` pairs.
+///
+/// The module tells which version of a function is imported, e.g. `seal0` or `seal1`.
+pub fn imported_functions(
+	original_code: &[u8],
+) -> Result<Vec<(Vec<u8>, Vec<u8>)>, PrepareError> {
+	let module =
+		elements::deserialize_buffer::<elements::Module>(original_code)
+			.map_err(|_| PrepareError::Deserialization)?;
+	Ok(module
+		.import_section()
+		.map(|is| is.entries())
+		.unwrap_or(&[])
+		.iter()
+		.filter(|entry| match *entry.external() {
+			External::Function(_) => is_seal_module(entry.module()),
+			_ => false,
+		})
+		.map(|entry| (entry.module().as_bytes().to_vec(), entry.field().as_bytes().to_vec()))
+		.collect())
 }
 
 #[cfg(test)]
@@ -422,6 +706,8 @@ mod tests {
 
 		nop(_ctx, _unused: u64) => { unreachable!(); },
 
+		[seal1] nop(_ctx, _unused: u32) => { unreachable!(); },
+
 		seal_println(_ctx, _ptr: u32, _len: u32) => { unreachable!(); },
 	);
 
@@ -450,7 +736,7 @@ mod tests {
 			)
 			(func (export "deploy"))
 		)"#,
-		Err("gas instrumentation failed")
+		Err(PrepareError::FloatUsage)
 	);
 
 	mod memories {
@@ -483,7 +769,7 @@ mod tests {
 				(func (export "deploy"))
 			)
 			"#,
-			Err("module declares internal memory")
+			Err(PrepareError::InternalMemory)
 		);
 
 		prepare_test!(no_memory_import,
@@ -506,7 +792,7 @@ mod tests {
 				(func (export "deploy"))
 			)
 			"#,
-			Err("Module is not valid")
+			Err(PrepareError::Validation)
 		);
 
 		prepare_test!(no_maximum,
@@ -518,7 +804,7 @@ mod tests {
 				(func (export "deploy"))
 			)
 			"#,
-			Err("Maximum number of pages should be always declared.")
+			Err(PrepareError::InvalidMemoryLimits)
 		);
 
 		prepare_test!(requested_maximum_exceeds_configured_maximum,
@@ -530,7 +816,7 @@ mod tests {
 				(func (export "deploy"))
 			)
 			"#,
-			Err("Maximum number of pages should not exceed the configured maximum.")
+			Err(PrepareError::MemoryLimitExceeded)
 		);
 
 		prepare_test!(field_name_not_memory,
@@ -542,7 +828,7 @@ mod tests {
 				(func (export "deploy"))
 			)
 			"#,
-			Err("Memory import must have the field name 'memory'")
+			Err(PrepareError::InvalidMemoryImport)
 		);
 
 		prepare_test!(multiple_memory_imports,
@@ -555,7 +841,7 @@ mod tests {
 				(func (export "deploy"))
 			)
 			"#,
-			Err("Module is not valid")
+			Err(PrepareError::Validation)
 		);
 
 		prepare_test!(table_import,
@@ -567,7 +853,7 @@ mod tests {
 				(func (export "deploy"))
 			)
 			"#,
-			Err("Cannot import tables")
+			Err(PrepareError::ForbiddenImport(_))
 		);
 
 		prepare_test!(global_import,
@@ -578,7 +864,7 @@ mod tests {
 				(func (export "deploy"))
 			)
 			"#,
-			Err("Cannot import globals")
+			Err(PrepareError::ForbiddenImport(_))
 		);
 	}
 
@@ -621,7 +907,7 @@ mod tests {
 				(func (export "call"))
 				(func (export "deploy"))
 			)"#,
-			Err("table exceeds maximum size allowed")
+			Err(PrepareError::TableLimitExceeded)
 		);
 	}
 
@@ -651,7 +937,7 @@ mod tests {
 				(func (export "deploy"))
 			)
 			"#,
-			Err("module imports a non-existent function")
+			Err(PrepareError::UnknownImport(ref name)) if name == b"gas"
 		);
 
 		// memory is in "env" and not in "seal0"
@@ -664,7 +950,7 @@ mod tests {
 				(func (export "deploy"))
 			)
 			"#,
-			Err("Invalid module for imported memory")
+			Err(PrepareError::InvalidMemoryImport)
 		);
 
 		// memory is in "env" and not in some arbitrary module
@@ -677,7 +963,7 @@ mod tests {
 				(func (export "deploy"))
 			)
 			"#,
-			Err("Invalid module for imported memory")
+			Err(PrepareError::InvalidMemoryImport)
 		);
 
 		// functions are in "env" and not in "seal0"
@@ -690,7 +976,7 @@ mod tests {
 				(func (export "deploy"))
 			)
 			"#,
-			Err("Invalid module for imported function")
+			Err(PrepareError::InvalidImportModule(ref module)) if module == b"env"
 		);
 
 		// functions are in "seal0" and not in in some arbitrary module
@@ -703,7 +989,46 @@ mod tests {
 				(func (export "deploy"))
 			)
 			"#,
-			Err("Invalid module for imported function")
+			Err(PrepareError::InvalidImportModule(ref module)) if module == b"any_module"
+		);
+
+		prepare_test!(function_in_later_version,
+			r#"
+			(module
+				(import "seal0" "nop" (func (param i64)))
+				(import "seal1" "nop" (func (param i32)))
+
+				(func (export "call"))
+				(func (export "deploy"))
+			)
+			"#,
+			Ok(_)
+		);
+
+		// the signature of another version
+		prepare_test!(function_with_signature_of_other_version,
+			r#"
+			(module
+				(import "seal1" "nop" (func (param i64)))
+
+				(func (export "call"))
+				(func (export "deploy"))
+			)
+			"#,
+			Err(PrepareError::UnknownImport(ref name)) if name == b"nop"
+		);
+
+		// there is no such version
+		prepare_test!(function_in_unknown_version,
+			r#"
+			(module
+				(import "seal2" "nop" (func (param i32)))
+
+				(func (export "call"))
+				(func (export "deploy"))
+			)
+			"#,
+			Err(PrepareError::UnknownImport(ref name)) if name == b"nop"
 		);
 
 		// wrong signature
@@ -716,7 +1041,7 @@ mod tests {
 				(func (export "deploy"))
 			)
 			"#,
-			Err("module imports a non-existent function")
+			Err(PrepareError::UnknownImport(_))
 		);
 
 		prepare_test!(unknown_func_name,
@@ -728,7 +1053,7 @@ mod tests {
 				(func (export "deploy"))
 			)
 			"#,
-			Err("module imports a non-existent function")
+			Err(PrepareError::UnknownImport(ref name)) if name == b"unknown_func"
 		);
 
 		prepare_test!(seal_println_debug_disabled,
@@ -740,7 +1065,7 @@ mod tests {
 				(func (export "deploy"))
 			)
 			"#,
-			Err("module imports `seal_println` but debug features disabled")
+			Err(PrepareError::PrintlnDisabled)
 		);
 
 		#[test]
@@ -781,7 +1106,7 @@ mod tests {
 				(func (export "call"))
 			)
 			"#,
-			Err("deploy function isn't exported")
+			Err(PrepareError::MissingExport(ref name)) if name == b"deploy"
 		);
 
 		prepare_test!(omit_call,
@@ -790,7 +1115,7 @@ mod tests {
 				(func (export "deploy"))
 			)
 			"#,
-			Err("call function isn't exported")
+			Err(PrepareError::MissingExport(ref name)) if name == b"call"
 		);
 
 		// Try to use imported function as an entry point.
@@ -804,7 +1129,7 @@ mod tests {
 				(export "call" (func 0))
 			)
 			"#,
-			Err("entry point points to an imported function")
+			Err(PrepareError::InvalidEntryPoint(_))
 		);
 
 		// Try to use imported function as an entry point.
@@ -815,7 +1140,7 @@ mod tests {
 				(global (export "call") i32 (i32.const 0))
 			)
 			"#,
-			Err("expected a function")
+			Err(PrepareError::InvalidEntryPoint(_))
 		);
 
 		prepare_test!(wrong_signature,
@@ -825,7 +1150,7 @@ mod tests {
 				(func (export "call") (param i32))
 			)
 			"#,
-			Err("entry point has wrong signature")
+			Err(PrepareError::InvalidEntryPoint(_))
 		);
 
 		prepare_test!(unknown_exports,
@@ -836,9 +1161,121 @@ mod tests {
 				(func (export "whatevs"))
 			)
 			"#,
-			Err("unknown export: expecting only deploy and call functions")
+			Ok(_)
 		);
 
+		#[test]
+		fn unknown_exports_are_stripped() {
+			let wasm = wat::parse_str(r#"
+			(module
+				(global (export "counter") i32 (i32.const 0))
+				(func (export "call"))
+				(func (export "deploy"))
+				(func (export "whatevs"))
+			)
+			"#).unwrap();
+			let prefab = prepare_contract::<TestEnv>(&wasm, &Schedule::default()).unwrap();
+			let module = elements::deserialize_buffer::<elements::Module>(&prefab.code).unwrap();
+			let exports: Vec<_> = module
+				.export_section()
+				.unwrap()
+				.entries()
+				.iter()
+				.map(|export| export.field())
+				.collect();
+			assert_eq!(exports, vec!["call", "deploy"]);
+		}
+
+		#[test]
+		fn contract_metadata_is_stripped() {
+			let wasm = wat::parse_str(r#"
+			(module
+				(func (export "call"))
+				(func (export "deploy"))
+			)
+			"#).unwrap();
+			let mut module = elements::deserialize_buffer::<elements::Module>(&wasm).unwrap();
+			module.sections_mut().push(elements::Section::Custom(
+				elements::CustomSection::new(CONTRACT_METADATA_SECTION.into(), b"abi".to_vec())
+			));
+			let wasm = elements::serialize(module).unwrap();
+
+			assert_eq!(contract_metadata(&wasm), Ok(Some(b"abi".to_vec())));
+			let prefab = prepare_contract::<TestEnv>(&wasm, &Schedule::default()).unwrap();
+			assert_eq!(contract_metadata(&prefab.code), Ok(None));
+		}
+
+		#[test]
+		fn normalize_strips_custom_sections() {
+			let wasm = wat::parse_str(r#"
+			(module
+				(func $call (export "call"))
+				(func $deploy (export "deploy"))
+			)
+			"#).unwrap();
+			let mut module = elements::deserialize_buffer::<elements::Module>(&wasm).unwrap();
+			strip_custom_sections(&mut module);
+			let stripped = elements::serialize(module).unwrap();
+
+			// The text format emits a name section for the named functions.
+			assert!(stripped.len() < wasm.len());
+			assert_eq!(normalize(&wasm), Ok(stripped.clone()));
+			assert_eq!(normalize(&stripped), Ok(stripped));
+		}
+
+		#[test]
+		fn imported_functions_include_the_module() {
+			let wasm = wat::parse_str(r#"
+			(module
+				(import "seal0" "seal_input" (func (param i32 i32)))
+				(import "seal1" "seal_call" (func (param i32) (result i32)))
+				(import "env" "memory" (memory 1 1))
+				(import "other" "seal_input" (func (param i32 i32)))
+				(func (export "call"))
+				(func (export "deploy"))
+			)
+			"#).unwrap();
+			assert_eq!(
+				imported_functions(&wasm),
+				Ok(vec![
+					(b"seal0".to_vec(), b"seal_input".to_vec()),
+					(b"seal1".to_vec(), b"seal_call".to_vec()),
+				]),
+			);
+		}
+
+		#[test]
+		fn prepare_for_storage_reports_the_stripped_bytes() {
+			let wasm = wat::parse_str(r#"
+			(module
+				(func $call (export "call"))
+				(func $deploy (export "deploy"))
+			)
+			"#).unwrap();
+			let stripped = normalize(&wasm).unwrap();
+			let custom_len = (wasm.len() - stripped.len()) as u32;
+			let schedule = Schedule::default();
+
+			// Only the instrumented code is stripped.
+			let prepared = prepare_for_storage::<TestEnv>(wasm.clone(), &schedule, false).unwrap();
+			assert_eq!(prepared.pristine_code, wasm);
+			assert_eq!(prepared.bytes_saved, custom_len);
+			assert_eq!(
+				prepared.prefab_module.code,
+				prepare_contract::<TestEnv>(&wasm, &schedule).unwrap().code,
+			);
+
+			// The pristine code is stripped as well.
+			let prepared = prepare_for_storage::<TestEnv>(wasm, &schedule, true).unwrap();
+			assert_eq!(prepared.pristine_code, stripped);
+			assert_eq!(prepared.bytes_saved, 2 * custom_len);
+
+			// Nothing is saved if there is nothing to strip.
+			let prepared = prepare_for_storage::<TestEnv>(stripped.clone(), &schedule, true).unwrap();
+			assert_eq!(prepared.pristine_code, stripped);
+			assert_eq!(prepared.bytes_saved, 0);
+		}
+
 		prepare_test!(global_float,
 			r#"
 			(module
@@ -847,7 +1284,7 @@ mod tests {
 				(func (export "deploy"))
 			)
 			"#,
-			Err("use of floating point type in globals is forbidden")
+			Err(PrepareError::FloatUsage)
 		);
 
 		prepare_test!(local_float,
@@ -858,7 +1295,7 @@ mod tests {
 				(func (export "deploy"))
 			)
 			"#,
-			Err("use of floating point type in locals is forbidden")
+			Err(PrepareError::FloatUsage)
 		);
 
 		prepare_test!(param_float,
@@ -869,7 +1306,7 @@ mod tests {
 				(func (export "deploy"))
 			)
 			"#,
-			Err("use of floating point type in function types is forbidden")
+			Err(PrepareError::FloatUsage)
 		);
 
 		prepare_test!(result_float,
@@ -880,7 +1317,7 @@ mod tests {
 				(func (export "deploy"))
 			)
 			"#,
-			Err("use of floating point type in function types is forbidden")
+			Err(PrepareError::FloatUsage)
 		);
 	}
 }
@@ -18,7 +18,7 @@
 
 use crate::{Schedule, Trait, CodeHash, BalanceOf, Error};
 use crate::exec::{
-	Ext, ExecResult, ExecReturnValue, StorageKey, TopicOf, ReturnFlags, ExecError
+	Ext, ExecResult, ExecReturnValue, StorageKey, TopicOf, ReturnFlags, CallFlags, ExecError
 };
 use crate::gas::{Gas, GasMeter, Token, GasMeterResult};
 use crate::wasm::env_def::ConvertibleToWasm;
@@ -35,6 +35,8 @@ use sp_io::hashing::{
 	blake2_128,
 	sha2_256,
 };
+use sp_io::crypto::{secp256k1_ecdsa_recover_compressed, sr25519_verify, ed25519_verify};
+use sp_core::{sr25519, ed25519};
 
 /// Every error that can be returned to a contract when it calls any of the host functions.
 #[repr(u32)]
@@ -63,8 +65,24 @@ pub enum ReturnCode {
 	/// The contract that was called is either no contract at all (a plain account)
 	/// or is a tombstone.
 	NotCallable = 8,
+	/// The public key could not be recovered from the signature or the supplied public
+	/// key is invalid.
+	EcdsaRecoverFailed = 9,
+	/// The sr25519 signature is not valid for the supplied message and public key.
+	Sr25519VerifyFailed = 10,
+	/// The ed25519 signature is not valid for the supplied message and public key.
+	Ed25519VerifyFailed = 11,
+	/// The contract that was called is already on the call stack and did not allow
+	/// to be reentered.
+	ReentranceDenied = 12,
+	/// The contract tried to change state while being executed read-only.
+	StateChangeDenied = 13,
 }
 
+/// Returned by host functions in place of a size if there is no value, e.g. by the `seal2`
+/// version of `seal_set_storage` if there was no previous value.
+pub const SENTINEL: u32 = u32::max_value();
+
 impl ConvertibleToWasm for ReturnCode {
 	type NativeType = Self;
 	const VALUE_TYPE: ValueType = ValueType::I32;
@@ -141,6 +159,13 @@ impl<'a, E: Ext + 'a> Runtime<'a, E> {
 			trap_reason: None,
 		}
 	}
+
+	/// Charge the gas meter with the specified token.
+	///
+	/// Returns `Err(HostError)` if there is not enough gas.
+	fn charge_gas(&mut self, token: RuntimeToken) -> Result<(), sp_sandbox::HostError> {
+		charge_gas(self.gas_meter, self.schedule, &mut self.trap_reason, token)
+	}
 }
 
 /// Converts the sandbox result and the runtime state into the execution outcome.
@@ -211,12 +236,98 @@ pub enum RuntimeToken {
 	ReadMemory(u32),
 	/// The given number of bytes is written to the sandbox memory.
 	WriteMemory(u32),
-	/// The given number of bytes is read from the sandbox memory and
-	/// is returned as the return data buffer of the call.
-	ReturnData(u32),
-	/// (topic_count, data_bytes): A buffer of the given size is posted as an event indexed with the
-	/// given number of topics.
-	DepositEvent(u32, u32),
+	/// Weight of calling `seal_caller`.
+	Caller,
+	/// Weight of calling `seal_address`.
+	Address,
+	/// Weight of calling `seal_origin`.
+	Origin,
+	/// Weight of calling `seal_caller_is_origin`.
+	CallerIsOrigin,
+	/// Weight of calling `seal_is_contract`.
+	IsContract,
+	/// Weight of calling `seal_code_hash`.
+	CodeHash,
+	/// Weight of calling `seal_own_code_hash`.
+	OwnCodeHash,
+	/// Weight of calling `seal_gas_left`.
+	GasLeft,
+	/// Weight of calling `seal_balance`.
+	Balance,
+	/// Weight of calling `seal_value_transferred`.
+	ValueTransferred,
+	/// Weight of calling `seal_minimum_balance`.
+	MinimumBalance,
+	/// Weight of calling `seal_tombstone_deposit`.
+	TombstoneDeposit,
+	/// Weight of calling `seal_rent_allowance`.
+	RentAllowance,
+	/// Weight of calling `seal_block_number`.
+	BlockNumber,
+	/// Weight of calling `seal_now`.
+	Now,
+	/// Weight of calling `seal_weight_to_fee`.
+	WeightToFee,
+	/// Weight of calling `seal_input`.
+	Input,
+	/// Weight of calling `seal_return` with the given number of bytes returned.
+	Return(u32),
+	/// Weight of calling `seal_terminate`.
+	Terminate,
+	/// Weight of calling `seal_restore_to` with the given number of delta keys.
+	RestoreTo(u32),
+	/// Weight of calling `seal_random`.
+	Random,
+	/// Weight of calling `seal_deposit_event` with the given number of data bytes.
+	DepositEvent(u32),
+	/// Weight of indexing an event with the given number of topics.
+	DepositEventTopics(u32),
+	/// Weight of calling `seal_set_rent_allowance`.
+	SetRentAllowance,
+	/// Weight of calling `seal_set_storage` for the given number of bytes stored.
+	SetStorage(u32),
+	/// Weight of calling `seal_clear_storage`.
+	ClearStorage,
+	/// Weight of calling `seal_get_storage` without output weight.
+	GetStorageBase,
+	/// Weight of an item received via `seal_get_storage` for the given size.
+	GetStorageCopyOut(u32),
+	/// Weight of calling `seal_contains_storage`.
+	ContainsStorage,
+	/// Weight of calling `seal_take_storage` without output weight.
+	TakeStorageBase,
+	/// Weight of an item received via `seal_take_storage` for the given size.
+	TakeStorageCopyOut(u32),
+	/// Weight of calling `seal_storage_next_key` without output weight.
+	StorageNextKeyBase,
+	/// Weight of a key received via `seal_storage_next_key` for the given size.
+	StorageNextKeyCopyOut(u32),
+	/// Weight of a variable length key passed to a storage function for the given size.
+	StorageKey(u32),
+	/// Weight of calling `seal_transfer`.
+	Transfer,
+	/// Weight of calling `seal_call`.
+	Call,
+	/// Weight of calling `seal_instantiate`.
+	Instantiate,
+	/// Weight of calling `seal_println`.
+	Println,
+	/// Weight of calling `seal_hash_sha2_256` for the given input size.
+	HashSha256(u32),
+	/// Weight of calling `seal_hash_keccak_256` for the given input size.
+	HashKeccak256(u32),
+	/// Weight of calling `seal_hash_blake2_256` for the given input size.
+	HashBlake256(u32),
+	/// Weight of calling `seal_hash_blake2_128` for the given input size.
+	HashBlake128(u32),
+	/// Weight of calling `seal_ecdsa_recover`.
+	EcdsaRecovery,
+	/// Weight of calling `seal_ecdsa_to_eth_address`.
+	EcdsaToEthAddress,
+	/// Weight of calling `seal_sr25519_verify` for the given message size.
+	Sr25519Verify(u32),
+	/// Weight of calling `seal_ed25519_verify` for the given message size.
+	Ed25519Verify(u32),
 }
 
 impl<T: Trait> Token<T> for RuntimeToken {
@@ -224,6 +335,7 @@ impl<T: Trait> Token<T> for RuntimeToken {
 
 	fn calculate_amount(&self, metadata: &Schedule) -> Gas {
 		use self::RuntimeToken::*;
+		let s = &metadata.host_fn_weights;
 		let value = match *self {
 			Explicit(amount) => Some(amount.into()),
 			ReadMemory(byte_count) => metadata
@@ -232,34 +344,65 @@ impl<T: Trait> Token<T> for RuntimeToken {
 			WriteMemory(byte_count) => metadata
 				.sandbox_data_write_cost
 				.checked_mul(byte_count.into()),
-			ReturnData(byte_count) => metadata
-				.return_data_per_byte_cost
-				.checked_mul(byte_count.into()),
-			DepositEvent(topic_count, data_byte_count) => {
-				let data_cost = metadata
-					.event_data_per_byte_cost
-					.checked_mul(data_byte_count.into());
-
-				let topics_cost = metadata
-					.event_per_topic_cost
-					.checked_mul(topic_count.into());
-
-				data_cost
-					.and_then(|data_cost| {
-						topics_cost.and_then(|topics_cost| {
-							data_cost.checked_add(topics_cost)
-						})
-					})
-					.and_then(|data_and_topics_cost|
-						data_and_topics_cost.checked_add(metadata.event_base_cost)
-					)
-			},
+			Caller => Some(s.caller),
+			Address => Some(s.address),
+			Origin => Some(s.origin),
+			CallerIsOrigin => Some(s.caller_is_origin),
+			IsContract => Some(s.is_contract),
+			CodeHash => Some(s.code_hash),
+			OwnCodeHash => Some(s.own_code_hash),
+			GasLeft => Some(s.gas_left),
+			Balance => Some(s.balance),
+			ValueTransferred => Some(s.value_transferred),
+			MinimumBalance => Some(s.minimum_balance),
+			TombstoneDeposit => Some(s.tombstone_deposit),
+			RentAllowance => Some(s.rent_allowance),
+			BlockNumber => Some(s.block_number),
+			Now => Some(s.now),
+			WeightToFee => Some(s.weight_to_fee),
+			Input => Some(s.input),
+			Return(len) => with_per_item(s.r#return, s.return_per_byte, len),
+			Terminate => Some(s.terminate),
+			RestoreTo(delta) => with_per_item(s.restore_to, s.restore_to_per_delta, delta),
+			Random => Some(s.random),
+			DepositEvent(len) => with_per_item(s.deposit_event, s.deposit_event_per_byte, len),
+			DepositEventTopics(count) => s.deposit_event_per_topic.checked_mul(count.into()),
+			SetRentAllowance => Some(s.set_rent_allowance),
+			SetStorage(len) => with_per_item(s.set_storage, s.set_storage_per_byte, len),
+			ClearStorage => Some(s.clear_storage),
+			GetStorageBase => Some(s.get_storage),
+			GetStorageCopyOut(len) => s.get_storage_per_byte.checked_mul(len.into()),
+			ContainsStorage => Some(s.contains_storage),
+			TakeStorageBase => Some(s.take_storage),
+			TakeStorageCopyOut(len) => s.take_storage_per_byte.checked_mul(len.into()),
+			StorageNextKeyBase => Some(s.storage_next_key),
+			StorageNextKeyCopyOut(len) => s.storage_next_key_per_byte.checked_mul(len.into()),
+			StorageKey(len) => s.storage_key_per_byte.checked_mul(len.into()),
+			Transfer => Some(s.transfer),
+			Call => Some(s.call),
+			Instantiate => Some(s.instantiate),
+			Println => Some(s.println),
+			HashSha256(len) => with_per_item(s.hash_sha2_256, s.hash_sha2_256_per_byte, len),
+			HashKeccak256(len) => with_per_item(s.hash_keccak_256, s.hash_keccak_256_per_byte, len),
+			HashBlake256(len) => with_per_item(s.hash_blake2_256, s.hash_blake2_256_per_byte, len),
+			HashBlake128(len) => with_per_item(s.hash_blake2_128, s.hash_blake2_128_per_byte, len),
+			EcdsaRecovery => Some(s.ecdsa_recover),
+			EcdsaToEthAddress => Some(s.ecdsa_to_eth_address),
+			Sr25519Verify(len) => with_per_item(s.sr25519_verify, s.sr25519_verify_per_byte, len),
+			Ed25519Verify(len) => with_per_item(s.ed25519_verify, s.ed25519_verify_per_byte, len),
 		};
 
 		value.unwrap_or_else(|| Bounded::max_value())
 	}
 }
 
+/// Adds `count` times `per_item` to `base`. Returns `None` on overflow.
+fn with_per_item(base: Gas, per_item: Gas, count: u32) -> Option<Gas> {
+	per_item
+		.checked_mul(count.into())
+		.and_then(|cost| cost.checked_add(base))
+}
+
 /// Charge the gas meter with the specified token.
 ///
 /// Returns `Err(HostError)` if there is not enough gas.
@@ -414,6 +557,26 @@ fn write_sandbox_output<E: Ext>(
 	Ok(())
 }
 
+/// Read a storage key of variable length from the sandbox memory, consuming an appropriate
+/// amount of gas.
+///
+/// Besides the memory access every byte of the key is charged with
+/// `HostFnWeights::storage_key_per_byte`.
+///
+/// Returns `Err` if the key is longer than `Schedule::max_storage_key_len` or if reading
+/// it from the sandbox memory fails.
+fn read_storage_key<E: Ext>(
+	ctx: &mut Runtime<E>,
+	key_ptr: u32,
+	key_len: u32,
+) -> Result<Vec<u8>, sp_sandbox::HostError> {
+	if key_len > ctx.schedule.max_storage_key_len {
+		return Err(store_err(ctx, Error::<E::T>::StorageKeyTooLarge));
+	}
+	ctx.charge_gas(RuntimeToken::StorageKey(key_len))?;
+	read_sandbox_memory(ctx, key_ptr, key_len)
+}
+
 /// Stores a DispatchError returned from an Ext function into the trap_reason.
 ///
 /// This allows through supervisor generated errors to the caller.
@@ -425,6 +588,16 @@ fn store_err<E, Error>(ctx: &mut Runtime<E>, err: Error) -> sp_sandbox::HostErro
 	sp_sandbox::HostError
 }
 
+/// Stores the error of a diverging host function if it was caused by a read-only execution.
+///
+/// Those functions trap on any error. Only this error is passed on to the caller because
+/// it is caused by the caller rather than the failing contract.
+fn store_state_change_denied<E: Ext>(ctx: &mut Runtime<E>, err: DispatchError) {
+	if err == Error::<E::T>::StateChangeDenied.into() {
+		store_err(ctx, err);
+	}
+}
+
 /// Fallible conversion of `DispatchError` to `ReturnCode`.
 fn err_into_return_code<T: Trait>(from: DispatchError) -> Result<ReturnCode, DispatchError> {
 	use ReturnCode::*;
@@ -434,6 +607,8 @@ fn err_into_return_code<T: Trait>(from: DispatchError) -> Result<ReturnCode, Dis
 	let not_funded = Error::<T>::NewContractNotFunded.into();
 	let no_code = Error::<T>::CodeNotFound.into();
 	let invalid_contract = Error::<T>::NotCallable.into();
+	let reentrance_denied = Error::<T>::ReentranceDenied.into();
+	let state_change_denied = Error::<T>::StateChangeDenied.into();
 
 	match from {
 		x if x == below_sub => Ok(BelowSubsistenceThreshold),
@@ -441,6 +616,8 @@ fn err_into_return_code<T: Trait>(from: DispatchError) -> Result<ReturnCode, Dis
 		x if x == not_funded => Ok(NewContractNotFunded),
 		x if x == no_code => Ok(CodeNotFound),
 		x if x == invalid_contract => Ok(NotCallable),
+		x if x == reentrance_denied => Ok(ReentranceDenied),
+		x if x == state_change_denied => Ok(StateChangeDenied),
 		err => Err(err)
 	}
 }
@@ -455,6 +632,9 @@ fn exec_into_return_code<T: Trait>(from: ExecResult) -> Result<ReturnCode, Dispa
 	};
 
 	match (error, origin) {
+		// The callee was executed read-only and trapped because it tried to change state.
+		(err, Callee) if err == Error::<T>::StateChangeDenied.into() =>
+			Ok(ReturnCode::StateChangeDenied),
 		(_, Callee) => Ok(ReturnCode::CalleeTrapped),
 		(err, _) => err_into_return_code::<T>(err)
 	}
@@ -493,6 +673,87 @@ fn map_dispatch_result<T, E: Ext>(ctx: &mut Runtime<E>, result: Result<T, Dispat
 	}
 }
 
+/// Make a call to another contract as requested by one of the versions of `seal_call`.
+///
+/// See `seal_call` for the meaning of the arguments.
+fn call<E: Ext>(
+	ctx: &mut Runtime<E>,
+	flags: CallFlags,
+	callee_ptr: u32,
+	callee_len: u32,
+	gas: u64,
+	value_ptr: u32,
+	value_len: u32,
+	input_data_ptr: u32,
+	input_data_len: u32,
+	output_ptr: u32,
+	output_len_ptr: u32,
+) -> Result<ReturnCode, sp_sandbox::HostError> {
+	ctx.charge_gas(RuntimeToken::Call)?;
+	let callee: <<E as Ext>::T as frame_system::Trait>::AccountId =
+		read_sandbox_memory_as(ctx, callee_ptr, callee_len)?;
+	let value: BalanceOf<<E as Ext>::T> = read_sandbox_memory_as(ctx, value_ptr, value_len)?;
+	let input_data = if flags.contains(CallFlags::CLONE_INPUT) {
+		let input = match ctx.input_data.as_ref() {
+			Some(input) => input.clone(),
+			None => return Err(store_err(ctx, Error::<E::T>::InputForwarded)),
+		};
+		// Cloning the input is as expensive as reading it from the sandbox memory.
+		ctx.charge_gas(RuntimeToken::ReadMemory(input.len() as u32))?;
+		input
+	} else if flags.contains(CallFlags::FORWARD_INPUT) {
+		match ctx.input_data.take() {
+			Some(input) => input,
+			None => return Err(store_err(ctx, Error::<E::T>::InputForwarded)),
+		}
+	} else {
+		read_sandbox_memory(ctx, input_data_ptr, input_data_len)?
+	};
+
+	let nested_gas_limit = if gas == 0 {
+		ctx.gas_meter.gas_left()
+	} else {
+		gas.saturated_into()
+	};
+	let allows_reentry = flags.contains(CallFlags::ALLOW_REENTRY);
+	let read_only = flags.contains(CallFlags::READ_ONLY);
+	let ext = &mut ctx.ext;
+	let call_outcome = ctx.gas_meter.with_nested(nested_gas_limit, |nested_meter| {
+		match nested_meter {
+			Some(nested_meter) => {
+				ext.call(
+					&callee,
+					value,
+					nested_meter,
+					input_data,
+					allows_reentry,
+					read_only,
+				)
+			}
+			// there is not enough gas to allocate for the nested call.
+			None => Err(Error::<<E as Ext>::T>::OutOfGas.into()),
+		}
+	});
+
+	// A tail call returns the output of the callee to our caller. The trap mechanism is
+	// used to end the execution of the current contract just like `seal_return` does.
+	// A failed call resumes the current contract like any other call.
+	if flags.contains(CallFlags::TAIL_CALL) {
+		if let Ok(output) = call_outcome {
+			ctx.trap_reason = Some(TrapReason::Return(ReturnData {
+				flags: output.flags.bits(),
+				data: output.data,
+			}));
+			return Err(sp_sandbox::HostError);
+		}
+	}
+
+	if let Ok(output) = &call_outcome {
+		write_sandbox_output(ctx, output_ptr, output_len_ptr, &output.data, true)?;
+	}
+	map_exec_result(ctx, call_outcome)
+}
+
 // ***********************************************************
 // * AFTER MAKING A CHANGE MAKE SURE TO UPDATE COMPLEXITY.MD *
 // ***********************************************************
@@ -505,6 +766,13 @@ fn map_dispatch_result<T, E: Ext>(ctx: &mut Runtime<E>, result: Result<T, Dispat
 // Any input that leads to a out of bound error (reading or writing) or failing to decode
 // data passed to the supervisor will lead to a trap. This is not documented explicitly
 // for every function.
+//
+// Functions that change state fail with `StateChangeDenied` if the contract is executed
+// read-only (see `CallFlags::READ_ONLY`). They return `ReturnCode::StateChangeDenied` in
+// this case. Every such function has a version that returns a `ReturnCode`. Only the
+// versions without a `ReturnCode` and `seal_terminate` and `seal_restore_to`, which never
+// return, trap instead. The caller of the read-only call then receives
+// `ReturnCode::StateChangeDenied`.
 define_env!(Env, <E: Ext>,
 
 	// Account for used gas. Traps if gas used is greater than gas limit.
@@ -543,10 +811,39 @@ define_env!(Env, <E: Ext>,
 			// Bail out if value length exceeds the set maximum value size.
 			return Err(sp_sandbox::HostError);
 		}
+		ctx.charge_gas(RuntimeToken::SetStorage(value_len))?;
 		let mut key: StorageKey = [0; 32];
 		read_sandbox_memory_into_buf(ctx, key_ptr, &mut key)?;
 		let value = Some(read_sandbox_memory(ctx, value_ptr, value_len)?);
-		ctx.ext.set_storage(key, value);
+		ctx.ext.set_storage(&key, value).map_err(|err| store_err(ctx, err))?;
+		Ok(())
+	},
+
+	// Set the value at the given key in the contract storage.
+	//
+	// Equivalent to the `seal0` version but the key can be of variable length. A 32 byte key
+	// refers to the same entry as the `seal0` version does.
+	//
+	// # Parameters
+	//
+	// - `key_ptr`: pointer into the linear memory where the location to store the value is placed.
+	// - `key_len`: the length of the key in bytes.
+	// - `value_ptr`: pointer into the linear memory where the value to set is placed.
+	// - `value_len`: the length of the value in bytes.
+	//
+	// # Traps
+	//
+	// - If the key length exceeds the configured maximum key length.
+	// - If value length exceeds the configured maximum value length of a storage entry.
+	[seal1] seal_set_storage(ctx, key_ptr: u32, key_len: u32, value_ptr: u32, value_len: u32) => {
+		if value_len > ctx.ext.max_value_size() {
+			// Bail out if value length exceeds the set maximum value size.
+			return Err(sp_sandbox::HostError);
+		}
+		ctx.charge_gas(RuntimeToken::SetStorage(value_len))?;
+		let key = read_storage_key(ctx, key_ptr, key_len)?;
+		let value = Some(read_sandbox_memory(ctx, value_ptr, value_len)?);
+		ctx.ext.set_storage(&key, value).map_err(|err| store_err(ctx, err))?;
 		Ok(())
 	},
 
@@ -556,12 +853,156 @@ define_env!(Env, <E: Ext>,
 	//
 	// - `key_ptr`: pointer into the linear memory where the location to clear the value is placed.
 	seal_clear_storage(ctx, key_ptr: u32) => {
+		ctx.charge_gas(RuntimeToken::ClearStorage)?;
 		let mut key: StorageKey = [0; 32];
 		read_sandbox_memory_into_buf(ctx, key_ptr, &mut key)?;
-		ctx.ext.set_storage(key, None);
+		ctx.ext.set_storage(&key, None).map_err(|err| store_err(ctx, err))?;
+		Ok(())
+	},
+
+	// Clear the value at the given key in the contract storage.
+	//
+	// Equivalent to the `seal0` version but the key can be of variable length.
+	//
+	// # Parameters
+	//
+	// - `key_ptr`: pointer into the linear memory where the location to clear the value is placed.
+	// - `key_len`: the length of the key in bytes.
+	//
+	// # Traps
+	//
+	// - If the key length exceeds the configured maximum key length.
+	[seal1] seal_clear_storage(ctx, key_ptr: u32, key_len: u32) => {
+		ctx.charge_gas(RuntimeToken::ClearStorage)?;
+		let key = read_storage_key(ctx, key_ptr, key_len)?;
+		ctx.ext.set_storage(&key, None).map_err(|err| store_err(ctx, err))?;
 		Ok(())
 	},
 
+	// Set the value at the given key in the contract storage.
+	//
+	// Equivalent to the `seal1` version but returns the size of the previous value.
+	//
+	// # Parameters
+	//
+	// - `key_ptr`: pointer into the linear memory where the location to store the value is placed.
+	// - `key_len`: the length of the key in bytes.
+	// - `value_ptr`: pointer into the linear memory where the value to set is placed.
+	// - `value_len`: the length of the value in bytes.
+	//
+	// # Return Value
+	//
+	// Returns the size of the value that was previously stored under the key or `SENTINEL`
+	// (`u32::max_value()`) if there was no value.
+	//
+	// # Traps
+	//
+	// - If the key length exceeds the configured maximum key length.
+	// - If value length exceeds the configured maximum value length of a storage entry.
+	[seal2] seal_set_storage(
+		ctx,
+		key_ptr: u32,
+		key_len: u32,
+		value_ptr: u32,
+		value_len: u32
+	) -> u32 => {
+		if value_len > ctx.ext.max_value_size() {
+			// Bail out if value length exceeds the set maximum value size.
+			return Err(sp_sandbox::HostError);
+		}
+		ctx.charge_gas(RuntimeToken::SetStorage(value_len))?;
+		let key = read_storage_key(ctx, key_ptr, key_len)?;
+		let value = Some(read_sandbox_memory(ctx, value_ptr, value_len)?);
+		let prev_len = ctx.ext.set_storage(&key, value).map_err(|err| store_err(ctx, err))?;
+		Ok(prev_len.unwrap_or(SENTINEL))
+	},
+
+	// Clear the value at the given key in the contract storage.
+	//
+	// Equivalent to the `seal1` version but returns the size of the previous value.
+	//
+	// # Parameters
+	//
+	// - `key_ptr`: pointer into the linear memory where the location to clear the value is placed.
+	// - `key_len`: the length of the key in bytes.
+	//
+	// # Return Value
+	//
+	// Returns the size of the value that was previously stored under the key or `SENTINEL`
+	// (`u32::max_value()`) if there was no value.
+	//
+	// # Traps
+	//
+	// - If the key length exceeds the configured maximum key length.
+	[seal2] seal_clear_storage(ctx, key_ptr: u32, key_len: u32) -> u32 => {
+		ctx.charge_gas(RuntimeToken::ClearStorage)?;
+		let key = read_storage_key(ctx, key_ptr, key_len)?;
+		let prev_len = ctx.ext.set_storage(&key, None).map_err(|err| store_err(ctx, err))?;
+		Ok(prev_len.unwrap_or(SENTINEL))
+	},
+
+	// Set the value at the given key in the contract storage.
+	//
+	// Equivalent to the `seal1` version but returns a `ReturnCode` instead of trapping
+	// if the contract is executed read-only.
+	//
+	// # Parameters
+	//
+	// - `key_ptr`: pointer into the linear memory where the location to store the value is placed.
+	// - `key_len`: the length of the key in bytes.
+	// - `value_ptr`: pointer into the linear memory where the value to set is placed.
+	// - `value_len`: the length of the value in bytes.
+	//
+	// # Errors
+	//
+	// `ReturnCode::StateChangeDenied`
+	//
+	// # Traps
+	//
+	// - If the key length exceeds the configured maximum key length.
+	// - If value length exceeds the configured maximum value length of a storage entry.
+	[seal3] seal_set_storage(
+		ctx,
+		key_ptr: u32,
+		key_len: u32,
+		value_ptr: u32,
+		value_len: u32
+	) -> ReturnCode => {
+		if value_len > ctx.ext.max_value_size() {
+			// Bail out if value length exceeds the set maximum value size.
+			return Err(sp_sandbox::HostError);
+		}
+		ctx.charge_gas(RuntimeToken::SetStorage(value_len))?;
+		let key = read_storage_key(ctx, key_ptr, key_len)?;
+		let value = Some(read_sandbox_memory(ctx, value_ptr, value_len)?);
+		let result = ctx.ext.set_storage(&key, value).map(|_| ());
+		map_dispatch_result(ctx, result)
+	},
+
+	// Clear the value at the given key in the contract storage.
+	//
+	// Equivalent to the `seal1` version but returns a `ReturnCode` instead of trapping
+	// if the contract is executed read-only.
+	//
+	// # Parameters
+	//
+	// - `key_ptr`: pointer into the linear memory where the location to clear the value is placed.
+	// - `key_len`: the length of the key in bytes.
+	//
+	// # Errors
+	//
+	// `ReturnCode::StateChangeDenied`
+	//
+	// # Traps
+	//
+	// - If the key length exceeds the configured maximum key length.
+	[seal3] seal_clear_storage(ctx, key_ptr: u32, key_len: u32) -> ReturnCode => {
+		ctx.charge_gas(RuntimeToken::ClearStorage)?;
+		let key = read_storage_key(ctx, key_ptr, key_len)?;
+		let result = ctx.ext.set_storage(&key, None).map(|_| ());
+		map_dispatch_result(ctx, result)
+	},
+
 	// Retrieve the value under the given key from storage.
 	//
 	// # Parameters
@@ -575,9 +1016,48 @@ define_env!(Env, <E: Ext>,
 	//
 	// `ReturnCode::KeyNotFound`
 	seal_get_storage(ctx, key_ptr: u32, out_ptr: u32, out_len_ptr: u32) -> ReturnCode => {
+		ctx.charge_gas(RuntimeToken::GetStorageBase)?;
 		let mut key: StorageKey = [0; 32];
 		read_sandbox_memory_into_buf(ctx, key_ptr, &mut key)?;
 		if let Some(value) = ctx.ext.get_storage(&key) {
+			ctx.charge_gas(RuntimeToken::GetStorageCopyOut(value.len() as u32))?;
+			write_sandbox_output(ctx, out_ptr, out_len_ptr, &value, false)?;
+			Ok(ReturnCode::Success)
+		} else {
+			Ok(ReturnCode::KeyNotFound)
+		}
+	},
+
+	// Retrieve the value under the given key from storage.
+	//
+	// Equivalent to the `seal0` version but the key can be of variable length.
+	//
+	// # Parameters
+	//
+	// - `key_ptr`: pointer into the linear memory where the key of the requested value is placed.
+	// - `key_len`: the length of the key in bytes.
+	// - `out_ptr`: pointer to the linear memory where the value is written to.
+	// - `out_len_ptr`: in-out pointer into linear memory where the buffer length
+	//   is read from and the value length is written to.
+	//
+	// # Errors
+	//
+	// `ReturnCode::KeyNotFound`
+	//
+	// # Traps
+	//
+	// - If the key length exceeds the configured maximum key length.
+	[seal1] seal_get_storage(
+		ctx,
+		key_ptr: u32,
+		key_len: u32,
+		out_ptr: u32,
+		out_len_ptr: u32
+	) -> ReturnCode => {
+		ctx.charge_gas(RuntimeToken::GetStorageBase)?;
+		let key = read_storage_key(ctx, key_ptr, key_len)?;
+		if let Some(value) = ctx.ext.get_storage(&key) {
+			ctx.charge_gas(RuntimeToken::GetStorageCopyOut(value.len() as u32))?;
 			write_sandbox_output(ctx, out_ptr, out_len_ptr, &value, false)?;
 			Ok(ReturnCode::Success)
 		} else {
@@ -585,6 +1065,124 @@ define_env!(Env, <E: Ext>,
 		}
 	},
 
+	// Check whether a value is stored under the given key without reading it.
+	//
+	// # Parameters
+	//
+	// - `key_ptr`: pointer into the linear memory where the key of the requested value is placed.
+	// - `key_len`: the length of the key in bytes.
+	// - `out_len_ptr`: pointer into linear memory where the size of the value is written to.
+	//   Nothing is written if there is no value.
+	//
+	// # Errors
+	//
+	// `ReturnCode::KeyNotFound`
+	//
+	// # Traps
+	//
+	// - If the key length exceeds the configured maximum key length.
+	seal_contains_storage(ctx, key_ptr: u32, key_len: u32, out_len_ptr: u32) -> ReturnCode => {
+		ctx.charge_gas(RuntimeToken::ContainsStorage)?;
+		let key = read_storage_key(ctx, key_ptr, key_len)?;
+		if let Some(len) = ctx.ext.get_storage_size(&key) {
+			write_sandbox_memory(ctx, out_len_ptr, &len.encode())?;
+			Ok(ReturnCode::Success)
+		} else {
+			Ok(ReturnCode::KeyNotFound)
+		}
+	},
+
+	// Retrieve the value under the given key from storage and remove it.
+	//
+	// # Parameters
+	//
+	// - `key_ptr`: pointer into the linear memory where the key of the requested value is placed.
+	// - `key_len`: the length of the key in bytes.
+	// - `out_ptr`: pointer to the linear memory where the value is written to.
+	// - `out_len_ptr`: in-out pointer into linear memory where the buffer length
+	//   is read from and the value length is written to.
+	//
+	// # Errors
+	//
+	// `ReturnCode::KeyNotFound`
+	//
+	// # Traps
+	//
+	// - If the key length exceeds the configured maximum key length.
+	// - If the output buffer is too small for the value. The value is not removed then.
+	seal_take_storage(
+		ctx,
+		key_ptr: u32,
+		key_len: u32,
+		out_ptr: u32,
+		out_len_ptr: u32
+	) -> ReturnCode => {
+		ctx.charge_gas(RuntimeToken::TakeStorageBase)?;
+		let key = read_storage_key(ctx, key_ptr, key_len)?;
+		match ctx.ext.take_storage(&key) {
+			Ok(Some(value)) => {
+				ctx.charge_gas(RuntimeToken::TakeStorageCopyOut(value.len() as u32))?;
+				write_sandbox_output(ctx, out_ptr, out_len_ptr, &value, false)?;
+				Ok(ReturnCode::Success)
+			},
+			Ok(None) => Ok(ReturnCode::KeyNotFound),
+			Err(err) => map_dispatch_result(ctx, Err::<(), _>(err)),
+		}
+	},
+
+	// Retrieve the next key of the contract storage that starts with the given prefix.
+	//
+	// Keys are visited in ascending order. Supplying the previously returned key as `after`
+	// allows a contract to iterate all keys under a prefix one step at a time. This is only
+	// available to contracts which were instantiated with `StorageMode::Unhashed`.
+	//
+	// # Parameters
+	//
+	// - `prefix_ptr`: pointer into the linear memory where the prefix is placed.
+	// - `prefix_len`: the length of the prefix in bytes.
+	// - `after_ptr`: pointer into the linear memory where the key after which to continue
+	//   is placed. `SENTINEL` (`u32::max_value()`) requests the first key with the prefix.
+	// - `after_len`: the length of the key after which to continue in bytes.
+	// - `out_ptr`: pointer to the linear memory where the next key is written to.
+	// - `out_len_ptr`: in-out pointer into linear memory where the buffer length
+	//   is read from and the key length is written to.
+	//
+	// # Errors
+	//
+	// `ReturnCode::KeyNotFound`: There is no further key with the given prefix.
+	//
+	// # Traps
+	//
+	// - If the prefix or the key after which to continue exceed the configured maximum
+	//   key length.
+	// - If the contract wasn't instantiated with `StorageMode::Unhashed`.
+	seal_storage_next_key(
+		ctx,
+		prefix_ptr: u32,
+		prefix_len: u32,
+		after_ptr: u32,
+		after_len: u32,
+		out_ptr: u32,
+		out_len_ptr: u32
+	) -> ReturnCode => {
+		ctx.charge_gas(RuntimeToken::StorageNextKeyBase)?;
+		let prefix = read_storage_key(ctx, prefix_ptr, prefix_len)?;
+		let after = if after_ptr == SENTINEL {
+			None
+		} else {
+			Some(read_storage_key(ctx, after_ptr, after_len)?)
+		};
+		let next = ctx.ext.next_storage_key(&prefix, after.as_ref().map(|key| &key[..]))
+			.map_err(|err| store_err(ctx, err))?;
+		if let Some(key) = next {
+			ctx.charge_gas(RuntimeToken::StorageNextKeyCopyOut(key.len() as u32))?;
+			write_sandbox_output(ctx, out_ptr, out_len_ptr, &key, false)?;
+			Ok(ReturnCode::Success)
+		} else {
+			Ok(ReturnCode::KeyNotFound)
+		}
+	},
+
 	// Transfer some value to another account.
 	//
 	// # Parameters
@@ -607,6 +1205,7 @@ define_env!(Env, <E: Ext>,
 		value_ptr: u32,
 		value_len: u32
 	) -> ReturnCode => {
+		ctx.charge_gas(RuntimeToken::Transfer)?;
 		let callee: <<E as Ext>::T as frame_system::Trait>::AccountId =
 			read_sandbox_memory_as(ctx, account_ptr, account_len)?;
 		let value: BalanceOf<<E as Ext>::T> =
@@ -622,6 +1221,9 @@ define_env!(Env, <E: Ext>,
 	// The copy of the output buffer can be skipped by supplying the sentinel value
 	// of `u32::max_value()` to `output_ptr`.
 	//
+	// The callee is allowed to reenter the calling contract. Use `seal1::seal_call` in order
+	// to deny reentrancy.
+	//
 	// # Parameters
 	//
 	// - callee_ptr: a pointer to the address of the callee contract.
@@ -659,36 +1261,91 @@ define_env!(Env, <E: Ext>,
 		output_ptr: u32,
 		output_len_ptr: u32
 	) -> ReturnCode => {
-		let callee: <<E as Ext>::T as frame_system::Trait>::AccountId =
-			read_sandbox_memory_as(ctx, callee_ptr, callee_len)?;
-		let value: BalanceOf<<E as Ext>::T> = read_sandbox_memory_as(ctx, value_ptr, value_len)?;
-		let input_data = read_sandbox_memory(ctx, input_data_ptr, input_data_len)?;
+		call(
+			ctx,
+			CallFlags::ALLOW_REENTRY,
+			callee_ptr,
+			callee_len,
+			gas,
+			value_ptr,
+			value_len,
+			input_data_ptr,
+			input_data_len,
+			output_ptr,
+			output_len_ptr,
+		)
+	},
 
-		let nested_gas_limit = if gas == 0 {
-			ctx.gas_meter.gas_left()
-		} else {
-			gas.saturated_into()
+	// Make a call to another contract.
+	//
+	// Identical to `seal0::seal_call` except that it takes a set of flags which customize
+	// the behaviour of the call. In contrast to `seal0::seal_call`, which implicitly passes
+	// `ALLOW_REENTRY`, the callee is not allowed to reenter the calling contract unless
+	// `ALLOW_REENTRY` is passed.
+	//
+	// # Parameters
+	//
+	// - flags: a bitfield of `CallFlags` which can be used to customize the call:
+	//   --- lsb ---
+	//   bit 0      : FORWARD_INPUT - Forward the input of this contract to the callee.
+	//                The input buffer is ignored and the input is no longer available to
+	//                this contract afterwards.
+	//   bit 1      : CLONE_INPUT - Same as FORWARD_INPUT but the input stays available.
+	//   bit 2      : TAIL_CALL - Return the output of the callee to our caller instead of
+	//                resuming this contract. The output buffer is ignored in this case.
+	//                If the call fails this contract resumes and receives the error code.
+	//   bit 3      : ALLOW_REENTRY - Allow the callee to reenter this contract.
+	//   bit 4      : READ_ONLY - Deny the callee and all contracts called by it to change
+	//                any state. Value can't be transferred along with a read-only call.
+	//   bit [5, 31]: Reserved for future use.
+	//   --- msb ---
+	//   Using a reserved bit or setting FORWARD_INPUT and CLONE_INPUT together triggers
+	//   a trap.
+	// - All other parameters are identical to `seal0::seal_call`.
+	//
+	// # Errors
+	//
+	// Same as for `seal0::seal_call` with the addition of:
+	//
+	// `ReturnCode::ReentranceDenied`
+	// `ReturnCode::StateChangeDenied`: Value was transferred along with a read-only call
+	//   or the read-only callee tried to change state.
+	//
+	// # Traps
+	//
+	// - The input was requested to be forwarded or cloned but was already forwarded or
+	//   read by `seal_input`.
+	[seal1] seal_call(
+		ctx,
+		flags: u32,
+		callee_ptr: u32,
+		callee_len: u32,
+		gas: u64,
+		value_ptr: u32,
+		value_len: u32,
+		input_data_ptr: u32,
+		input_data_len: u32,
+		output_ptr: u32,
+		output_len_ptr: u32
+	) -> ReturnCode => {
+		let flags = match CallFlags::from_bits(flags) {
+			Some(flags)
+				if !flags.contains(CallFlags::FORWARD_INPUT | CallFlags::CLONE_INPUT) => flags,
+			_ => return Err(store_err(ctx, Error::<<E as Ext>::T>::InvalidCallFlags)),
 		};
-		let ext = &mut ctx.ext;
-		let call_outcome = ctx.gas_meter.with_nested(nested_gas_limit, |nested_meter| {
-			match nested_meter {
-				Some(nested_meter) => {
-					ext.call(
-						&callee,
-						value,
-						nested_meter,
-						input_data,
-					)
-				}
-				// there is not enough gas to allocate for the nested call.
-				None => Err(Error::<<E as Ext>::T>::OutOfGas.into()),
-			}
-		});
-
-		if let Ok(output) = &call_outcome {
-			write_sandbox_output(ctx, output_ptr, output_len_ptr, &output.data, true)?;
-		}
-		map_exec_result(ctx, call_outcome)
+		call(
+			ctx,
+			flags,
+			callee_ptr,
+			callee_len,
+			gas,
+			value_ptr,
+			value_len,
+			input_data_ptr,
+			input_data_len,
+			output_ptr,
+			output_len_ptr,
+		)
 	},
 
 	// Instantiate a contract with the specified code hash.
@@ -748,6 +1405,7 @@ define_env!(Env, <E: Ext>,
 		output_ptr: u32,
 		output_len_ptr: u32
 	) -> ReturnCode => {
+		ctx.charge_gas(RuntimeToken::Instantiate)?;
 		let code_hash: CodeHash<<E as Ext>::T> =
 			read_sandbox_memory_as(ctx, code_hash_ptr, code_hash_len)?;
 		let value: BalanceOf<<E as Ext>::T> = read_sandbox_memory_as(ctx, value_ptr, value_len)?;
@@ -803,16 +1461,19 @@ define_env!(Env, <E: Ext>,
 		beneficiary_ptr: u32,
 		beneficiary_len: u32
 	) => {
+		ctx.charge_gas(RuntimeToken::Terminate)?;
 		let beneficiary: <<E as Ext>::T as frame_system::Trait>::AccountId =
 			read_sandbox_memory_as(ctx, beneficiary_ptr, beneficiary_len)?;
 
-		if let Ok(_) = ctx.ext.terminate(&beneficiary, ctx.gas_meter) {
-			ctx.trap_reason = Some(TrapReason::Termination);
+		match ctx.ext.terminate(&beneficiary, ctx.gas_meter) {
+			Ok(_) => ctx.trap_reason = Some(TrapReason::Termination),
+			Err(err) => store_state_change_denied(ctx, err),
 		}
 		Err(sp_sandbox::HostError)
 	},
 
 	seal_input(ctx, buf_ptr: u32, buf_len_ptr: u32) => {
+		ctx.charge_gas(RuntimeToken::Input)?;
 		if let Some(input) = ctx.input_data.take() {
 			write_sandbox_output(ctx, buf_ptr, buf_len_ptr, &input, false)
 		} else {
@@ -838,13 +1499,7 @@ define_env!(Env, <E: Ext>,
 	//
 	// Using a reserved bit triggers a trap.
 	seal_return(ctx, flags: u32, data_ptr: u32, data_len: u32) => {
-		charge_gas(
-			ctx.gas_meter,
-			ctx.schedule,
-			&mut ctx.trap_reason,
-			RuntimeToken::ReturnData(data_len)
-		)?;
-
+		ctx.charge_gas(RuntimeToken::Return(data_len))?;
 		ctx.trap_reason = Some(TrapReason::Return(ReturnData {
 			flags,
 			data: read_sandbox_memory(ctx, data_ptr, data_len)?,
@@ -867,6 +1522,7 @@ define_env!(Env, <E: Ext>,
 	// extrinsic will be returned. Otherwise, if this call is initiated by another contract then the
 	// address of the contract will be returned. The value is encoded as T::AccountId.
 	seal_caller(ctx, out_ptr: u32, out_len_ptr: u32) => {
+		ctx.charge_gas(RuntimeToken::Caller)?;
 		write_sandbox_output(ctx, out_ptr, out_len_ptr, &ctx.ext.caller().encode(), false)
 	},
 
@@ -877,9 +1533,100 @@ define_env!(Env, <E: Ext>,
 	// `out_ptr`. This call overwrites it with the size of the value. If the available
 	// space at `out_ptr` is less than the size of the value a trap is triggered.
 	seal_address(ctx, out_ptr: u32, out_len_ptr: u32) => {
+		ctx.charge_gas(RuntimeToken::Address)?;
 		write_sandbox_output(ctx, out_ptr, out_len_ptr, &ctx.ext.address().encode(), false)
 	},
 
+	// Stores the address of the origin of the call stack into the supplied buffer.
+	//
+	// The value is stored to linear memory at the address pointed to by `out_ptr`.
+	// `out_len_ptr` must point to a u32 value that describes the available space at
+	// `out_ptr`. This call overwrites it with the size of the value. If the available
+	// space at `out_ptr` is less than the size of the value a trap is triggered.
+	//
+	// The origin is the account that signed the extrinsic which started the execution.
+	// Contrary to `seal_caller` it is the same for every contract in the call stack. The
+	// value is encoded as T::AccountId.
+	seal_origin(ctx, out_ptr: u32, out_len_ptr: u32) => {
+		ctx.charge_gas(RuntimeToken::Origin)?;
+		write_sandbox_output(ctx, out_ptr, out_len_ptr, &ctx.ext.origin().encode(), false)
+	},
+
+	// Checks whether the caller of the current contract is the origin of the call stack.
+	//
+	// This is the case if the current contract was called directly by an extrinsic rather
+	// than by another contract.
+	//
+	// # Return Value
+	//
+	// Returns `1` if the caller is the origin and `0` otherwise.
+	seal_caller_is_origin(ctx) -> u32 => {
+		ctx.charge_gas(RuntimeToken::CallerIsOrigin)?;
+		Ok(ctx.ext.caller_is_origin() as u32)
+	},
+
+	// Checks whether the specified account is a contract.
+	//
+	// # Parameters
+	//
+	// - account_ptr: a pointer to the address of the account to check.
+	//   Should be decodable as an `T::AccountId`. Traps otherwise.
+	// - account_len: length of the address buffer.
+	//
+	// # Return Value
+	//
+	// Returns `1` if the account is an alive contract and `0` otherwise. Tombstones
+	// are not considered contracts.
+	seal_is_contract(ctx, account_ptr: u32, account_len: u32) -> u32 => {
+		ctx.charge_gas(RuntimeToken::IsContract)?;
+		let account: <<E as Ext>::T as frame_system::Trait>::AccountId =
+			read_sandbox_memory_as(ctx, account_ptr, account_len)?;
+		Ok(ctx.ext.is_contract(&account) as u32)
+	},
+
+	// Stores the code hash of the specified contract into the supplied buffer.
+	//
+	// # Parameters
+	//
+	// - account_ptr: a pointer to the address of the contract.
+	//   Should be decodable as an `T::AccountId`. Traps otherwise.
+	// - account_len: length of the address buffer.
+	// - out_ptr: pointer to the linear memory where the code hash is placed.
+	// - out_len_ptr: in-out pointer to where the length of the buffer is read from
+	//   and the code hash length is written to.
+	//
+	// # Errors
+	//
+	// `ReturnCode::NotCallable`: The account is not an alive contract.
+	seal_code_hash(
+		ctx,
+		account_ptr: u32,
+		account_len: u32,
+		out_ptr: u32,
+		out_len_ptr: u32
+	) -> ReturnCode => {
+		ctx.charge_gas(RuntimeToken::CodeHash)?;
+		let account: <<E as Ext>::T as frame_system::Trait>::AccountId =
+			read_sandbox_memory_as(ctx, account_ptr, account_len)?;
+		if let Some(code_hash) = ctx.ext.code_hash(&account) {
+			write_sandbox_output(ctx, out_ptr, out_len_ptr, &code_hash.encode(), false)?;
+			Ok(ReturnCode::Success)
+		} else {
+			Ok(ReturnCode::NotCallable)
+		}
+	},
+
+	// Stores the code hash of the current contract into the supplied buffer.
+	//
+	// The value is stored to linear memory at the address pointed to by `out_ptr`.
+	// `out_len_ptr` must point to a u32 value that describes the available space at
+	// `out_ptr`. This call overwrites it with the size of the value. If the available
+	// space at `out_ptr` is less than the size of the value a trap is triggered.
+	seal_own_code_hash(ctx, out_ptr: u32, out_len_ptr: u32) => {
+		ctx.charge_gas(RuntimeToken::OwnCodeHash)?;
+		write_sandbox_output(ctx, out_ptr, out_len_ptr, &ctx.ext.own_code_hash().encode(), false)
+	},
+
 	// Stores the price for the specified amount of gas into the supplied buffer.
 	//
 	// The value is stored to linear memory at the address pointed to by `out_ptr`.
@@ -894,6 +1641,7 @@ define_env!(Env, <E: Ext>,
 	// It is recommended to avoid specifying very small values for `gas` as the prices for a single
 	// gas can be smaller than one.
 	seal_weight_to_fee(ctx, gas: u64, out_ptr: u32, out_len_ptr: u32) => {
+		ctx.charge_gas(RuntimeToken::WeightToFee)?;
 		write_sandbox_output(
 			ctx, out_ptr, out_len_ptr, &ctx.ext.get_weight_price(gas).encode(), false
 		)
@@ -908,6 +1656,7 @@ define_env!(Env, <E: Ext>,
 	//
 	// The data is encoded as Gas.
 	seal_gas_left(ctx, out_ptr: u32, out_len_ptr: u32) => {
+		ctx.charge_gas(RuntimeToken::GasLeft)?;
 		write_sandbox_output(ctx, out_ptr, out_len_ptr, &ctx.gas_meter.gas_left().encode(), false)
 	},
 
@@ -920,6 +1669,7 @@ define_env!(Env, <E: Ext>,
 	//
 	// The data is encoded as T::Balance.
 	seal_balance(ctx, out_ptr: u32, out_len_ptr: u32) => {
+		ctx.charge_gas(RuntimeToken::Balance)?;
 		write_sandbox_output(ctx, out_ptr, out_len_ptr, &ctx.ext.balance().encode(), false)
 	},
 
@@ -932,6 +1682,7 @@ define_env!(Env, <E: Ext>,
 	//
 	// The data is encoded as T::Balance.
 	seal_value_transferred(ctx, out_ptr: u32, out_len_ptr: u32) => {
+		ctx.charge_gas(RuntimeToken::ValueTransferred)?;
 		write_sandbox_output(
 			ctx, out_ptr, out_len_ptr, &ctx.ext.value_transferred().encode(), false
 		)
@@ -946,6 +1697,7 @@ define_env!(Env, <E: Ext>,
 	//
 	// The data is encoded as T::Hash.
 	seal_random(ctx, subject_ptr: u32, subject_len: u32, out_ptr: u32, out_len_ptr: u32) => {
+		ctx.charge_gas(RuntimeToken::Random)?;
 		// The length of a subject can't exceed `max_subject_len`.
 		if subject_len > ctx.schedule.max_subject_len {
 			return Err(sp_sandbox::HostError);
@@ -963,6 +1715,7 @@ define_env!(Env, <E: Ext>,
 	// `out_ptr`. This call overwrites it with the size of the value. If the available
 	// space at `out_ptr` is less than the size of the value a trap is triggered.
 	seal_now(ctx, out_ptr: u32, out_len_ptr: u32) => {
+		ctx.charge_gas(RuntimeToken::Now)?;
 		write_sandbox_output(ctx, out_ptr, out_len_ptr, &ctx.ext.now().encode(), false)
 	},
 
@@ -970,6 +1723,7 @@ define_env!(Env, <E: Ext>,
 	//
 	// The data is encoded as T::Balance.
 	seal_minimum_balance(ctx, out_ptr: u32, out_len_ptr: u32) => {
+		ctx.charge_gas(RuntimeToken::MinimumBalance)?;
 		write_sandbox_output(ctx, out_ptr, out_len_ptr, &ctx.ext.minimum_balance().encode(), false)
 	},
 
@@ -989,6 +1743,7 @@ define_env!(Env, <E: Ext>,
 	// below the sum of existential deposit and the tombstone deposit. The sum
 	// is commonly referred as subsistence threshold in code.
 	seal_tombstone_deposit(ctx, out_ptr: u32, out_len_ptr: u32) => {
+		ctx.charge_gas(RuntimeToken::TombstoneDeposit)?;
 		write_sandbox_output(
 			ctx, out_ptr, out_len_ptr, &ctx.ext.tombstone_deposit().encode(), false
 		)
@@ -1031,6 +1786,7 @@ define_env!(Env, <E: Ext>,
 		delta_ptr: u32,
 		delta_count: u32
 	) => {
+		ctx.charge_gas(RuntimeToken::RestoreTo(delta_count))?;
 		let dest: <<E as Ext>::T as frame_system::Trait>::AccountId =
 			read_sandbox_memory_as(ctx, dest_ptr, dest_len)?;
 		let code_hash: CodeHash<<E as Ext>::T> =
@@ -1058,13 +1814,14 @@ define_env!(Env, <E: Ext>,
 			delta
 		};
 
-		if let Ok(()) = ctx.ext.restore_to(
+		match ctx.ext.restore_to(
 			dest,
 			code_hash,
 			rent_allowance,
 			delta,
 		) {
-			ctx.trap_reason = Some(TrapReason::Restoration);
+			Ok(()) => ctx.trap_reason = Some(TrapReason::Restoration),
+			Err(err) => store_state_change_denied(ctx, err),
 		}
 		Err(sp_sandbox::HostError)
 	},
@@ -1078,6 +1835,7 @@ define_env!(Env, <E: Ext>,
 	// - data_ptr - a pointer to a raw data buffer which will saved along the event.
 	// - data_len - the length of the data buffer.
 	seal_deposit_event(ctx, topics_ptr: u32, topics_len: u32, data_ptr: u32, data_len: u32) => {
+		ctx.charge_gas(RuntimeToken::DepositEvent(data_len))?;
 		let mut topics: Vec::<TopicOf<<E as Ext>::T>> = match topics_len {
 			0 => Vec::new(),
 			_ => read_sandbox_memory_as(ctx, topics_ptr, topics_len)?,
@@ -1095,30 +1853,80 @@ define_env!(Env, <E: Ext>,
 
 		let event_data = read_sandbox_memory(ctx, data_ptr, data_len)?;
 
-		charge_gas(
-			ctx.gas_meter,
-			ctx.schedule,
-			&mut ctx.trap_reason,
-			RuntimeToken::DepositEvent(topics.len() as u32, data_len)
-		)?;
-		ctx.ext.deposit_event(topics, event_data);
+		ctx.charge_gas(RuntimeToken::DepositEventTopics(topics.len() as u32))?;
+		ctx.ext.deposit_event(topics, event_data).map_err(|err| store_err(ctx, err))?;
 
 		Ok(())
 	},
 
+	// Deposit a contract event with the data buffer and optional list of topics.
+	//
+	// Equivalent to the `seal0` version but returns a `ReturnCode` instead of trapping
+	// if the contract is executed read-only.
+	//
+	// # Errors
+	//
+	// `ReturnCode::StateChangeDenied`
+	[seal1] seal_deposit_event(
+		ctx,
+		topics_ptr: u32,
+		topics_len: u32,
+		data_ptr: u32,
+		data_len: u32
+	) -> ReturnCode => {
+		ctx.charge_gas(RuntimeToken::DepositEvent(data_len))?;
+		let mut topics: Vec::<TopicOf<<E as Ext>::T>> = match topics_len {
+			0 => Vec::new(),
+			_ => read_sandbox_memory_as(ctx, topics_ptr, topics_len)?,
+		};
+
+		// If there are more than `max_event_topics`, then trap.
+		if topics.len() > ctx.schedule.max_event_topics as usize {
+			return Err(sp_sandbox::HostError);
+		}
+
+		// Check for duplicate topics. If there are any, then trap.
+		if has_duplicates(&mut topics) {
+			return Err(sp_sandbox::HostError);
+		}
+
+		let event_data = read_sandbox_memory(ctx, data_ptr, data_len)?;
+
+		ctx.charge_gas(RuntimeToken::DepositEventTopics(topics.len() as u32))?;
+		let result = ctx.ext.deposit_event(topics, event_data);
+		map_dispatch_result(ctx, result)
+	},
+
 	// Set rent allowance of the contract
 	//
 	// - value_ptr: a pointer to the buffer with value, how much to allow for rent
 	//   Should be decodable as a `T::Balance`. Traps otherwise.
 	// - value_len: length of the value buffer.
 	seal_set_rent_allowance(ctx, value_ptr: u32, value_len: u32) => {
+		ctx.charge_gas(RuntimeToken::SetRentAllowance)?;
 		let value: BalanceOf<<E as Ext>::T> =
 			read_sandbox_memory_as(ctx, value_ptr, value_len)?;
-		ctx.ext.set_rent_allowance(value);
+		ctx.ext.set_rent_allowance(value).map_err(|err| store_err(ctx, err))?;
 
 		Ok(())
 	},
 
+	// Set rent allowance of the contract.
+	//
+	// Equivalent to the `seal0` version but returns a `ReturnCode` instead of trapping
+	// if the contract is executed read-only.
+	//
+	// # Errors
+	//
+	// `ReturnCode::StateChangeDenied`
+	[seal1] seal_set_rent_allowance(ctx, value_ptr: u32, value_len: u32) -> ReturnCode => {
+		ctx.charge_gas(RuntimeToken::SetRentAllowance)?;
+		let value: BalanceOf<<E as Ext>::T> =
+			read_sandbox_memory_as(ctx, value_ptr, value_len)?;
+		let result = ctx.ext.set_rent_allowance(value);
+		map_dispatch_result(ctx, result)
+	},
+
 	// Stores the rent allowance into the supplied buffer.
 	//
 	// The value is stored to linear memory at the address pointed to by `out_ptr`.
@@ -1128,6 +1936,7 @@ define_env!(Env, <E: Ext>,
 	//
 	// The data is encoded as T::Balance.
 	seal_rent_allowance(ctx, out_ptr: u32, out_len_ptr: u32) => {
+		ctx.charge_gas(RuntimeToken::RentAllowance)?;
 		write_sandbox_output(ctx, out_ptr, out_len_ptr, &ctx.ext.rent_allowance().encode(), false)
 	},
 
@@ -1135,6 +1944,7 @@ define_env!(Env, <E: Ext>,
 	// Only available on `--dev` chains.
 	// This function may be removed at any time, superseded by a more general contract debugging feature.
 	seal_println(ctx, str_ptr: u32, str_len: u32) => {
+		ctx.charge_gas(RuntimeToken::Println)?;
 		let data = read_sandbox_memory(ctx, str_ptr, str_len)?;
 		if let Ok(utf8) = core::str::from_utf8(&data) {
 			sp_runtime::print(utf8);
@@ -1149,6 +1959,7 @@ define_env!(Env, <E: Ext>,
 	// `out_ptr`. This call overwrites it with the size of the value. If the available
 	// space at `out_ptr` is less than the size of the value a trap is triggered.
 	seal_block_number(ctx, out_ptr: u32, out_len_ptr: u32) => {
+		ctx.charge_gas(RuntimeToken::BlockNumber)?;
 		write_sandbox_output(ctx, out_ptr, out_len_ptr, &ctx.ext.block_number().encode(), false)
 	},
 
@@ -1173,6 +1984,7 @@ define_env!(Env, <E: Ext>,
 	//                 data is placed. The function will write the result
 	//                 directly into this buffer.
 	seal_hash_sha2_256(ctx, input_ptr: u32, input_len: u32, output_ptr: u32) => {
+		ctx.charge_gas(RuntimeToken::HashSha256(input_len))?;
 		compute_hash_on_intermediate_buffer(ctx, sha2_256, input_ptr, input_len, output_ptr)
 	},
 
@@ -1197,6 +2009,7 @@ define_env!(Env, <E: Ext>,
 	//                 data is placed. The function will write the result
 	//                 directly into this buffer.
 	seal_hash_keccak_256(ctx, input_ptr: u32, input_len: u32, output_ptr: u32) => {
+		ctx.charge_gas(RuntimeToken::HashKeccak256(input_len))?;
 		compute_hash_on_intermediate_buffer(ctx, keccak_256, input_ptr, input_len, output_ptr)
 	},
 
@@ -1221,6 +2034,7 @@ define_env!(Env, <E: Ext>,
 	//                 data is placed. The function will write the result
 	//                 directly into this buffer.
 	seal_hash_blake2_256(ctx, input_ptr: u32, input_len: u32, output_ptr: u32) => {
+		ctx.charge_gas(RuntimeToken::HashBlake256(input_len))?;
 		compute_hash_on_intermediate_buffer(ctx, blake2_256, input_ptr, input_len, output_ptr)
 	},
 
@@ -1245,10 +2059,171 @@ define_env!(Env, <E: Ext>,
 	//                 data is placed. The function will write the result
 	//                 directly into this buffer.
 	seal_hash_blake2_128(ctx, input_ptr: u32, input_len: u32, output_ptr: u32) => {
+		ctx.charge_gas(RuntimeToken::HashBlake128(input_len))?;
 		compute_hash_on_intermediate_buffer(ctx, blake2_128, input_ptr, input_len, output_ptr)
 	},
+
+	// Recover the ECDSA public key from the given message hash and signature.
+	//
+	// Writes the public key into the given output buffer.
+	// Assumes the secp256k1 curve.
+	//
+	// # Parameters
+	//
+	// - `signature_ptr`: the pointer into the linear memory where the signature
+	//                    is placed. Should be 65 bytes long: the `r` and `s` values
+	//                    followed by the recovery id.
+	// - `message_hash_ptr`: the pointer into the linear memory where the message
+	//                       hash is placed. Should be 32 bytes long.
+	// - `output_ptr`: the pointer into the linear memory where the output
+	//                 data is placed. The buffer should be 33 bytes long.
+	//                 The function will write the result directly into this buffer.
+	//
+	// # Errors
+	//
+	// `ReturnCode::EcdsaRecoverFailed`
+	seal_ecdsa_recover(
+		ctx,
+		signature_ptr: u32,
+		message_hash_ptr: u32,
+		output_ptr: u32
+	) -> ReturnCode => {
+		ctx.charge_gas(RuntimeToken::EcdsaRecovery)?;
+
+		let mut signature: [u8; 65] = [0; 65];
+		read_sandbox_memory_into_buf(ctx, signature_ptr, &mut signature)?;
+		let mut message_hash: [u8; 32] = [0; 32];
+		read_sandbox_memory_into_buf(ctx, message_hash_ptr, &mut message_hash)?;
+
+		match secp256k1_ecdsa_recover_compressed(&signature, &message_hash) {
+			Ok(public_key) => {
+				write_sandbox_memory(ctx, output_ptr, &public_key)?;
+				Ok(ReturnCode::Success)
+			},
+			Err(_) => Ok(ReturnCode::EcdsaRecoverFailed),
+		}
+	},
+
+	// Convert a compressed ECDSA public key into the Ethereum address it controls.
+	//
+	// The address consists of the last 20 bytes of the `keccak_256` hash of the
+	// uncompressed public key. It is written into the given output buffer.
+	//
+	// # Parameters
+	//
+	// - `key_ptr`: the pointer into the linear memory where the compressed public key
+	//              is placed. Should be 33 bytes long as returned by `seal_ecdsa_recover`.
+	// - `output_ptr`: the pointer into the linear memory where the output
+	//                 data is placed. The buffer should be 20 bytes long.
+	//                 The function will write the result directly into this buffer.
+	//
+	// # Errors
+	//
+	// `ReturnCode::EcdsaRecoverFailed`
+	seal_ecdsa_to_eth_address(ctx, key_ptr: u32, output_ptr: u32) -> ReturnCode => {
+		ctx.charge_gas(RuntimeToken::EcdsaToEthAddress)?;
+
+		let mut compressed_key: [u8; 33] = [0; 33];
+		read_sandbox_memory_into_buf(ctx, key_ptr, &mut compressed_key)?;
+
+		match ecdsa_to_eth_address(&compressed_key) {
+			Ok(address) => {
+				write_sandbox_memory(ctx, output_ptr, &address)?;
+				Ok(ReturnCode::Success)
+			},
+			Err(()) => Ok(ReturnCode::EcdsaRecoverFailed),
+		}
+	},
+
+	// Verify an sr25519 signature of the given message.
+	//
+	// # Parameters
+	//
+	// - `signature_ptr`: the pointer into the linear memory where the signature
+	//                    is placed. Should be 64 bytes long.
+	// - `pub_key_ptr`: the pointer into the linear memory where the public key
+	//                  is placed. Should be 32 bytes long.
+	// - `message_ptr`: the pointer into the linear memory where the message
+	//                  is placed.
+	// - `message_len`: the length of the message in bytes.
+	//
+	// # Errors
+	//
+	// `ReturnCode::Sr25519VerifyFailed`
+	seal_sr25519_verify(
+		ctx,
+		signature_ptr: u32,
+		pub_key_ptr: u32,
+		message_ptr: u32,
+		message_len: u32
+	) -> ReturnCode => {
+		ctx.charge_gas(RuntimeToken::Sr25519Verify(message_len))?;
+
+		let mut signature: [u8; 64] = [0; 64];
+		read_sandbox_memory_into_buf(ctx, signature_ptr, &mut signature)?;
+		let mut pub_key: [u8; 32] = [0; 32];
+		read_sandbox_memory_into_buf(ctx, pub_key_ptr, &mut pub_key)?;
+		let message = read_sandbox_memory(ctx, message_ptr, message_len)?;
+
+		let signature = sr25519::Signature::from_raw(signature);
+		if sr25519_verify(&signature, &message, &sr25519::Public::from_raw(pub_key)) {
+			Ok(ReturnCode::Success)
+		} else {
+			Ok(ReturnCode::Sr25519VerifyFailed)
+		}
+	},
+
+	// Verify an ed25519 signature of the given message.
+	//
+	// # Parameters
+	//
+	// - `signature_ptr`: the pointer into the linear memory where the signature
+	//                    is placed. Should be 64 bytes long.
+	// - `pub_key_ptr`: the pointer into the linear memory where the public key
+	//                  is placed. Should be 32 bytes long.
+	// - `message_ptr`: the pointer into the linear memory where the message
+	//                  is placed.
+	// - `message_len`: the length of the message in bytes.
+	//
+	// # Errors
+	//
+	// `ReturnCode::Ed25519VerifyFailed`
+	seal_ed25519_verify(
+		ctx,
+		signature_ptr: u32,
+		pub_key_ptr: u32,
+		message_ptr: u32,
+		message_len: u32
+	) -> ReturnCode => {
+		ctx.charge_gas(RuntimeToken::Ed25519Verify(message_len))?;
+
+		let mut signature: [u8; 64] = [0; 64];
+		read_sandbox_memory_into_buf(ctx, signature_ptr, &mut signature)?;
+		let mut pub_key: [u8; 32] = [0; 32];
+		read_sandbox_memory_into_buf(ctx, pub_key_ptr, &mut pub_key)?;
+		let message = read_sandbox_memory(ctx, message_ptr, message_len)?;
+
+		let signature = ed25519::Signature::from_raw(signature);
+		if ed25519_verify(&signature, &message, &ed25519::Public::from_raw(pub_key)) {
+			Ok(ReturnCode::Success)
+		} else {
+			Ok(ReturnCode::Ed25519VerifyFailed)
+		}
+	},
 );
 
+/// Returns the Ethereum address controlled by the given compressed ECDSA public key.
+///
+/// Fails if the key is not a valid point on the secp256k1 curve.
+pub fn ecdsa_to_eth_address(compressed_key: &[u8; 33]) -> Result<[u8; 20], ()> {
+	let public_key = secp256k1::PublicKey::parse_compressed(compressed_key).map_err(|_| ())?;
+	// The uncompressed key is prefixed with a tag byte that isn't part of the hash.
+	let hash = keccak_256(&public_key.serialize()[1..]);
+	let mut address = [0; 20];
+	address.copy_from_slice(&hash[12..]);
+	Ok(address)
+}
+
 /// Computes the given hash function on the supplied input.
 ///
 /// Reads from the sandboxed input buffer into an intermediate buffer.
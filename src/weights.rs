@@ -0,0 +1,1093 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Weights for the contracts pallet.
+//!
+//! PLACEHOLDERS: the values below were NOT generated by the benchmark CLI. They are hand written
+//! estimations which only follow the structure of the benchmarks in `benchmarking.rs`. They must
+//! be replaced before this pallet is used in production by running the `benchmark` command of a
+//! node that includes this pallet with `--pallet=pallet_contracts --extrinsic='*'` on the
+//! targeted hardware.
+//!
+//! The default instruction and host function costs of the `Schedule` are derived from the
+//! `instr_*` and `seal_*` weights (see `InstructionWeights::from_weight_info` and
+//! `HostFnWeights::from_weight_info`). The placeholders of these benchmarks reproduce the
+//! estimations the schedule used before.
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed by the dispatchables of this pallet.
+///
+/// The weight of `call` and `instantiate` only covers the overhead of the dispatchable.
+/// The supplied gas limit is added on top of it.
+pub trait WeightInfo {
+	fn update_schedule() -> Weight;
+	fn update_schedule_at() -> Weight;
+	fn put_code(n: u32) -> Weight;
+	fn reinstrument(n: u32) -> Weight;
+	fn instantiate() -> Weight;
+	fn call() -> Weight;
+	fn claim_surcharge() -> Weight;
+	fn deposit_rent() -> Weight;
+	fn restore_contract(n: u32, k: u32) -> Weight;
+	fn restore_contract_with_proof(n: u32, k: u32) -> Weight;
+	fn import_storage(n: u32, k: u32) -> Weight;
+	fn instr_const(r: u32) -> Weight;
+	fn instr_local(r: u32) -> Weight;
+	fn instr_global(r: u32) -> Weight;
+	fn instr_load(r: u32) -> Weight;
+	fn instr_store(r: u32) -> Weight;
+	fn instr_branch(r: u32) -> Weight;
+	fn instr_call(r: u32) -> Weight;
+	fn instr_call_indirect(r: u32) -> Weight;
+	fn instr_integer_comparison(r: u32) -> Weight;
+	fn instr_bit(r: u32) -> Weight;
+	fn instr_add(r: u32) -> Weight;
+	fn instr_mul(r: u32) -> Weight;
+	fn instr_div(r: u32) -> Weight;
+	fn instr_conversion(r: u32) -> Weight;
+	fn instr_nop(r: u32) -> Weight;
+	fn instr_current_memory(r: u32) -> Weight;
+	fn instr_grow_memory(r: u32) -> Weight;
+	fn seal_caller(r: u32) -> Weight;
+	fn seal_address(r: u32) -> Weight;
+	fn seal_origin(r: u32) -> Weight;
+	fn seal_caller_is_origin(r: u32) -> Weight;
+	fn seal_is_contract(r: u32) -> Weight;
+	fn seal_code_hash(r: u32) -> Weight;
+	fn seal_own_code_hash(r: u32) -> Weight;
+	fn seal_gas_left(r: u32) -> Weight;
+	fn seal_balance(r: u32) -> Weight;
+	fn seal_value_transferred(r: u32) -> Weight;
+	fn seal_minimum_balance(r: u32) -> Weight;
+	fn seal_tombstone_deposit(r: u32) -> Weight;
+	fn seal_rent_allowance(r: u32) -> Weight;
+	fn seal_block_number(r: u32) -> Weight;
+	fn seal_now(r: u32) -> Weight;
+	fn seal_weight_to_fee(r: u32) -> Weight;
+	fn seal_input(r: u32) -> Weight;
+	fn seal_return(r: u32) -> Weight;
+	fn seal_return_per_kb(n: u32) -> Weight;
+	fn seal_terminate(r: u32) -> Weight;
+	fn seal_restore_to(d: u32) -> Weight;
+	fn seal_random(r: u32) -> Weight;
+	fn seal_deposit_event(r: u32) -> Weight;
+	fn seal_deposit_event_per_topic_and_kb(t: u32, n: u32) -> Weight;
+	fn seal_set_rent_allowance(r: u32) -> Weight;
+	fn seal_set_storage(r: u32) -> Weight;
+	fn seal_set_storage_per_kb(n: u32) -> Weight;
+	fn seal_clear_storage(r: u32) -> Weight;
+	fn seal_get_storage(r: u32) -> Weight;
+	fn seal_get_storage_per_kb(n: u32) -> Weight;
+	fn seal_contains_storage(r: u32) -> Weight;
+	fn seal_take_storage(r: u32) -> Weight;
+	fn seal_take_storage_per_kb(n: u32) -> Weight;
+	fn seal_storage_next_key(r: u32) -> Weight;
+	fn seal_transfer(r: u32) -> Weight;
+	fn seal_call(r: u32) -> Weight;
+	fn seal_instantiate(r: u32) -> Weight;
+	fn seal_println(r: u32) -> Weight;
+	fn seal_hash_sha2_256(r: u32) -> Weight;
+	fn seal_hash_sha2_256_per_kb(n: u32) -> Weight;
+	fn seal_hash_keccak_256(r: u32) -> Weight;
+	fn seal_hash_keccak_256_per_kb(n: u32) -> Weight;
+	fn seal_hash_blake2_256(r: u32) -> Weight;
+	fn seal_hash_blake2_256_per_kb(n: u32) -> Weight;
+	fn seal_hash_blake2_128(r: u32) -> Weight;
+	fn seal_hash_blake2_128_per_kb(n: u32) -> Weight;
+	fn seal_ecdsa_recover(r: u32) -> Weight;
+	fn seal_ecdsa_to_eth_address(r: u32) -> Weight;
+	fn seal_sr25519_verify(r: u32) -> Weight;
+	fn seal_sr25519_verify_per_kb(n: u32) -> Weight;
+	fn seal_ed25519_verify(r: u32) -> Weight;
+	fn seal_ed25519_verify_per_kb(n: u32) -> Weight;
+}
+
+/// Placeholder weights for the contracts pallet. See the module documentation.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Trait> WeightInfo for SubstrateWeight<T> {
+	fn update_schedule() -> Weight {
+		(35_214_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	fn update_schedule_at() -> Weight {
+		(38_402_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn put_code(n: u32) -> Weight {
+		(0 as Weight)
+			.saturating_add((109_238_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn reinstrument(n: u32) -> Weight {
+		(12_437_000 as Weight)
+			.saturating_add((41_756_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn instantiate() -> Weight {
+		(243_931_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(7 as Weight))
+			.saturating_add(T::DbWeight::get().writes(4 as Weight))
+	}
+	fn call() -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn claim_surcharge() -> Weight {
+		(508_020_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(3 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	fn deposit_rent() -> Weight {
+		(127_506_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn restore_contract(n: u32, k: u32) -> Weight {
+		(183_605_000 as Weight)
+			.saturating_add((21_403_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add((3_254_000 as Weight).saturating_mul(k as Weight))
+			.saturating_add(T::DbWeight::get().reads(3 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+			.saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(n as Weight)))
+	}
+	fn restore_contract_with_proof(n: u32, k: u32) -> Weight {
+		(191_338_000 as Weight)
+			.saturating_add((48_917_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add((3_275_000 as Weight).saturating_mul(k as Weight))
+			.saturating_add(T::DbWeight::get().reads(3 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+			.saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(n as Weight)))
+	}
+	fn import_storage(n: u32, k: u32) -> Weight {
+		(97_214_000 as Weight)
+			.saturating_add((19_862_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add((3_241_000 as Weight).saturating_mul(k as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(n as Weight)))
+	}
+	fn instr_const(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((700_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn instr_local(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((700_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn instr_global(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((2_200_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn instr_load(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((2_500_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn instr_store(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((2_900_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn instr_branch(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((1_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn instr_call(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((2_500_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn instr_call_indirect(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((3_900_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn instr_integer_comparison(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((1_500_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn instr_bit(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((1_500_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn instr_add(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((1_500_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn instr_mul(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((1_650_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn instr_div(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((2_500_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn instr_conversion(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((1_100_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn instr_nop(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((300_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn instr_current_memory(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((800_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn instr_grow_memory(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((15_700_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_caller(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((10_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_address(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((10_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_origin(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((10_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_caller_is_origin(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((10_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_is_contract(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((100_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_code_hash(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((100_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_own_code_hash(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((100_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_gas_left(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((10_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_balance(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((10_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_value_transferred(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((10_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_minimum_balance(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((10_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_tombstone_deposit(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((10_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_rent_allowance(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((10_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_block_number(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((10_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_now(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((10_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_weight_to_fee(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((10_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_input(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((500_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_return(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((500_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_return_per_kb(n: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((512_000_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_terminate(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((50_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_restore_to(d: u32) -> Weight {
+		(270_714_000 as Weight)
+			.saturating_add((100_000_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_random(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((100_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_deposit_event(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((10_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_deposit_event_per_topic_and_kb(t: u32, n: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((500_000 as Weight).saturating_mul(t as Weight))
+			.saturating_add((512_000_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_set_rent_allowance(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((10_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_set_storage(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((100_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_set_storage_per_kb(n: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((512_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_clear_storage(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((100_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_get_storage(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((100_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_get_storage_per_kb(n: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((512_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_contains_storage(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((100_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_take_storage(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((200_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_take_storage_per_kb(n: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((512_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_storage_next_key(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((100_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_transfer(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((10_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_call(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((10_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_instantiate(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((10_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_println(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((10_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_hash_sha2_256(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((10_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_hash_sha2_256_per_kb(n: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((512_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_hash_keccak_256(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((10_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_hash_keccak_256_per_kb(n: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((512_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_hash_blake2_256(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((10_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_hash_blake2_256_per_kb(n: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((512_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_hash_blake2_128(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((10_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_hash_blake2_128_per_kb(n: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((512_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_ecdsa_recover(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((1_000_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_ecdsa_to_eth_address(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((100_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_sr25519_verify(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((1_000_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_sr25519_verify_per_kb(n: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((512_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_ed25519_verify(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((1_000_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn seal_ed25519_verify_per_kb(n: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((512_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+	fn update_schedule() -> Weight {
+		(35_214_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
+	fn update_schedule_at() -> Weight {
+		(38_402_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn put_code(n: u32) -> Weight {
+		(0 as Weight)
+			.saturating_add((109_238_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn reinstrument(n: u32) -> Weight {
+		(12_437_000 as Weight)
+			.saturating_add((41_756_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn instantiate() -> Weight {
+		(243_931_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(7 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(4 as Weight))
+	}
+	fn call() -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn claim_surcharge() -> Weight {
+		(508_020_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(3 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
+	fn deposit_rent() -> Weight {
+		(127_506_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn restore_contract(n: u32, k: u32) -> Weight {
+		(183_605_000 as Weight)
+			.saturating_add((21_403_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add((3_254_000 as Weight).saturating_mul(k as Weight))
+			.saturating_add(RocksDbWeight::get().reads(3 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((1 as Weight).saturating_mul(n as Weight)))
+	}
+	fn restore_contract_with_proof(n: u32, k: u32) -> Weight {
+		(191_338_000 as Weight)
+			.saturating_add((48_917_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add((3_275_000 as Weight).saturating_mul(k as Weight))
+			.saturating_add(RocksDbWeight::get().reads(3 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((1 as Weight).saturating_mul(n as Weight)))
+	}
+	fn import_storage(n: u32, k: u32) -> Weight {
+		(97_214_000 as Weight)
+			.saturating_add((19_862_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add((3_241_000 as Weight).saturating_mul(k as Weight))
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((1 as Weight).saturating_mul(n as Weight)))
+	}
+	fn instr_const(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((700_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn instr_local(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((700_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn instr_global(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((2_200_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn instr_load(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((2_500_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn instr_store(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((2_900_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn instr_branch(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((1_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn instr_call(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((2_500_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn instr_call_indirect(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((3_900_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn instr_integer_comparison(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((1_500_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn instr_bit(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((1_500_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn instr_add(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((1_500_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn instr_mul(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((1_650_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn instr_div(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((2_500_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn instr_conversion(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((1_100_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn instr_nop(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((300_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn instr_current_memory(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((800_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn instr_grow_memory(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((15_700_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_caller(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((10_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_address(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((10_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_origin(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((10_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_caller_is_origin(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((10_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_is_contract(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((100_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_code_hash(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((100_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_own_code_hash(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((100_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_gas_left(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((10_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_balance(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((10_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_value_transferred(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((10_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_minimum_balance(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((10_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_tombstone_deposit(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((10_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_rent_allowance(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((10_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_block_number(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((10_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_now(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((10_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_weight_to_fee(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((10_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_input(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((500_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_return(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((500_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_return_per_kb(n: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((512_000_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_terminate(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((50_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_restore_to(d: u32) -> Weight {
+		(270_714_000 as Weight)
+			.saturating_add((100_000_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_random(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((100_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_deposit_event(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((10_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_deposit_event_per_topic_and_kb(t: u32, n: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((500_000 as Weight).saturating_mul(t as Weight))
+			.saturating_add((512_000_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_set_rent_allowance(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((10_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_set_storage(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((100_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_set_storage_per_kb(n: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((512_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_clear_storage(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((100_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_get_storage(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((100_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_get_storage_per_kb(n: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((512_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_contains_storage(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((100_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_take_storage(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((200_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_take_storage_per_kb(n: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((512_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_storage_next_key(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((100_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_transfer(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((10_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_call(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((10_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_instantiate(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((10_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_println(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((10_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_hash_sha2_256(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((10_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_hash_sha2_256_per_kb(n: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((512_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_hash_keccak_256(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((10_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_hash_keccak_256_per_kb(n: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((512_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_hash_blake2_256(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((10_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_hash_blake2_256_per_kb(n: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((512_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_hash_blake2_128(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((10_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_hash_blake2_128_per_kb(n: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((512_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_ecdsa_recover(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((1_000_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_ecdsa_to_eth_address(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((100_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_sr25519_verify(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((1_000_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_sr25519_verify_per_kb(n: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((512_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_ed25519_verify(r: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((1_000_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn seal_ed25519_verify_per_kb(n: u32) -> Weight {
+		(220_714_000 as Weight)
+			.saturating_add((512_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+}